@@ -0,0 +1,145 @@
+use crate::complex::Complex;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::{Index, IndexMut};
+
+/// Spherical harmonic coefficients `a_lm` of a real map, for `0 <= m <= l <= lmax`.
+///
+/// Coefficients with negative `m` are not stored, since a real map has
+/// `a_l(-m) = (-1)^m conj(a_lm)`. The storage order matches healpy: all `l` for `m = 0`, then
+/// all `l >= 1` for `m = 1`, and so on.
+///
+/// The harmonics are orthonormal and include the Condon-Shortley phase `(-1)^m`, as in
+/// HEALPix and healpy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alm {
+    lmax: usize,
+    coefficients: Vec<Complex>,
+}
+
+impl Alm {
+    /// Creates zero coefficients up to `lmax`.
+    pub fn new(lmax: usize) -> Self {
+        Self {
+            lmax,
+            coefficients: vec![Complex::ZERO; Self::size(lmax)],
+        }
+    }
+
+    /// Wraps coefficients stored in the healpy order.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidAlmSize`] unless `coefficients` holds exactly
+    /// `(lmax + 1) * (lmax + 2) / 2` values.
+    pub fn from_vec(lmax: usize, coefficients: Vec<Complex>) -> crate::Result<Self> {
+        if coefficients.len() != Self::size(lmax) {
+            return Err(crate::Error::InvalidAlmSize);
+        }
+        Ok(Self { lmax, coefficients })
+    }
+
+    /// Returns the number of coefficients stored up to `lmax`.
+    pub fn size(lmax: usize) -> usize {
+        (lmax + 1) * (lmax + 2) / 2
+    }
+
+    /// Returns the largest multipole `l`.
+    pub fn lmax(&self) -> usize {
+        self.lmax
+    }
+
+    /// Returns the coefficients in the healpy order.
+    pub fn coefficients(&self) -> &[Complex] {
+        &self.coefficients
+    }
+
+    /// Returns the coefficients mutably, in the healpy order.
+    pub fn coefficients_mut(&mut self) -> &mut [Complex] {
+        &mut self.coefficients
+    }
+
+    /// Returns the position of `a_lm` in [`Self::coefficients`].
+    pub fn index_of(&self, l: usize, m: usize) -> usize {
+        m * (2 * self.lmax + 1 - m) / 2 + l
+    }
+
+    /// Returns `a_lm`, or `None` unless `m <= l <= lmax`.
+    pub fn get(&self, l: usize, m: usize) -> Option<Complex> {
+        (m <= l && l <= self.lmax).then(|| self.coefficients[self.index_of(l, m)])
+    }
+
+    /// Returns the angular power spectrum `C_l` for `l` up to `lmax`, like healpy's
+    /// `alm2cl`.
+    pub fn spectrum(&self) -> Vec<f64> {
+        self.cross_spectrum(self)
+    }
+
+    /// Returns the cross power spectrum `C_l = Σ_m Re(a_lm conj(b_lm)) / (2l + 1)` of two sets
+    /// of coefficients, up to the smaller `lmax`.
+    pub fn cross_spectrum(&self, other: &Alm) -> Vec<f64> {
+        let lmax = self.lmax.min(other.lmax);
+        (0..=lmax)
+            .map(|l| {
+                let term = |m: usize| {
+                    let product = self[(l, m)] * other[(l, m)].conj();
+                    product.re
+                };
+                // Each m > 0 also stands for -m, which contributes the same real part.
+                let sum = term(0) + 2.0 * (1..=l).map(term).sum::<f64>();
+                sum / (2 * l + 1) as f64
+            })
+            .collect()
+    }
+}
+
+impl Index<(usize, usize)> for Alm {
+    type Output = Complex;
+
+    /// # Panics
+    /// Panics unless `m <= l <= lmax`.
+    fn index(&self, (l, m): (usize, usize)) -> &Complex {
+        assert!(m <= l && l <= self.lmax, "a_lm out of range");
+        &self.coefficients[self.index_of(l, m)]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Alm {
+    /// # Panics
+    /// Panics unless `m <= l <= lmax`.
+    fn index_mut(&mut self, (l, m): (usize, usize)) -> &mut Complex {
+        assert!(m <= l && l <= self.lmax, "a_lm out of range");
+        let index = self.index_of(l, m);
+        &mut self.coefficients[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storage_follows_the_healpy_order() {
+        let alm = Alm::new(3);
+        assert_eq!(alm.coefficients().len(), 10);
+        let alm = &alm;
+        let order: Vec<_> = (0..=3)
+            .flat_map(|m| (m..=3).map(move |l| alm.index_of(l, m)))
+            .collect();
+        assert_eq!(order, (0..10).collect::<Vec<_>>());
+        assert_eq!(alm.get(2, 3), None);
+        assert!(Alm::from_vec(3, vec![Complex::ZERO; 9]).is_err());
+    }
+
+    #[test]
+    fn spectrum_averages_over_m() {
+        let mut a = Alm::new(2);
+        a[(1, 0)] = Complex::new(3.0, 0.0);
+        a[(1, 1)] = Complex::new(0.0, 2.0);
+        a[(2, 2)] = Complex::new(1.0, 1.0);
+        assert_eq!(a.spectrum(), [0.0, 17.0 / 3.0, 4.0 / 5.0]);
+
+        let mut b = Alm::new(1);
+        b[(1, 1)] = Complex::new(0.0, 1.0);
+        assert_eq!(a.cross_spectrum(&b), [0.0, 4.0 / 3.0]);
+    }
+}
@@ -0,0 +1,132 @@
+/// A 3D vector type usable with [`crate::Healpix::vec_to_pixel`] and
+/// [`crate::Healpix::pixel_to_vec`], which work with Cartesian directions instead of angles.
+///
+/// `x` points to `theta = pi/2, phi = 0`, `y` to `theta = pi/2, phi = pi/2` and `z` to the
+/// north pole. It is implemented for `[f64; 3]` and `(f64, f64, f64)`, for nalgebra's
+/// `Vector3` with the `nalgebra` feature, and for glam's `DVec3` and `Vec3` with the `glam`
+/// feature.
+pub trait CartesianVector {
+    /// Returns the `x`, `y` and `z` components.
+    fn to_xyz(&self) -> [f64; 3];
+
+    /// Creates a vector from its `x`, `y` and `z` components.
+    fn from_xyz(xyz: [f64; 3]) -> Self;
+}
+
+impl CartesianVector for [f64; 3] {
+    #[inline]
+    fn to_xyz(&self) -> [f64; 3] {
+        *self
+    }
+
+    #[inline]
+    fn from_xyz(xyz: [f64; 3]) -> Self {
+        xyz
+    }
+}
+
+impl CartesianVector for (f64, f64, f64) {
+    #[inline]
+    fn to_xyz(&self) -> [f64; 3] {
+        [self.0, self.1, self.2]
+    }
+
+    #[inline]
+    fn from_xyz([x, y, z]: [f64; 3]) -> Self {
+        (x, y, z)
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl CartesianVector for nalgebra::Vector3<f64> {
+    #[inline]
+    fn to_xyz(&self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    #[inline]
+    fn from_xyz([x, y, z]: [f64; 3]) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl CartesianVector for nalgebra::Vector3<f32> {
+    #[inline]
+    fn to_xyz(&self) -> [f64; 3] {
+        [self.x as f64, self.y as f64, self.z as f64]
+    }
+
+    #[inline]
+    fn from_xyz([x, y, z]: [f64; 3]) -> Self {
+        Self::new(x as f32, y as f32, z as f32)
+    }
+}
+
+#[cfg(feature = "glam")]
+impl CartesianVector for glam::DVec3 {
+    #[inline]
+    fn to_xyz(&self) -> [f64; 3] {
+        self.to_array()
+    }
+
+    #[inline]
+    fn from_xyz(xyz: [f64; 3]) -> Self {
+        Self::from_array(xyz)
+    }
+}
+
+#[cfg(feature = "glam")]
+impl CartesianVector for glam::Vec3 {
+    #[inline]
+    fn to_xyz(&self) -> [f64; 3] {
+        self.as_dvec3().to_array()
+    }
+
+    #[inline]
+    fn from_xyz(xyz: [f64; 3]) -> Self {
+        glam::DVec3::from_array(xyz).as_vec3()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DynamicHealpix, Healpix, Nested, Pixel};
+
+    #[test]
+    fn tuples_and_arrays_agree() {
+        let healpix = DynamicHealpix::new(8).unwrap();
+        let pixel = Pixel::<Nested>::from_u64(300);
+        let array: [f64; 3] = healpix.pixel_to_vec(pixel).unwrap();
+        let tuple: (f64, f64, f64) = healpix.pixel_to_vec(pixel).unwrap();
+        assert_eq!(array, tuple.to_xyz());
+        assert_eq!(healpix.vec_to_pixel::<Nested, _>(&tuple), pixel);
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn nalgebra_vectors_round_trip() {
+        let healpix = DynamicHealpix::new(64).unwrap();
+        for pixel in [0, 1234, 49151].map(Pixel::<Nested>::from_u64) {
+            let v: nalgebra::Vector3<f64> = healpix.pixel_to_vec(pixel).unwrap();
+            assert!((v.norm() - 1.0).abs() < 1e-15);
+            assert_eq!(healpix.vec_to_pixel::<Nested, _>(&v), pixel);
+            let v: nalgebra::Vector3<f32> = healpix.pixel_to_vec(pixel).unwrap();
+            assert_eq!(healpix.vec_to_pixel::<Nested, _>(&v), pixel);
+        }
+    }
+
+    #[cfg(feature = "glam")]
+    #[test]
+    fn glam_vectors_round_trip() {
+        let healpix = DynamicHealpix::new(64).unwrap();
+        for pixel in [0, 1234, 49151].map(Pixel::<Nested>::from_u64) {
+            let v: glam::DVec3 = healpix.pixel_to_vec(pixel).unwrap();
+            assert!((v.length() - 1.0).abs() < 1e-15);
+            assert_eq!(healpix.vec_to_pixel::<Nested, _>(&v), pixel);
+            let v: glam::Vec3 = healpix.pixel_to_vec(pixel).unwrap();
+            assert_eq!(healpix.vec_to_pixel::<Nested, _>(&v), pixel);
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! Checks every [`NumberingScheme`] against the HEALPix C++ fixtures in
+//! `tests/fixtures/healpix/cpp`, at every resolution from 1 to 2^20.
+//!
+//! The fixtures are checked in, so these tests run offline. `generate.py` next to them
+//! computes them with healpy when it is installed, and otherwise with its own Python
+//! transcription of the HEALPix C++ algorithms; the first line of each fixture records which
+//! one produced it. The checked-in ones come from the transcription, which has not been checked
+//! against healpy, so they show that the crate follows those algorithms bit for bit, not that
+//! it agrees with healpy. Points on pixel edges, at the poles, at z = ±2/3 and at φ = 0 and 2π
+//! are included.
+
+use crate::dynamic_healpix::DynamicHealpix;
+use crate::healpix::Healpix;
+use crate::nested::Nested;
+use crate::numbering_scheme::NumberingScheme;
+#[cfg(feature = "alloc")]
+use crate::numbering_scheme::edge_loc;
+use crate::pixel::Pixel;
+use crate::ring::Ring;
+use core::str::Split;
+
+const ANG2PIX: &str = include_str!("../tests/fixtures/healpix/cpp/ang2pix.csv");
+const PIX2ANG: &str = include_str!("../tests/fixtures/healpix/cpp/pix2ang.csv");
+const NEIGHBOURS: &str = include_str!("../tests/fixtures/healpix/cpp/neighbours.csv");
+const NEST2RING: &str = include_str!("../tests/fixtures/healpix/cpp/nest2ring.csv");
+const RING_INFO: &str = include_str!("../tests/fixtures/healpix/cpp/ring_info.csv");
+const INTERPOLATION: &str = include_str!("../tests/fixtures/healpix/cpp/interpolation.csv");
+#[cfg(feature = "alloc")]
+const BOUNDARIES: &str = include_str!("../tests/fixtures/healpix/cpp/boundaries.csv");
+
+/// Returns the fields of every row of a fixture, skipping comments.
+fn rows(fixture: &'static str) -> impl Iterator<Item = Split<'static, char>> {
+    fixture
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| line.split(','))
+}
+
+/// Returns the fields of the rows of a fixture for scheme `N`, after the scheme column.
+fn scheme_rows<N: NumberingScheme>(
+    fixture: &'static str,
+) -> impl Iterator<Item = Split<'static, char>> {
+    rows(fixture).filter_map(|mut fields| (fields.next() == Some(N::ORDERING)).then_some(fields))
+}
+
+/// Whether `actual`, a result of `acos`, `atan2`, `sin` or `cos`, matches `expected`.
+///
+/// The fixtures were computed with the platform's math library, which `std` uses too. Without
+/// `std` these functions come from `libm`, which rounds some results differently by an ulp,
+/// such as `acos` for the centre of Nested pixel 7 at nside 4. Every other value must match
+/// exactly.
+fn math_matches(actual: f64, expected: f64) -> bool {
+    #[cfg(feature = "std")]
+    return actual == expected;
+    #[cfg(not(feature = "std"))]
+    return (actual.to_bits() as i64 - expected.to_bits() as i64).unsigned_abs() <= 1;
+}
+
+/// Whether `actual`, an interpolation weight at resolution `nside`, matches `expected`.
+///
+/// The weights divide colatitudes by the spacing of the rings, about `1 / nside`, so without
+/// `std` an ulp of difference in `acos` or `atan2` moves a weight by up to a few `nside` ulps
+/// of 1. With `std` they must match exactly.
+#[cfg_attr(feature = "std", allow(unused_variables))]
+fn weight_matches(nside: u32, actual: f64, expected: f64) -> bool {
+    #[cfg(feature = "std")]
+    return actual == expected;
+    #[cfg(not(feature = "std"))]
+    return (actual - expected).abs() <= 8.0 * nside as f64 * f64::EPSILON;
+}
+
+/// Parses the next field of a row.
+fn next<T: core::str::FromStr>(fields: &mut Split<'_, char>) -> T
+where
+    T::Err: core::fmt::Debug,
+{
+    fields.next().expect("missing field").parse().unwrap()
+}
+
+fn check_ang2pix<N: NumberingScheme>() -> usize {
+    let mut count = 0;
+    for mut fields in rows(ANG2PIX) {
+        let nside: u32 = next(&mut fields);
+        let theta: f64 = next(&mut fields);
+        let phi: f64 = next(&mut fields);
+        let nested: u64 = next(&mut fields);
+        let ring: u64 = next(&mut fields);
+        let expected = if N::ORDERING == Nested::ORDERING {
+            nested
+        } else {
+            ring
+        };
+        assert_eq!(
+            N::angle_to_pixel::<N>(nside, theta, phi).as_u64(),
+            expected,
+            "ang2pix nside {nside}, theta {theta}, phi {phi}"
+        );
+        count += 1;
+    }
+    count
+}
+
+fn check_pix2ang<N: NumberingScheme>() -> usize {
+    let mut count = 0;
+    for mut fields in scheme_rows::<N>(PIX2ANG) {
+        let nside: u32 = next(&mut fields);
+        let pixel: u64 = next(&mut fields);
+        let theta: f64 = next(&mut fields);
+        let phi: f64 = next(&mut fields);
+        let (actual_theta, actual_phi) =
+            N::pixel_to_angle::<N>(nside, Pixel::from_u64(pixel)).unwrap();
+        assert!(
+            math_matches(actual_theta, theta) && actual_phi == phi,
+            "pix2ang nside {nside}, pixel {pixel}: ({actual_theta}, {actual_phi}) is not \
+             ({theta}, {phi})"
+        );
+        count += 1;
+    }
+    count
+}
+
+fn check_neighbours<N: NumberingScheme>() -> usize {
+    let mut count = 0;
+    for mut fields in scheme_rows::<N>(NEIGHBOURS) {
+        let nside: u32 = next(&mut fields);
+        let pixel: u64 = next(&mut fields);
+        // HEALPix C++ starts at SW and ends at S, which comes first here.
+        let mut expected: [Option<u64>; 8] =
+            core::array::from_fn(|_| u64::try_from(next::<i64>(&mut fields)).ok());
+        expected.rotate_right(1);
+        let actual = N::neighbours::<N>(nside, Pixel::from_u64(pixel)).unwrap();
+        assert_eq!(
+            actual.map(|neighbour| neighbour.map(|n| n.as_u64())),
+            expected,
+            "neighbours nside {nside}, pixel {pixel}"
+        );
+        count += 1;
+    }
+    count
+}
+
+fn check_nest2ring<N: NumberingScheme>() -> usize {
+    let mut count = 0;
+    for mut fields in rows(NEST2RING) {
+        let nside: u32 = next(&mut fields);
+        let nested = Pixel::<Nested>::from_u64(next(&mut fields));
+        let ring = Pixel::<Ring>::from_u64(next(&mut fields));
+        let healpix = DynamicHealpix::new(nside).unwrap();
+        let expected = if N::ORDERING == Nested::ORDERING {
+            nested.as_u64()
+        } else {
+            ring.as_u64()
+        };
+        let from_nested: Pixel<N> = healpix.convert_pixel(nested).unwrap();
+        let from_ring: Pixel<N> = healpix.convert_pixel(ring).unwrap();
+        assert_eq!(from_nested.as_u64(), expected, "nest2ring nside {nside}");
+        assert_eq!(from_ring.as_u64(), expected, "ring2nest nside {nside}");
+        count += 1;
+    }
+    count
+}
+
+#[cfg(feature = "alloc")]
+fn check_boundaries<N: NumberingScheme>() -> usize {
+    let mut count = 0;
+    for mut fields in scheme_rows::<N>(BOUNDARIES) {
+        let nside: u32 = next(&mut fields);
+        let pixel: u64 = next(&mut fields);
+        // HEALPix C++ reports vectors, which are compared before the conversion to angles.
+        let points = N::pixel_boundary::<N>(nside, Pixel::from_u64(pixel), 2).unwrap();
+        let hpd = N::pixel_to_hpd(nside, pixel);
+        for (index, point) in points.into_iter().enumerate() {
+            let (z, phi, sin_theta) = edge_loc(nside, &hpd, index / 2, index % 2, 2);
+            assert_eq!(point, (Ring::loc_to_theta(z, sin_theta), phi));
+            let expected: [f64; 3] = core::array::from_fn(|_| next(&mut fields));
+            let actual = Ring::loc_to_vec(z, phi, sin_theta);
+            assert!(
+                math_matches(actual[0], expected[0])
+                    && math_matches(actual[1], expected[1])
+                    && actual[2] == expected[2],
+                "boundaries nside {nside}, pixel {pixel}: {actual:?} is not {expected:?}"
+            );
+        }
+        count += 1;
+    }
+    count
+}
+
+fn check_interpolation<N: NumberingScheme>() -> usize {
+    let mut count = 0;
+    for mut fields in rows(INTERPOLATION) {
+        let nside: u32 = next(&mut fields);
+        let theta: f64 = next(&mut fields);
+        let phi: f64 = next(&mut fields);
+        let healpix = DynamicHealpix::new(nside).unwrap();
+        let ring_pixels: [u64; 4] = core::array::from_fn(|_| next(&mut fields));
+        let expected_weights: [f64; 4] = core::array::from_fn(|_| next(&mut fields));
+        let expected_pixels = ring_pixels.map(|pixel| {
+            healpix
+                .convert_pixel::<Ring, N>(Pixel::from_u64(pixel))
+                .unwrap()
+        });
+        let (pixels, weights) = healpix.get_interpolation_weights::<N>(theta, phi);
+        assert!(
+            pixels == expected_pixels
+                && weights
+                    .iter()
+                    .zip(expected_weights)
+                    .all(|(actual, expected)| weight_matches(nside, *actual, expected)),
+            "interpolation nside {nside}, theta {theta}, phi {phi}: {:?} {weights:?}",
+            pixels.map(|pixel| pixel.as_u64())
+        );
+        count += 1;
+    }
+    count
+}
+
+/// Runs every check against scheme `N`. Each one must find rows for the scheme, so that a
+/// truncated fixture cannot pass silently.
+fn check_scheme<N: NumberingScheme>() {
+    assert_ne!(check_ang2pix::<N>(), 0);
+    assert_ne!(check_pix2ang::<N>(), 0);
+    assert_ne!(check_neighbours::<N>(), 0);
+    assert_ne!(check_nest2ring::<N>(), 0);
+    assert_ne!(check_interpolation::<N>(), 0);
+    #[cfg(feature = "alloc")]
+    assert_ne!(check_boundaries::<N>(), 0);
+}
+
+#[test]
+fn nested_should_match_the_cpp_fixtures() {
+    check_scheme::<Nested>();
+}
+
+#[test]
+fn ring_should_match_the_cpp_fixtures() {
+    check_scheme::<Ring>();
+}
+
+#[test]
+fn ring_info_should_match_the_cpp_fixtures() {
+    let mut count = 0;
+    for mut fields in rows(RING_INFO) {
+        let nside: i64 = next(&mut fields);
+        let ring: i64 = next(&mut fields);
+        let info = Ring::ring_info(nside, ring);
+        let expected = (
+            next::<i64>(&mut fields),
+            next::<i64>(&mut fields),
+            next::<f64>(&mut fields),
+            next::<u8>(&mut fields) == 1,
+        );
+        assert_eq!(
+            (info.first_pixel, info.pixel_count, info.z, info.shifted),
+            expected,
+            "ring info nside {nside}, ring {ring}"
+        );
+        count += 1;
+    }
+    assert_ne!(count, 0);
+}
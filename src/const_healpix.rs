@@ -1,3 +1,4 @@
+use crate::dynamic_healpix::DynamicHealpix;
 use crate::healpix::Healpix;
 
 /// A constant generic struct representing a HEALPix (Hierarchical Equal Area isoLatitude
@@ -10,7 +11,7 @@ use crate::healpix::Healpix;
 ///
 /// # Type Parameters
 /// * `FACE_RESOLUTION` - A `u32` constant specifying the resolution of the HEALPix face.
-///                       Higher values indicate finer levels of detail.
+///   Higher values indicate finer levels of detail.
 ///
 /// # Example
 /// ```
@@ -30,7 +31,8 @@ impl<const FACE_RESOLUTION: u32> ConstHealpix<FACE_RESOLUTION> {
     /// the function will panic at compile time with an appropriate error message.
     ///
     /// # Panics
-    /// Panics if `FACE_RESOLUTION` is not a power of two.
+    /// Panics if `FACE_RESOLUTION` is not a power of two, or exceeds
+    /// [`DynamicHealpix::MAX_FACE_RESOLUTION`].
     ///
     /// # Returns
     /// A new instance of the struct.
@@ -46,20 +48,52 @@ impl<const FACE_RESOLUTION: u32> ConstHealpix<FACE_RESOLUTION> {
             FACE_RESOLUTION.is_power_of_two(),
             "FACE_RESOLUTION must be a power of two"
         );
+        assert!(
+            FACE_RESOLUTION <= DynamicHealpix::MAX_FACE_RESOLUTION,
+            "FACE_RESOLUTION must not exceed 2^29"
+        );
+        Self { _private: () }
+    }
+
+    /// Creates a grid for use with [`crate::Ring`] ordering only, which allows any
+    /// `FACE_RESOLUTION` from 1 to [`DynamicHealpix::MAX_FACE_RESOLUTION`]. See
+    /// [`DynamicHealpix::new_for`].
+    ///
+    /// # Panics
+    /// Panics if `FACE_RESOLUTION` is 0 or exceeds [`DynamicHealpix::MAX_FACE_RESOLUTION`].
+    ///
+    /// # Example
+    /// ```
+    /// use realpix::ConstHealpix;
+    ///
+    /// const HEALPIX: ConstHealpix<3000> = ConstHealpix::new_ring();
+    /// ```
+    pub const fn new_ring() -> Self {
+        assert!(FACE_RESOLUTION > 0, "FACE_RESOLUTION must be positive");
+        assert!(
+            FACE_RESOLUTION <= DynamicHealpix::MAX_FACE_RESOLUTION,
+            "FACE_RESOLUTION must not exceed 2^29"
+        );
         Self { _private: () }
     }
 }
 
+impl<const FACE_RESOLUTION: u32> Default for ConstHealpix<FACE_RESOLUTION> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<const FACE_RESOLUTION: u32> Healpix for ConstHealpix<FACE_RESOLUTION> {
     fn face_resolution(&self) -> u32 {
         FACE_RESOLUTION
     }
 
-    fn pixels_per_face(&self) -> u32 {
-        FACE_RESOLUTION * FACE_RESOLUTION
+    fn pixels_per_face(&self) -> u64 {
+        FACE_RESOLUTION as u64 * FACE_RESOLUTION as u64
     }
 
-    fn total_pixels(&self) -> u32 {
-        12 * (FACE_RESOLUTION * FACE_RESOLUTION)
+    fn total_pixels(&self) -> u64 {
+        12 * self.pixels_per_face()
     }
 }
@@ -1,4 +1,8 @@
 use crate::Healpix;
+use crate::HierarchicalPixel;
+use crate::Nested;
+use crate::NumberingScheme;
+use crate::numbering_scheme::check_face_resolution;
 
 /// A struct representing a dynamic HEALPix (Hierarchical Equal Area isoLatitude Pixelization) grid.
 ///
@@ -30,10 +34,45 @@ pub struct DynamicHealpix {
 }
 
 impl DynamicHealpix {
+    /// The largest supported face resolution, `2^29`, as in the HEALPix C++ library. Pixel
+    /// indices then still fit in 64 bits.
+    pub const MAX_FACE_RESOLUTION: u32 = 1 << HierarchicalPixel::MAX_DEPTH;
+
+    /// Creates a grid with `face_resolution` pixels along each side of a base face.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::ResolutionTooLarge`] if `face_resolution` exceeds
+    /// [`Self::MAX_FACE_RESOLUTION`] and [`crate::Error::InvalidFaceResolution`] if it is not a
+    /// power of two.
     pub fn new(face_resolution: u32) -> crate::Result<Self> {
-        if !face_resolution.is_power_of_two() {
-            return Err(crate::Error::InvalidFaceResolution);
-        }
+        Self::new_for::<Nested>(face_resolution)
+    }
+
+    /// Creates a grid for use with numbering scheme `N` only, which allows any resolution
+    /// `N` supports: [`crate::Ring`] accepts every `face_resolution` from 1 to
+    /// [`Self::MAX_FACE_RESOLUTION`], as healpy does, while [`crate::Nested`] needs a power of
+    /// two like [`Self::new`].
+    ///
+    /// Fallible [`crate::Nested`] operations on a grid whose resolution is not a power of two
+    /// return [`crate::Error::InvalidFaceResolution`]. The infallible ones, such as
+    /// [`crate::Healpix::angle_to_pixel`] for [`crate::Nested`] on a grid made for
+    /// [`crate::Ring`] at resolution 1000, panic instead.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::ResolutionTooLarge`] if `face_resolution` exceeds
+    /// [`Self::MAX_FACE_RESOLUTION`] and [`crate::Error::InvalidFaceResolution`] if `N` does
+    /// not support it otherwise.
+    ///
+    /// # Example
+    /// ```rust
+    /// use realpix::{DynamicHealpix, Healpix, Nested, Ring};
+    ///
+    /// let healpix = DynamicHealpix::new_for::<Ring>(1000).unwrap();
+    /// assert_eq!(healpix.total_pixels(), 12_000_000);
+    /// assert!(DynamicHealpix::new_for::<Nested>(1000).is_err());
+    /// ```
+    pub fn new_for<N: NumberingScheme>(face_resolution: u32) -> crate::Result<Self> {
+        check_face_resolution::<N>(face_resolution)?;
         Ok(Self { face_resolution })
     }
 }
@@ -43,11 +82,11 @@ impl Healpix for DynamicHealpix {
         self.face_resolution
     }
 
-    fn pixels_per_face(&self) -> u32 {
-        self.face_resolution * self.face_resolution
+    fn pixels_per_face(&self) -> u64 {
+        self.face_resolution as u64 * self.face_resolution as u64
     }
 
-    fn total_pixels(&self) -> u32 {
-        12 * (self.face_resolution * self.face_resolution)
+    fn total_pixels(&self) -> u64 {
+        12 * self.pixels_per_face()
     }
 }
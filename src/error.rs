@@ -4,12 +4,45 @@ use thiserror::Error;
 ///
 /// Variants:
 /// - `InvalidFaceResolution`: This variant indicates that an invalid or unsupported face resolution value has been provided.
-/// This could occur in scenarios where the input does not meet the required constraints or parameters.
+///   This could occur in scenarios where the input does not meet the required constraints or parameters.
 #[derive(Error, Debug)]
 pub enum Error {
-    #[error("face resolution must be a power of two")]
+    #[error("face resolution must be positive, and a power of two for nested ordering")]
     InvalidFaceResolution,
 
+    #[error("face resolution exceeds the largest supported, 2^29")]
+    ResolutionTooLarge,
+
     #[error("The pixel is out of bounds")]
     InvalidPixel,
+
+    #[error("face coordinates need a face below 12 and x and y within [0, 1]")]
+    InvalidFaceCoordinates,
+
+    #[error("theta must lie within [0, pi] and phi must be finite")]
+    InvalidAngle,
+
+    #[error("RA must be finite and Dec must lie within [-90, 90] degrees")]
+    InvalidRaDec,
+
+    #[error("depth exceeds the deepest supported level")]
+    InvalidDepth,
+
+    #[error("the map must hold exactly one value per pixel")]
+    InvalidMapSize,
+
+    #[error("a polygon needs at least 3 distinct vertices")]
+    InvalidPolygon,
+
+    #[error("the MOC text is malformed")]
+    InvalidMoc,
+
+    #[error("the FITS data is malformed or unsupported")]
+    InvalidFits,
+
+    #[error("the coefficients do not match the number expected for lmax")]
+    InvalidAlmSize,
+
+    #[error("the mask covers no pixels")]
+    EmptyMask,
 }
@@ -0,0 +1,152 @@
+use crate::fractional_face_coordinates::FractionalFaceCoordinates;
+use crate::hpd::Hpd;
+
+/// The position of a pixel inside one of the 12 base faces, independent of the numbering
+/// scheme.
+///
+/// `x` and `y` run from `0` to `nside - 1` across the face. `x` grows towards the north-east
+/// and `y` towards the north-west, so `(0, 0)` is the southernmost pixel of the face. Faces 0-3
+/// touch the north pole, 4-7 straddle the equator and 8-11 touch the south pole.
+///
+/// # Example
+/// ```rust
+/// use realpix::{ConstHealpix, FaceCoordinates, Healpix, Nested, Pixel, Ring};
+///
+/// let healpix = ConstHealpix::<4>::new();
+/// let coordinates = healpix.pixel_to_face_coordinates(Pixel::<Nested>::from_u64(100)).unwrap();
+/// assert_eq!(coordinates, FaceCoordinates { face: 6, x: 2, y: 0 });
+/// let ring: Pixel<Ring> = healpix.face_coordinates_to_pixel(coordinates).unwrap();
+/// assert_eq!(healpix.ring2nest(ring).unwrap().as_u64(), 100);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FaceCoordinates {
+    /// The base face, from 0 to 11.
+    pub face: u8,
+    /// The column of the pixel within the face.
+    pub x: u32,
+    /// The row of the pixel within the face.
+    pub y: u32,
+}
+
+impl FaceCoordinates {
+    /// Returns the continuous face coordinates of the pixel centre at `face_resolution`.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPixel`] if the coordinates do not lie on the grid.
+    pub fn centre(&self, face_resolution: u32) -> crate::Result<FractionalFaceCoordinates> {
+        self.to_hpd(face_resolution)?;
+        let nside = face_resolution as f64;
+        Ok(FractionalFaceCoordinates {
+            face: self.face,
+            x: (self.x as f64 + 0.5) / nside,
+            y: (self.y as f64 + 0.5) / nside,
+        })
+    }
+
+    /// Returns the coordinates as an `Hpd`, checking that they lie on the grid.
+    pub(crate) fn to_hpd(self, face_resolution: u32) -> crate::Result<Hpd> {
+        if self.face >= 12 || self.x >= face_resolution || self.y >= face_resolution {
+            return Err(crate::Error::InvalidPixel);
+        }
+        Ok(Hpd {
+            x: self.x as i64,
+            y: self.y as i64,
+            f: self.face as i32,
+        })
+    }
+}
+
+impl From<Hpd> for FaceCoordinates {
+    fn from(hpd: Hpd) -> Self {
+        Self {
+            face: hpd.f as u8,
+            x: hpd.x as u32,
+            y: hpd.y as u32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DynamicHealpix, Healpix, Nested, NumberingScheme, Pixel, Ring};
+
+    fn assert_round_trips<N: NumberingScheme + core::fmt::Debug + Copy>(healpix: &DynamicHealpix) {
+        let nside = healpix.face_resolution();
+        for pixel in healpix.iter_pixels::<N>() {
+            let coordinates = healpix.pixel_to_face_coordinates(pixel).unwrap();
+            assert!(coordinates.face < 12 && coordinates.x < nside && coordinates.y < nside);
+            assert_eq!(
+                healpix.face_coordinates_to_pixel::<N>(coordinates).unwrap(),
+                pixel
+            );
+        }
+    }
+
+    #[test]
+    fn pixels_round_trip_in_both_schemes() {
+        let healpix = DynamicHealpix::new(8).unwrap();
+        assert_round_trips::<Nested>(&healpix);
+        assert_round_trips::<Ring>(&healpix);
+        assert_round_trips::<Ring>(&DynamicHealpix::new_for::<Ring>(5).unwrap());
+    }
+
+    #[test]
+    fn schemes_share_face_coordinates() {
+        let healpix = DynamicHealpix::new(4).unwrap();
+        for pixel in healpix.iter_pixels::<Nested>() {
+            let ring = healpix.nest2ring(pixel).unwrap();
+            assert_eq!(
+                healpix.pixel_to_face_coordinates(pixel).unwrap(),
+                healpix.pixel_to_face_coordinates(ring).unwrap()
+            );
+        }
+        // Nested pixels are numbered face by face, starting at the face's south corner.
+        let coordinates = FaceCoordinates {
+            face: 3,
+            x: 0,
+            y: 0,
+        };
+        let pixel: Pixel<Nested> = healpix.face_coordinates_to_pixel(coordinates).unwrap();
+        assert_eq!(pixel.as_u64(), 3 * 16);
+    }
+
+    #[test]
+    fn rejects_coordinates_off_the_grid() {
+        let healpix = DynamicHealpix::new(4).unwrap();
+        for coordinates in [
+            FaceCoordinates {
+                face: 12,
+                x: 0,
+                y: 0,
+            },
+            FaceCoordinates {
+                face: 0,
+                x: 4,
+                y: 0,
+            },
+            FaceCoordinates {
+                face: 0,
+                x: 0,
+                y: 4,
+            },
+        ] {
+            assert!(
+                healpix
+                    .face_coordinates_to_pixel::<Ring>(coordinates)
+                    .is_err()
+            );
+            assert!(coordinates.centre(4).is_err());
+        }
+        let ring = DynamicHealpix::new_for::<Ring>(3).unwrap();
+        let coordinates = FaceCoordinates {
+            face: 0,
+            x: 0,
+            y: 0,
+        };
+        assert!(
+            ring.face_coordinates_to_pixel::<Nested>(coordinates)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,511 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Size of a FITS block; every header and data unit is padded to a multiple of it.
+pub const BLOCK_SIZE: usize = 2880;
+
+/// Length of a header card.
+const CARD_SIZE: usize = 80;
+
+/// A header card value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Logical(bool),
+    Integer(i64),
+    Float(f64),
+    Text(String),
+}
+
+/// The keyword records of a FITS header, in order.
+#[derive(Debug, Clone, Default)]
+pub struct Header {
+    cards: Vec<(String, Value)>,
+}
+
+impl Header {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a keyword record, replacing an earlier record with the same keyword.
+    pub fn set(&mut self, keyword: &str, value: Value) {
+        match self.cards.iter_mut().find(|(k, _)| k == keyword) {
+            Some(card) => card.1 = value,
+            None => self.cards.push((keyword.to_string(), value)),
+        }
+    }
+
+    pub fn get(&self, keyword: &str) -> Option<&Value> {
+        self.cards
+            .iter()
+            .find(|(k, _)| k == keyword)
+            .map(|(_, v)| v)
+    }
+
+    pub fn get_integer(&self, keyword: &str) -> Option<i64> {
+        match self.get(keyword)? {
+            Value::Integer(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns a required non-negative integer value, such as an axis length.
+    pub fn get_size(&self, keyword: &str) -> crate::Result<usize> {
+        let value = self.get_integer(keyword).ok_or(crate::Error::InvalidFits)?;
+        usize::try_from(value).map_err(|_| crate::Error::InvalidFits)
+    }
+
+    pub fn get_text(&self, keyword: &str) -> Option<&str> {
+        match self.get(keyword)? {
+            Value::Text(v) => Some(v.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns a numeric value, accepting integers as well as floats.
+    #[cfg(feature = "fits")]
+    pub fn get_float(&self, keyword: &str) -> Option<f64> {
+        match self.get(keyword)? {
+            Value::Integer(v) => Some(*v as f64),
+            Value::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Serializes the header, terminated by `END` and padded to a whole block.
+    pub fn write(&self, out: &mut Vec<u8>) {
+        let start = out.len();
+        for (keyword, value) in &self.cards {
+            let value = match value {
+                Value::Logical(v) => format!("{:>20}", if *v { "T" } else { "F" }),
+                Value::Integer(v) => format!("{:>20}", v),
+                Value::Float(v) => format!("{:>20}", format!("{:E}", v)),
+                Value::Text(v) => format!("'{:<8}'", v.replace('\'', "''")),
+            };
+            let card = format!("{:<8}= {}", keyword, value);
+            out.extend(format!("{:<80}", card).bytes().take(CARD_SIZE));
+        }
+        out.extend(format!("{:<80}", "END").bytes());
+        pad(out, start, b' ');
+    }
+
+    /// Parses a header starting at `bytes[0]`, returning it with the number of bytes it spans.
+    pub fn parse(bytes: &[u8]) -> crate::Result<(Self, usize)> {
+        let mut header = Header::new();
+        for (i, card) in bytes.chunks_exact(CARD_SIZE).enumerate() {
+            // Header cards are restricted to printable ASCII, so slicing by byte is safe.
+            if !card.is_ascii() {
+                return Err(crate::Error::InvalidFits);
+            }
+            let card = core::str::from_utf8(card).map_err(|_| crate::Error::InvalidFits)?;
+            let keyword = card[..8].trim_end();
+            if keyword == "END" {
+                let length = ((i + 1) * CARD_SIZE).div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+                return Ok((header, length));
+            }
+            if &card[8..10] != "= " {
+                // Commentary cards such as COMMENT and HISTORY carry no value.
+                continue;
+            }
+            header.set(keyword, parse_value(&card[10..])?);
+        }
+        Err(crate::Error::InvalidFits)
+    }
+}
+
+fn parse_value(field: &str) -> crate::Result<Value> {
+    let field = field.trim_start();
+    if let Some(rest) = field.strip_prefix('\'') {
+        let mut text = String::new();
+        let mut chars = rest.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                } else {
+                    return Ok(Value::Text(text.trim_end().to_string()));
+                }
+            }
+            text.push(c);
+        }
+        return Err(crate::Error::InvalidFits);
+    }
+
+    let field = field.split('/').next().unwrap_or("").trim();
+    match field {
+        "T" => Ok(Value::Logical(true)),
+        "F" => Ok(Value::Logical(false)),
+        _ => {
+            if let Ok(v) = field.parse::<i64>() {
+                Ok(Value::Integer(v))
+            } else {
+                field
+                    .replace('D', "E")
+                    .parse::<f64>()
+                    .map(Value::Float)
+                    .map_err(|_| crate::Error::InvalidFits)
+            }
+        }
+    }
+}
+
+/// Pads `out` from `start` to a whole number of blocks.
+pub fn pad(out: &mut Vec<u8>, start: usize, fill: u8) {
+    let length = out.len() - start;
+    out.resize(start + length.div_ceil(BLOCK_SIZE) * BLOCK_SIZE, fill);
+}
+
+/// Writes an empty primary HDU that announces extensions.
+pub fn write_empty_primary(out: &mut Vec<u8>) {
+    let mut header = Header::new();
+    header.set("SIMPLE", Value::Logical(true));
+    header.set("BITPIX", Value::Integer(8));
+    header.set("NAXIS", Value::Integer(0));
+    header.set("EXTEND", Value::Logical(true));
+    header.write(out);
+}
+
+/// A column of a binary table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    /// Number of elements per row.
+    pub repeat: usize,
+    /// The FITS type code, such as `K` for 64-bit integers or `D` for doubles.
+    pub code: char,
+}
+
+impl Column {
+    pub fn new(name: &str, repeat: usize, code: char) -> Self {
+        Self {
+            name: name.to_string(),
+            repeat,
+            code,
+        }
+    }
+
+    /// Size of one element in bytes.
+    pub fn element_size(&self) -> usize {
+        match self.code {
+            'L' | 'B' | 'A' => 1,
+            'I' => 2,
+            'J' | 'E' => 4,
+            'K' | 'D' => 8,
+            _ => 0,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.repeat * self.element_size()
+    }
+}
+
+/// A binary table extension, with its rows stored as raw big-endian bytes.
+#[derive(Debug, Clone)]
+pub struct BinTable {
+    pub header: Header,
+    pub columns: Vec<Column>,
+    pub rows: usize,
+    pub data: Vec<u8>,
+}
+
+impl BinTable {
+    /// Creates an empty table with the given columns. Extra keywords can be added to `header`.
+    pub fn new(columns: Vec<Column>) -> Self {
+        Self {
+            header: Header::new(),
+            columns,
+            rows: 0,
+            data: Vec::new(),
+        }
+    }
+
+    pub fn row_width(&self) -> usize {
+        self.columns.iter().map(Column::width).sum()
+    }
+
+    /// Returns the index of the column named `name`, ignoring case.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Appends one row, given as the big-endian bytes of every column in order.
+    pub fn push_row(&mut self, row: &[u8]) {
+        debug_assert_eq!(row.len(), self.row_width());
+        self.data.extend_from_slice(row);
+        self.rows += 1;
+    }
+
+    /// Iterates over the raw bytes of every element of column `index`, row after row.
+    fn elements(&self, index: usize) -> impl Iterator<Item = &[u8]> + '_ {
+        let column = &self.columns[index];
+        let offset: usize = self.columns[..index].iter().map(Column::width).sum();
+        let row_width = self.row_width();
+        (0..self.rows).flat_map(move |row| {
+            let start = row * row_width + offset;
+            self.data[start..start + column.width()].chunks_exact(column.element_size())
+        })
+    }
+
+    /// Decodes every element of integer column `index` as `i64`, row after row.
+    pub fn read_integer_column(&self, index: usize) -> crate::Result<Vec<i64>> {
+        let code = self.columns[index].code;
+        self.elements(index)
+            .map(|element| match code {
+                'B' => Ok(element[0] as i64),
+                'I' => Ok(i16::from_be_bytes(element.try_into().unwrap()) as i64),
+                'J' => Ok(i32::from_be_bytes(element.try_into().unwrap()) as i64),
+                'K' => Ok(i64::from_be_bytes(element.try_into().unwrap())),
+                _ => Err(crate::Error::InvalidFits),
+            })
+            .collect()
+    }
+
+    /// Decodes every element of numeric column `index` as `f64`, row after row.
+    #[cfg(feature = "fits")]
+    pub fn read_column(&self, index: usize) -> crate::Result<Vec<f64>> {
+        let code = self.columns[index].code;
+        if matches!(code, 'E' | 'D') {
+            self.elements(index)
+                .map(|element| {
+                    Ok(match code {
+                        'E' => f32::from_be_bytes(element.try_into().unwrap()) as f64,
+                        _ => f64::from_be_bytes(element.try_into().unwrap()),
+                    })
+                })
+                .collect()
+        } else {
+            Ok(self
+                .read_integer_column(index)?
+                .into_iter()
+                .map(|v| v as f64)
+                .collect())
+        }
+    }
+
+    /// Serializes the table as a `BINTABLE` extension.
+    pub fn write(&self, out: &mut Vec<u8>) {
+        let mut header = Header::new();
+        header.set("XTENSION", Value::Text("BINTABLE".to_string()));
+        header.set("BITPIX", Value::Integer(8));
+        header.set("NAXIS", Value::Integer(2));
+        header.set("NAXIS1", Value::Integer(self.row_width() as i64));
+        header.set("NAXIS2", Value::Integer(self.rows as i64));
+        header.set("PCOUNT", Value::Integer(0));
+        header.set("GCOUNT", Value::Integer(1));
+        header.set("TFIELDS", Value::Integer(self.columns.len() as i64));
+        for (i, column) in self.columns.iter().enumerate() {
+            header.set(&format!("TTYPE{}", i + 1), Value::Text(column.name.clone()));
+            header.set(
+                &format!("TFORM{}", i + 1),
+                Value::Text(format!("{}{}", column.repeat, column.code)),
+            );
+        }
+        for (keyword, value) in &self.header.cards {
+            header.set(keyword, value.clone());
+        }
+        header.write(out);
+
+        let start = out.len();
+        out.extend_from_slice(&self.data);
+        pad(out, start, 0);
+    }
+
+    /// Finds and parses the first `BINTABLE` extension of a FITS file.
+    pub fn parse_first(bytes: &[u8]) -> crate::Result<Self> {
+        let (primary, length) = Header::parse(bytes)?;
+        let mut offset = length
+            .checked_add(data_size(&primary)?)
+            .ok_or(crate::Error::InvalidFits)?;
+
+        while offset < bytes.len() {
+            let (header, length) = Header::parse(&bytes[offset..])?;
+            offset += length;
+            let size = data_size(&header)?;
+            let end = offset.checked_add(size).ok_or(crate::Error::InvalidFits)?;
+            if header.get_text("XTENSION") == Some("BINTABLE") {
+                let data = bytes.get(offset..end).ok_or(crate::Error::InvalidFits)?;
+                return Self::from_parts(header, data);
+            }
+            offset = end;
+        }
+        Err(crate::Error::InvalidFits)
+    }
+
+    fn from_parts(header: Header, data: &[u8]) -> crate::Result<Self> {
+        let fields = header.get_size("TFIELDS")?;
+        let mut columns = Vec::new();
+        for i in 1..=fields {
+            let name = header
+                .get_text(&format!("TTYPE{}", i))
+                .unwrap_or("")
+                .to_string();
+            let form = header
+                .get_text(&format!("TFORM{}", i))
+                .ok_or(crate::Error::InvalidFits)?
+                .trim();
+            let code_at = form
+                .find(|c: char| c.is_ascii_alphabetic())
+                .ok_or(crate::Error::InvalidFits)?;
+            let repeat = if code_at == 0 {
+                1
+            } else {
+                form[..code_at]
+                    .parse()
+                    .map_err(|_| crate::Error::InvalidFits)?
+            };
+            let code = form[code_at..].chars().next().unwrap();
+            columns.push(Column { name, repeat, code });
+        }
+
+        let row_width = header.get_size("NAXIS1")?;
+        let rows = header.get_size("NAXIS2")?;
+        // The column widths are summed with overflow checks before `row_width` relies on them.
+        let columns_width = columns.iter().try_fold(0usize, |width, column| {
+            column
+                .repeat
+                .checked_mul(column.element_size())
+                .and_then(|w| width.checked_add(w))
+        });
+        if columns_width != Some(row_width) || columns.iter().any(|c| c.element_size() == 0) {
+            return Err(crate::Error::InvalidFits);
+        }
+        let data = row_width
+            .checked_mul(rows)
+            .and_then(|size| data.get(..size))
+            .ok_or(crate::Error::InvalidFits)?;
+        Ok(Self {
+            header,
+            columns,
+            rows,
+            data: data.to_vec(),
+        })
+    }
+}
+
+/// Returns the padded size of the data unit described by `header`.
+fn data_size(header: &Header) -> crate::Result<usize> {
+    let axes = header
+        .get_integer("NAXIS")
+        .ok_or(crate::Error::InvalidFits)?;
+    if axes == 0 {
+        return Ok(0);
+    }
+    let bitpix = header
+        .get_integer("BITPIX")
+        .ok_or(crate::Error::InvalidFits)?;
+    let mut size = bitpix.unsigned_abs() as usize / 8;
+    for i in 1..=axes {
+        size = size
+            .checked_mul(header.get_size(&format!("NAXIS{}", i))?)
+            .ok_or(crate::Error::InvalidFits)?;
+    }
+    if header.get("PCOUNT").is_some() {
+        size = size
+            .checked_add(header.get_size("PCOUNT")?)
+            .ok_or(crate::Error::InvalidFits)?;
+    }
+    size.div_ceil(BLOCK_SIZE)
+        .checked_mul(BLOCK_SIZE)
+        .ok_or(crate::Error::InvalidFits)
+}
+
+/// Overwrites the card of `keyword` in a serialized file with `card`.
+#[cfg(test)]
+pub(crate) fn replace_card(bytes: &mut [u8], keyword: &str, card: &str) {
+    let start = bytes
+        .chunks_exact(CARD_SIZE)
+        .position(|c| c.starts_with(format!("{:<8}=", keyword).as_bytes()))
+        .unwrap()
+        * CARD_SIZE;
+    let mut card = card.as_bytes().to_vec();
+    card.resize(CARD_SIZE, b' ');
+    bytes[start..start + CARD_SIZE].copy_from_slice(&card);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_round_trips() {
+        let mut header = Header::new();
+        header.set("SIMPLE", Value::Logical(true));
+        header.set("NSIDE", Value::Integer(1024));
+        header.set("ORDERING", Value::Text("NESTED".to_string()));
+        header.set("BAD_DATA", Value::Float(-1.6375e30));
+        header.set("OBJECT", Value::Text("it's".to_string()));
+
+        let mut bytes = Vec::new();
+        header.write(&mut bytes);
+        assert_eq!(bytes.len(), BLOCK_SIZE);
+
+        let (parsed, length) = Header::parse(&bytes).unwrap();
+        assert_eq!(length, BLOCK_SIZE);
+        assert_eq!(parsed.get("SIMPLE"), Some(&Value::Logical(true)));
+        assert_eq!(parsed.get_integer("NSIDE"), Some(1024));
+        assert_eq!(parsed.get_text("ORDERING"), Some("NESTED"));
+        assert_eq!(parsed.get("BAD_DATA"), Some(&Value::Float(-1.6375e30)));
+        assert_eq!(parsed.get_text("OBJECT"), Some("it's"));
+    }
+
+    #[test]
+    fn bintable_round_trips() {
+        let mut table = BinTable::new(alloc::vec![
+            Column::new("A", 1, 'K'),
+            Column::new("B", 2, 'E')
+        ]);
+        for i in 0..3i64 {
+            let mut row = Vec::new();
+            row.extend(i.to_be_bytes());
+            row.extend((i as f32 * 0.5).to_be_bytes());
+            row.extend((-(i as f32)).to_be_bytes());
+            table.push_row(&row);
+        }
+
+        let mut bytes = Vec::new();
+        write_empty_primary(&mut bytes);
+        table.write(&mut bytes);
+        assert_eq!(bytes.len() % BLOCK_SIZE, 0);
+
+        let parsed = BinTable::parse_first(&bytes).unwrap();
+        assert_eq!(parsed.columns, table.columns);
+        assert_eq!(parsed.read_integer_column(0).unwrap(), [0, 1, 2]);
+        assert_eq!(parsed.data, table.data);
+    }
+
+    #[test]
+    fn malformed_headers_are_rejected() {
+        let mut table = BinTable::new(alloc::vec![Column::new("A", 1, 'K')]);
+        table.push_row(&7i64.to_be_bytes());
+        let mut bytes = Vec::new();
+        write_empty_primary(&mut bytes);
+        table.write(&mut bytes);
+        assert!(BinTable::parse_first(&bytes).is_ok());
+
+        let cards = [
+            ("NAXIS2", "NAXIS2  =                   -1"),
+            ("NAXIS2", "NAXIS2  =  9223372036854775807"),
+            ("NAXIS1", "NAXIS1  =  9223372036854775807"),
+            ("PCOUNT", "PCOUNT  =                   -1"),
+            ("TFORM1", "TFORM1  = '2305843009213693952K'"),
+            ("TTYPE1", "TTYPE1  = 'caf\u{e9}'"),
+            // A multibyte character across the end of the keyword field.
+            ("TTYPE1", "TTYPE1 \u{e9}= 'A'"),
+        ];
+        for (keyword, card) in cards {
+            let mut malformed = bytes.clone();
+            replace_card(&mut malformed, keyword, card);
+            assert!(
+                matches!(
+                    BinTable::parse_first(&malformed),
+                    Err(crate::Error::InvalidFits)
+                ),
+                "{card}"
+            );
+        }
+    }
+}
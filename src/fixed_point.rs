@@ -0,0 +1,300 @@
+use crate::hpd::Hpd;
+
+/// `z = 1` in the fixed-point format taken by [`fixed_to_hpd`].
+pub(crate) const Z_ONE: i64 = 1 << 62;
+
+/// The number of CORDIC rotations in [`first_octant_turns`]. What remains after them is an
+/// angle below `2^-32`, which equals its tangent to well within the precision of the result.
+const CORDIC_STEPS: usize = 32;
+
+/// `atan(2^-i)` as a fraction of a full turn, scaled by `2^96`.
+const ATAN_TURNS: [i128; CORDIC_STEPS] = [
+    0x2000_0000_0000_0000_0000_0000,
+    0x12e4_051d_9df3_0866_5688_f6db,
+    0x9fb_385b_5ee3_9e8d_df43_f3ca,
+    0x511_11d4_1ddd_9a1b_7f92_55cb,
+    0x28b_0d43_0e58_9aec_c0cc_0012,
+    0x145_d7e1_5904_6278_569c_94df,
+    0xa2_f61e_5c28_2629_84d6_bf59,
+    0x51_7c55_11d4_42ae_a2c3_06cb,
+    0x28_be53_46d0_c336_fc91_7a6f,
+    0x14_5f2e_bb30_ab37_b934_1f2d,
+    0xa_2f98_0091_ba7b_67f4_3a92,
+    0x5_17cc_14a8_0cb7_0788_f004,
+    0x2_8be6_0cdf_ec61_994b_7616,
+    0x1_45f3_06c1_72f2_46af_4bfa,
+    0xa2f9_836a_e911_5853_9db4,
+    0x517c_c1b6_ba7b_b2f7_23fe,
+    0x28be_60db_85fc_3a56_ab55,
+    0x145f_306d_c815_e946_c44b,
+    0xa2f_9836_e4ad_ee26_d055,
+    0x517_cc1b_726b_5643_d5f3,
+    0x28b_e60d_b938_3707_f8b3,
+    0x145_f306_dc9c_6d00_be11,
+    0xa2_f983_6e4e_40af_f73f,
+    0x51_7cc1_b727_219d_eea6,
+    0x28_be60_db93_90f7_b5b4,
+    0x14_5f30_6dc9_c880_f2a6,
+    0xa_2f98_36e4_e441_1c4d,
+    0x5_17cc_1b72_7220_a285,
+    0x2_8be6_0db9_3910_53cf,
+    0x1_45f3_06dc_9c88_2a39,
+    0xa2f9_836e_4e44_1527,
+    0x517c_c1b7_2722_0a95,
+];
+
+/// `2^64 / (2 pi)`, rounded.
+const INV_TAU: i128 = 2_935_890_503_282_001_226;
+
+/// Convert a fixed-point vector to the fixed-point `(z, phi)` taken by [`fixed_to_hpd`], using
+/// integer arithmetic only.
+///
+/// The vector need not be normalised, but must not be zero. `z` comes from the integer square
+/// root of the squared length, and `phi` from the quadrant and octant of `(x, y)` and the
+/// arctangent of the ratio of the smaller to the larger coordinate in that octant.
+pub(crate) fn fixed_vec_to_loc([x, y, z]: [i64; 3]) -> (i64, u64) {
+    let largest = x.unsigned_abs().max(y.unsigned_abs()).max(z.unsigned_abs());
+    assert!(largest != 0, "the zero vector has no direction");
+    // Scale the largest coordinate into [2^62, 2^63), so the square root keeps 62 bits.
+    let shift = largest.leading_zeros() - 1;
+    let [x, y, z] = [x, y, z].map(|c| (c as i128) << shift);
+    let length = ((x * x) as u128 + (y * y) as u128 + (z * z) as u128).isqrt();
+    let z = ((z << 62) / length as i128) as i64;
+
+    if x == 0 && y == 0 {
+        return (z, 0);
+    }
+    // Rotate (x, y) by whole quarter turns into x > 0, y >= 0, as atan2 would place it.
+    let (quadrant, x, y) = if x > 0 && y >= 0 {
+        (0, x, y)
+    } else if x <= 0 && y > 0 {
+        (1, y, -x)
+    } else if x < 0 && y <= 0 {
+        (2, -x, -y)
+    } else {
+        (3, -y, x)
+    };
+    // Reflect the second octant of the quadrant onto the first.
+    let turns = if y > x {
+        (1 << 94) - first_octant_turns(y as u128, x as u128)
+    } else {
+        first_octant_turns(x as u128, y as u128)
+    };
+    let phi = ((quadrant as u64) << 62).wrapping_add(((turns + (1 << 31)) >> 32) as u64);
+    (z, phi)
+}
+
+/// Returns `atan(minor / major)` as a fraction of a full turn, scaled by `2^96`, for
+/// `0 <= minor <= major` and `major > 0`.
+fn first_octant_turns(major: u128, minor: u128) -> i128 {
+    // Work with the larger coordinate near 2^98, so the rounding of the shifts stays far
+    // below the precision of the result. The rotations grow it by less than 2^2.3, and what
+    // is left of `y` is below `x * 2^-31`, so `y << 56` below fits in an i128.
+    let shift = major.leading_zeros() as i32 - 29;
+    let scale = |c: u128| {
+        if shift >= 0 {
+            (c << shift) as i128
+        } else {
+            (c >> -shift) as i128
+        }
+    };
+    let (mut x, mut y) = (scale(major), scale(minor));
+    let mut turns = 0;
+    for (i, atan) in ATAN_TURNS.iter().enumerate() {
+        let (dx, dy) = (y >> i, x >> i);
+        if y >= 0 {
+            (x, y) = (x + dx, y - dy);
+            turns += atan;
+        } else {
+            (x, y) = (x - dx, y + dy);
+            turns -= atan;
+        }
+    }
+    // The remaining angle is below 2^-32, where atan(t) = t - t^3 / 3 differs from t by less
+    // than 2^-97.
+    let radians = ((y >> 24) << 80) / (x >> 24);
+    turns + ((radians * INV_TAU) >> 48)
+}
+
+/// Convert a fixed-point `(z, phi)` to the discrete face coordinates (`Hpd`) of the pixel
+/// containing it, using integer arithmetic only.
+///
+/// `z` is `cos(theta) * 2^62` and `phi` is the azimuth as a fraction of a full turn, scaled by
+/// `2^64`. This follows `Ring::loc2hpd` step by step, including which pixel a point on an
+/// edge belongs to, but every floor is exact: the equatorial region is linear in `z` and
+/// `phi`, and in the polar caps `floor(sqrt(q))` is computed as the integer square root of
+/// `floor(q)`.
+pub(crate) fn fixed_to_hpd(face_resolution: u32, z: i64, phi: u64) -> Hpd {
+    let nside = face_resolution as u64;
+    let z = z.clamp(-Z_ONE, Z_ONE);
+
+    if 3 * (z.unsigned_abs() as u128) <= 2 * Z_ONE as u128 {
+        // Equatorial region: jp = 0.5 + tt - 0.75 z and jm = 0.5 + tt + 0.75 z, with
+        // tt = 4 phi, all scaled by 2^64.
+        let temp1 = 4 * phi as i128 + (1 << 63);
+        let temp2 = 3 * z as i128;
+        let jp = temp1 - temp2;
+        let jm = temp1 + temp2;
+        let ifp = (jp >> 64) as u32;
+        let ifm = (jm >> 64) as u32;
+        let f = if ifp == ifm {
+            ifp | 4
+        } else if ifp < ifm {
+            ifp
+        } else {
+            ifm + 8
+        };
+        let x = (jm as u64 as u128 * nside as u128) >> 64;
+        let y = (jp as u64 as u128 * nside as u128) >> 64;
+        Hpd {
+            x: x as i64,
+            y: (nside - 1 - y as u64) as i64,
+            f: f as i32,
+        }
+    } else {
+        // Polar regions: jp = tp * sqrt(3 (1 - |z|)) and jm = (1 - tp) * sqrt(3 (1 - |z|)),
+        // where tp is the position of phi inside its quarter turn.
+        let ntt = (phi >> 62) as i32;
+        let tp = (phi << 2) as u128;
+        let w3 = 3 * (Z_ONE - z.abs()) as u64;
+        let jp = polar_edge(w3, tp, nside).min(nside - 1);
+        let jm = polar_edge(w3, (1 << 64) - tp, nside).min(nside - 1);
+        if z > 0 {
+            Hpd {
+                x: (nside - 1 - jm) as i64,
+                y: (nside - 1 - jp) as i64,
+                f: ntt,
+            }
+        } else {
+            Hpd {
+                x: jp as i64,
+                y: jm as i64,
+                f: ntt + 8,
+            }
+        }
+    }
+}
+
+/// Returns `floor(j * nside)` for one of the polar edge coordinates
+/// `j = t * sqrt(3 (1 - |z|))`.
+///
+/// `w3` is `3 (1 - |z|) * 2^62` and `t` is the fraction of the quarter turn, scaled by
+/// `2^64`. `(j * nside)^2 = w3 * t^2 * nside^2 / 2^190`, which needs 254 bits, and the floor
+/// of its square root is the integer square root of the floor of the square.
+fn polar_edge(w3: u64, t: u128, nside: u64) -> u64 {
+    let mut square = [w3, 0, 0, 0];
+    for factor in [t, t, nside as u128, nside as u128] {
+        square = mul_u256(square, factor);
+    }
+    // The integer part of the square, above bit 190.
+    ((square[3] << 2) | (square[2] >> 62)).isqrt()
+}
+
+/// Multiplies a 256-bit little-endian integer by `factor`, which is at most `2^64`. The product
+/// must fit in 256 bits.
+fn mul_u256(value: [u64; 4], factor: u128) -> [u64; 4] {
+    if factor == 1 << 64 {
+        return [0, value[0], value[1], value[2]];
+    }
+    let mut result = [0; 4];
+    let mut carry = 0u128;
+    for (limb, out) in value.iter().zip(result.iter_mut()) {
+        let product = *limb as u128 * factor + carry;
+        *out = product as u64;
+        carry = product >> 64;
+    }
+    debug_assert_eq!(carry, 0, "256-bit product overflowed");
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math;
+    use crate::numbering_scheme::NumberingScheme;
+    use crate::ring::Ring;
+    use core::f64::consts::TAU;
+
+    /// A full turn in the fixed-point format of `phi`.
+    const TURN: f64 = (1u128 << 64) as f64;
+
+    /// Returns the f64 `(z, phi)` of a fixed-point point. `z` is exact for values with at most
+    /// 53 significant bits.
+    fn to_f64(z: i64, phi: u64) -> (f64, f64) {
+        (z as f64 / Z_ONE as f64, phi as f64 / TURN * TAU)
+    }
+
+    /// A small deterministic generator, so the comparison covers the same points every run.
+    fn xorshift(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn agrees_with_the_floating_point_path() {
+        let mut state = 0x9e37_79b9_7f4a_7c15;
+        for nside in [1, 2, 5, 64, 1 << 13, 1 << 29] {
+            for _ in 0..20_000 {
+                // Keep 53 significant bits so that the f64 path sees the same z.
+                let z = (xorshift(&mut state) as i64 >> 1).clamp(-Z_ONE, Z_ONE) & !0x3ff;
+                let phi = xorshift(&mut state) & !0x7ff;
+                let (zf, phif) = to_f64(z, phi);
+                assert_eq!(
+                    fixed_to_hpd(nside, z, phi),
+                    Ring::loc2hpd(nside, zf, None, phif),
+                    "nside {nside}, z {z}, phi {phi}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn vectors_reduce_to_their_angles() {
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        for i in 0..100_000 {
+            // Vary the magnitude too, down to a few bits per coordinate.
+            let shift = i % 60;
+            let vector = [(); 3].map(|_| xorshift(&mut state) as i64 >> shift);
+            let (z, phi) = fixed_vec_to_loc(vector);
+            let [x, y, zf] = vector.map(|c| c as f64);
+            let length = math::sqrt(x * x + y * y + zf * zf);
+            let expected_z = zf / length * Z_ONE as f64;
+            let expected_phi = math::rem_euclid(math::atan2(y, x), TAU) / TAU * TURN;
+            // f64 carries 53 bits, so allow a few of its ulps at these scales.
+            assert!((z as f64 - expected_z).abs() <= 4096.0, "{vector:?}: z {z}");
+            let error = (phi as f64 - expected_phi).abs();
+            assert!(error.min(TURN - error) <= 16384.0, "{vector:?}: phi {phi}");
+        }
+    }
+
+    #[test]
+    fn pixel_centres_hash_to_their_pixel() {
+        let nside = 16;
+        for pixel in 0..12 * nside as u64 * nside as u64 {
+            let hpd = Ring::pixel_to_hpd(nside, pixel);
+            let (theta, phi) = Ring::hpd2ang(nside as i64, &hpd);
+            let z = (math::cos(theta) * Z_ONE as f64) as i64;
+            let phi = (phi / TAU * TURN) as u64;
+            assert_eq!(fixed_to_hpd(nside, z, phi), hpd, "pixel {pixel}");
+        }
+    }
+
+    #[test]
+    fn exact_boundaries_are_resolved_exactly() {
+        let hpd = |nside, z, phi| {
+            let hpd = fixed_to_hpd(nside, z, phi);
+            (hpd.f, hpd.x, hpd.y)
+        };
+        // phi = pi/4 on the equator is the corner shared by faces 0, 4, 5 and 8, where
+        // jp = jm = 1 exactly. In f64 the multiplication by 2/pi can land either side of it.
+        assert_eq!(hpd(2, 0, 1 << 61), (5, 0, 1));
+        // At the start of a quarter turn jp = 0 exactly, in both caps.
+        assert_eq!(hpd(4, 3 << 60, 0), (0, 0, 3));
+        assert_eq!(hpd(4, -(3 << 60), 0), (8, 0, 3));
+        // The poles belong to the pixels at the tips of faces 0 and 8.
+        assert_eq!(hpd(4, Z_ONE, 0), (0, 3, 3));
+        assert_eq!(hpd(4, -Z_ONE, 0), (8, 0, 0));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::face_coordinates::FaceCoordinates;
+use crate::math;
+use crate::ring::Ring;
+
+/// The largest `f64` below 1.
+const BELOW_ONE: f64 = 1.0 - f64::EPSILON / 2.0;
+
+/// A point inside one of the 12 base faces, with `x` and `y` running continuously over
+/// `[0, 1)` across the face.
+///
+/// This is the resolution-free form of [`FaceCoordinates`]: at `nside` the point lies in the
+/// pixel `(floor(x * nside), floor(y * nside))`, so it can be binned at any resolution or used
+/// for custom tilings of the faces.
+///
+/// # Example
+/// ```rust
+/// use realpix::{FaceCoordinates, FractionalFaceCoordinates};
+///
+/// let point = FractionalFaceCoordinates::from_angle(1.2, 0.3);
+/// assert_eq!(point.face, 4);
+/// let (theta, phi) = point.to_angle().unwrap();
+/// assert!((theta - 1.2).abs() < 1e-12 && (phi - 0.3).abs() < 1e-12);
+/// assert_eq!(point.to_face_coordinates(16).face, 4);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FractionalFaceCoordinates {
+    /// The base face, from 0 to 11.
+    pub face: u8,
+    /// The position along the face's north-east axis.
+    pub x: f64,
+    /// The position along the face's north-west axis.
+    pub y: f64,
+}
+
+impl FractionalFaceCoordinates {
+    /// Returns the face coordinates of the colatitude `theta` and azimuth `phi`, in radians.
+    ///
+    /// Points on the boundary between faces are assigned to the face of the pixel containing
+    /// them.
+    pub fn from_angle(theta: f64, phi: f64) -> Self {
+        let (face, x, y) = Ring::ang2face(theta, phi);
+        Self {
+            face: face as u8,
+            x: x.clamp(0.0, BELOW_ONE),
+            y: y.clamp(0.0, BELOW_ONE),
+        }
+    }
+
+    /// Returns the colatitude `theta` and azimuth `phi` of the point, in radians.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidFaceCoordinates`] if `face` is not a base face or `x` or
+    /// `y` lies outside `[0, 1]`.
+    pub fn to_angle(&self) -> crate::Result<(f64, f64)> {
+        let unit = 0.0..=1.0;
+        if self.face >= 12 || !unit.contains(&self.x) || !unit.contains(&self.y) {
+            return Err(crate::Error::InvalidFaceCoordinates);
+        }
+        Ok(Ring::face_to_angle(self.face as i32, self.x, self.y))
+    }
+
+    /// Returns the pixel containing the point at `face_resolution`.
+    pub fn to_face_coordinates(&self, face_resolution: u32) -> FaceCoordinates {
+        let nside = face_resolution as f64;
+        FaceCoordinates {
+            face: self.face,
+            x: math::floor(self.x * nside).clamp(0.0, nside - 1.0) as u32,
+            y: math::floor(self.y * nside).clamp(0.0, nside - 1.0) as u32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConstHealpix, Healpix, Pixel};
+    use core::f64::consts::PI;
+
+    #[test]
+    fn agrees_with_the_pixel_containing_the_angle() {
+        let healpix = ConstHealpix::<16>::new();
+        for i in 0..=60 {
+            let theta = PI * i as f64 / 60.0;
+            let phi = 0.37 * i as f64;
+            let point = FractionalFaceCoordinates::from_angle(theta, phi);
+            assert!((0.0..1.0).contains(&point.x) && (0.0..1.0).contains(&point.y));
+
+            let pixel = Pixel::from_u64(Ring::hpd2ring(16, Ring::ang2hpd(16, theta, phi)) as u64);
+            assert_eq!(
+                point.to_face_coordinates(16),
+                healpix.pixel_to_face_coordinates::<Ring>(pixel).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn pixel_centres_round_trip_through_angles() {
+        let healpix = ConstHealpix::<8>::new();
+        for pixel in healpix.iter_pixels::<Ring>() {
+            let coordinates = healpix.pixel_to_face_coordinates(pixel).unwrap();
+            let centre = coordinates.centre(8).unwrap();
+            let (theta, phi) = centre.to_angle().unwrap();
+            // Pixel centres are computed from integers like HEALPix C++ does, so the
+            // continuous path agrees to within rounding.
+            let expected = healpix.pixel_to_angle(pixel).unwrap();
+            assert!((theta - expected.0).abs() < 1e-15 && (phi - expected.1).abs() < 1e-15);
+
+            let back = FractionalFaceCoordinates::from_angle(theta, phi);
+            assert_eq!(back.face, centre.face);
+            assert!((back.x - centre.x).abs() < 1e-12 && (back.y - centre.y).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn keeps_precision_at_the_poles() {
+        // Pixels at the deepest resolution are about 2e-9 rad across, so a point 1e-9 rad
+        // from the pole must keep its colatitude to well below that.
+        let nside = 1 << 29;
+        let point = FractionalFaceCoordinates::from_angle(1e-9, 0.5);
+        let (theta, phi) = point.to_angle().unwrap();
+        assert!((theta - 1e-9).abs() < 1e-15 && (phi - 0.5).abs() < 1e-6);
+        let coordinates = point.to_face_coordinates(nside);
+        assert!(coordinates.x > nside - 16 && coordinates.y > nside - 16);
+    }
+
+    #[test]
+    fn rejects_points_off_the_faces() {
+        let point = |face, x, y| FractionalFaceCoordinates { face, x, y };
+        assert!(point(11, 1.0, 0.0).to_angle().is_ok());
+        for invalid in [
+            point(12, 0.5, 0.5),
+            point(u8::MAX, 0.5, 0.5),
+            point(0, -0.1, 0.5),
+            point(0, 0.5, 1.5),
+            point(0, f64::NAN, 0.5),
+        ] {
+            assert!(matches!(
+                invalid.to_angle(),
+                Err(crate::Error::InvalidFaceCoordinates)
+            ));
+        }
+    }
+}
@@ -0,0 +1,30 @@
+/// Selects how values are combined or spread when changing the resolution of a map.
+///
+/// The result is scaled by `(nside_out / nside_in)^power`, so each mode is the same whether
+/// degrading or upgrading: [`GradeMode::Sum`] keeps the total of the map unchanged both ways.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GradeMode {
+    /// Degraded pixels hold the mean of their sub-pixels and upgraded pixels repeat the value
+    /// of their parent, suited to intensity maps.
+    #[default]
+    Mean,
+
+    /// Degraded pixels hold the sum of their sub-pixels and upgraded pixels share the value of
+    /// their parent, suited to hit counts. Equivalent to `Power(-2.0)`.
+    Sum,
+
+    /// The mean scaled by `(nside_out / nside_in)^power`, like healpy's `power` argument.
+    /// `Power(2.0)` suits variance maps.
+    Power(f64),
+}
+
+impl GradeMode {
+    /// Returns the exponent of `nside_out / nside_in` the values are scaled by.
+    pub(crate) fn power(self) -> f64 {
+        match self {
+            GradeMode::Mean => 0.0,
+            GradeMode::Sum => -2.0,
+            GradeMode::Power(power) => power,
+        }
+    }
+}
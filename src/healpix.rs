@@ -1,8 +1,70 @@
+use crate::cartesian_vector::CartesianVector;
+use crate::face_coordinates::FaceCoordinates;
+use crate::fixed_point::{fixed_to_hpd, fixed_vec_to_loc};
+#[cfg(feature = "std")]
 use crate::gnomonic_project::gnomonic_project;
-use crate::numbering_scheme::NumberingScheme;
+#[cfg(feature = "alloc")]
+use crate::grade_mode::GradeMode;
+#[cfg(feature = "alloc")]
+use crate::healpix_map::{UNSEEN, is_unseen};
+use crate::hierarchical_pixel::HierarchicalPixel;
+use crate::interpolation::interpolation_weights;
+#[cfg(feature = "alloc")]
+use crate::map_value::MapValue;
+#[cfg(feature = "std")]
+use crate::math;
+use crate::nested::Nested;
+use crate::numbering_scheme::{
+    NumberingScheme, assert_face_resolution, check_face_resolution, checked_hpd,
+};
 use crate::pixel::Pixel;
+#[cfg(feature = "alloc")]
+use crate::power_spectrum::{anafast, pixel_window};
+#[cfg(feature = "alloc")]
+use crate::query_box::query_box_ring;
+#[cfg(feature = "alloc")]
+use crate::query_disc::query_disc_nested_ranges;
+#[cfg(feature = "alloc")]
+use crate::query_mode::QueryMode;
+#[cfg(feature = "alloc")]
+use crate::query_strip::{query_strip_ring_ranges, ring_to_scheme};
+#[cfg(feature = "alloc")]
+use crate::reorder::reorder;
+use crate::ring::Ring;
+#[cfg(feature = "alloc")]
+use crate::spectrum_options::SpectrumOptions;
+#[cfg(feature = "alloc")]
+use crate::spherical_polygon::{SphericalPolygon, query_polygon};
+#[cfg(feature = "alloc")]
+use crate::ud_grade::ud_grade_nested;
+#[cfg(feature = "alloc")]
+use crate::vec3::Vec3;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::ops::Range;
+#[cfg(feature = "std")]
 use latlong::{Declination, Float, RaDec, RightAscension, TangentPosition};
 
+/// Converts RA/Dec to the colatitude `theta` and azimuth `phi`, in radians.
+#[cfg(feature = "std")]
+fn ra_dec_to_angle<T: Float>(ra_dec: &RaDec<T>) -> (f64, f64) {
+    let theta = core::f64::consts::FRAC_PI_2 - ra_dec.dec.radians().to_f64();
+    let phi = math::rem_euclid(ra_dec.ra.radians().to_f64(), core::f64::consts::TAU);
+    (theta, phi)
+}
+
+/// Converts the colatitude `theta` and azimuth `phi`, in radians, to RA/Dec.
+#[cfg(feature = "std")]
+fn angle_to_ra_dec<T: Float>(theta: f64, phi: f64) -> RaDec<T> {
+    let dec = core::f64::consts::FRAC_PI_2 - theta;
+    let ra = math::rem_euclid(phi, core::f64::consts::TAU);
+    RaDec {
+        ra: RightAscension::from_radians(T::from(ra)),
+        dec: Declination::from_radians(T::from(dec)),
+    }
+}
+
 /// A trait that defines the basic operations for a HEALPix (Hierarchical Equal Area isoLatitude Pixelization)
 /// grid structure. It provides methods for determining resolution, pixel counts, and coordinate transformations
 /// between angular positions and pixel indices based on a numbering scheme.
@@ -14,48 +76,768 @@ pub trait Healpix {
     fn face_resolution(&self) -> u32;
 
     /// Returns the total number of pixels in the HEALPix grid per face.
-    fn pixels_per_face(&self) -> u32;
+    fn pixels_per_face(&self) -> u64;
 
     /// Returns the total number of pixels in the HEALPix grid.
-    fn total_pixels(&self) -> u32;
+    fn total_pixels(&self) -> u64;
 
     /// Converts angular coordinates to pixel indices based on the HEALPix grid numbering scheme.
+    ///
+    /// The angles are not checked, so NaN, infinite values or a `theta` outside `[0, π]` still
+    /// produce a pixel. Use [`Self::try_angle_to_pixel`] for untrusted input.
+    ///
+    /// # Panics
+    /// Panics if `N` does not support the resolution; see [`crate::DynamicHealpix::new_for`].
     fn angle_to_pixel<N: NumberingScheme>(&self, theta: f64, phi: f64) -> Pixel<N> {
         N::angle_to_pixel(self.face_resolution(), theta, phi)
     }
 
+    /// Converts angular coordinates to a pixel index, rejecting angles that name no point on
+    /// the sphere.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidAngle`] unless `theta` lies within `[0, π]` and `phi` is
+    /// finite, [`crate::Error::ResolutionTooLarge`] if the resolution exceeds
+    /// [`crate::DynamicHealpix::MAX_FACE_RESOLUTION`] and
+    /// [`crate::Error::InvalidFaceResolution`] if `N` does not support it otherwise.
+    ///
+    /// # Example
+    /// ```rust
+    /// use realpix::{ConstHealpix, Error, Healpix, Nested, Pixel};
+    ///
+    /// let healpix = ConstHealpix::<16>::new();
+    /// let pixel: Pixel<Nested> = healpix.try_angle_to_pixel(1.0, 2.0).unwrap();
+    /// assert_eq!(pixel, healpix.angle_to_pixel(1.0, 2.0));
+    /// assert!(matches!(
+    ///     healpix.try_angle_to_pixel::<Nested>(f64::NAN, 2.0),
+    ///     Err(Error::InvalidAngle)
+    /// ));
+    /// ```
+    fn try_angle_to_pixel<N: NumberingScheme>(
+        &self,
+        theta: f64,
+        phi: f64,
+    ) -> crate::Result<Pixel<N>> {
+        N::try_angle_to_pixel(self.face_resolution(), theta, phi)
+    }
+
     /// Converts pixel indices to angular coordinates based on the HEALPix grid numbering scheme.
     fn pixel_to_angle<N: NumberingScheme>(&self, pixel: Pixel<N>) -> crate::Result<(f64, f64)> {
         N::pixel_to_angle(self.face_resolution(), pixel)
     }
 
+    /// Returns the pixel containing a point given in fixed point, using integer arithmetic only.
+    ///
+    /// `z` is `cos(theta)` scaled by `2^62`, so `1 << 62` is the north pole, and `phi` is the
+    /// azimuth as a fraction of a full turn scaled by `2^64`. This suits cores without an FPU.
+    /// Every rounding step is exact, so the result only differs from [`Self::angle_to_pixel`]
+    /// for points so close to a pixel boundary that the f64 path rounds them across it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use realpix::{ConstHealpix, Healpix, Pixel, Ring};
+    ///
+    /// let healpix = ConstHealpix::<1024>::new();
+    /// // theta = 1 and phi = 2, as a star tracker might deliver them.
+    /// let z = (1f64.cos() * (1u64 << 62) as f64) as i64;
+    /// let phi = (2.0 / std::f64::consts::TAU * 2f64.powi(64)) as u64;
+    /// let pixel: Pixel<Ring> = healpix.fixed_z_phi_to_pixel(z, phi);
+    /// assert_eq!(pixel, healpix.angle_to_pixel(1.0, 2.0));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `N` does not support the resolution; see [`crate::DynamicHealpix::new_for`].
+    fn fixed_z_phi_to_pixel<N: NumberingScheme>(&self, z: i64, phi: u64) -> Pixel<N> {
+        assert_face_resolution::<N>(self.face_resolution());
+        let hpd = fixed_to_hpd(self.face_resolution(), z, phi);
+        Pixel::from_u64(N::hpd_to_pixel(self.face_resolution(), hpd))
+    }
+
+    /// Returns the pixel containing the direction of a fixed-point vector, using integer
+    /// arithmetic only.
+    ///
+    /// The vector need not be normalised, so any fixed-point scale works. The reduction to
+    /// `(z, phi)` is done in integers too: `z` from an integer square root, and `phi` from the
+    /// octant of `(x, y)` and the arctangent of the ratio of its coordinates there, to within
+    /// `2^-62` of a turn. The result matches [`Self::angle_to_pixel`] except for points that
+    /// close to a pixel boundary.
+    ///
+    /// # Example
+    /// ```rust
+    /// use realpix::{ConstHealpix, Healpix, Pixel, Ring};
+    ///
+    /// let healpix = ConstHealpix::<1024>::new();
+    /// // theta = 1 and phi = 2, as a unit vector scaled by 2^62.
+    /// let scale = (1u64 << 62) as f64;
+    /// let (theta, phi) = (1f64, 2f64);
+    /// let vector = [
+    ///     (theta.sin() * phi.cos() * scale) as i64,
+    ///     (theta.sin() * phi.sin() * scale) as i64,
+    ///     (theta.cos() * scale) as i64,
+    /// ];
+    /// let pixel: Pixel<Ring> = healpix.fixed_vec_to_pixel(vector);
+    /// assert_eq!(pixel, healpix.angle_to_pixel(theta, phi));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `vector` is zero, or if `N` does not support the resolution; see
+    /// [`crate::DynamicHealpix::new_for`].
+    fn fixed_vec_to_pixel<N: NumberingScheme>(&self, vector: [i64; 3]) -> Pixel<N> {
+        assert_face_resolution::<N>(self.face_resolution());
+        let (z, phi) = fixed_vec_to_loc(vector);
+        let hpd = fixed_to_hpd(self.face_resolution(), z, phi);
+        Pixel::from_u64(N::hpd_to_pixel(self.face_resolution(), hpd))
+    }
+
+    /// Returns the pixel containing the direction of `vector`, which need not be normalised.
+    ///
+    /// This works from `z = cos(theta)` directly, with no `acos`, and keeps full precision near
+    /// the poles. See [`CartesianVector`] for the supported vector types.
+    ///
+    /// # Panics
+    /// Panics if `N` does not support the resolution; see [`crate::DynamicHealpix::new_for`].
+    fn vec_to_pixel<N: NumberingScheme, V: CartesianVector>(&self, vector: &V) -> Pixel<N> {
+        assert_face_resolution::<N>(self.face_resolution());
+        let hpd = Ring::vec2hpd(self.face_resolution(), vector.to_xyz());
+        Pixel::from_u64(N::hpd_to_pixel(self.face_resolution(), hpd))
+    }
+
+    /// Like [`Self::vec_to_pixel`], but rejects vectors that have no direction.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidAngle`] if `vector` is zero or has a non-finite
+    /// component, [`crate::Error::ResolutionTooLarge`] if the resolution exceeds
+    /// [`crate::DynamicHealpix::MAX_FACE_RESOLUTION`] and
+    /// [`crate::Error::InvalidFaceResolution`] if `N` does not support it otherwise.
+    fn try_vec_to_pixel<N: NumberingScheme, V: CartesianVector>(
+        &self,
+        vector: &V,
+    ) -> crate::Result<Pixel<N>> {
+        check_face_resolution::<N>(self.face_resolution())?;
+        let xyz = vector.to_xyz();
+        if xyz.iter().any(|c| !c.is_finite()) || xyz.iter().all(|&c| c == 0.0) {
+            return Err(crate::Error::InvalidAngle);
+        }
+        Ok(self.vec_to_pixel(vector))
+    }
+
+    /// Returns the unit vector pointing to the centre of `pixel`.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPixel`] if the pixel is out of bounds and
+    /// [`crate::Error::InvalidFaceResolution`] if `N` does not support the resolution.
+    fn pixel_to_vec<N: NumberingScheme, V: CartesianVector>(
+        &self,
+        pixel: Pixel<N>,
+    ) -> crate::Result<V> {
+        let hpd = checked_hpd::<N>(self.face_resolution(), pixel.as_u64())?;
+        Ok(V::from_xyz(Ring::hpd2vec(
+            self.face_resolution() as i64,
+            &hpd,
+        )))
+    }
+
+    /// Converts RA/Dec to a pixel index.
+    ///
+    /// The coordinates are not checked, so NaN or a Dec beyond the poles still produce a
+    /// pixel. Use [`Self::try_ra_dec_to_pixel`] for untrusted input.
+    ///
+    /// # Panics
+    /// Panics if `N` does not support the resolution; see [`crate::DynamicHealpix::new_for`].
+    #[cfg(feature = "std")]
     fn ra_dec_to_pixel<N: NumberingScheme, T: Float>(&self, ra_dec: &RaDec<T>) -> Pixel<N> {
-        let theta = core::f64::consts::FRAC_PI_2 - ra_dec.dec.radians().to_f64();
-        let phi = ra_dec
-            .ra
-            .radians()
-            .to_f64()
-            .rem_euclid(core::f64::consts::TAU);
+        let (theta, phi) = ra_dec_to_angle(ra_dec);
         N::angle_to_pixel(self.face_resolution(), theta, phi)
     }
 
+    /// Converts RA/Dec to a pixel index, rejecting coordinates that name no point on the sky.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidRaDec`] unless RA is finite and Dec lies within
+    /// `[-90, 90]` degrees, [`crate::Error::ResolutionTooLarge`] if the resolution exceeds
+    /// [`crate::DynamicHealpix::MAX_FACE_RESOLUTION`] and
+    /// [`crate::Error::InvalidFaceResolution`] if `N` does not support it otherwise.
+    #[cfg(feature = "std")]
+    fn try_ra_dec_to_pixel<N: NumberingScheme, T: Float>(
+        &self,
+        ra_dec: &RaDec<T>,
+    ) -> crate::Result<Pixel<N>> {
+        check_face_resolution::<N>(self.face_resolution())?;
+        let ra = ra_dec.ra.radians().to_f64();
+        let dec = ra_dec.dec.degrees().to_f64();
+        if !ra.is_finite() || !(-90.0..=90.0).contains(&dec) {
+            return Err(crate::Error::InvalidRaDec);
+        }
+        Ok(self.ra_dec_to_pixel(ra_dec))
+    }
+
+    #[cfg(feature = "std")]
     fn pixel_to_ra_dec<N: NumberingScheme, T: Float>(
         &self,
         pixel: Pixel<N>,
     ) -> crate::Result<RaDec<T>> {
         let (theta, phi) = N::pixel_to_angle(self.face_resolution(), pixel)?;
-        let dec = core::f64::consts::FRAC_PI_2 - theta;
-        let ra = phi.rem_euclid(core::f64::consts::TAU);
-        Ok(RaDec {
-            ra: RightAscension::from_radians(T::from(ra)),
-            dec: Declination::from_radians(T::from(dec)),
-        })
+        Ok(angle_to_ra_dec(theta, phi))
+    }
+
+    /// Returns the 4 pixels surrounding `(theta, phi)` and their bilinear interpolation weights,
+    /// like healpy's `get_interp_weights`.
+    ///
+    /// The first two pixels are the neighbours in azimuth on the ring north of the point and
+    /// the last two those on the ring south of it. Near a pole, where one ring is missing, the
+    /// 4 pixels around the pole share its weight. The weights sum to 1. The arithmetic follows
+    /// HEALPix C++'s `get_interpol`, on which healpy's function is built.
+    ///
+    /// # Panics
+    /// Panics if `N` does not support the resolution; see [`crate::DynamicHealpix::new_for`].
+    fn get_interpolation_weights<N: NumberingScheme>(
+        &self,
+        theta: f64,
+        phi: f64,
+    ) -> ([Pixel<N>; 4], [f64; 4]) {
+        let face_resolution = self.face_resolution();
+        assert_face_resolution::<N>(face_resolution);
+        let (pixels, weights) = interpolation_weights(face_resolution, theta, phi);
+        let pixels = pixels.map(|pixel| {
+            let hpd = Ring::pixel_to_hpd(face_resolution, pixel);
+            Pixel::from_u64(N::hpd_to_pixel(face_resolution, hpd))
+        });
+        (pixels, weights)
+    }
+
+    /// Bilinearly interpolates a map holding one value per pixel, in the ordering of scheme
+    /// `N`, at `(theta, phi)`, with the weights of [`Self::get_interpolation_weights`].
+    ///
+    /// `UNSEEN` and NaN values are left out and the remaining weights rescaled, as HEALPix
+    /// C++'s `Healpix_Map::interpolated_value` does; the result is `UNSEEN` when all 4 values
+    /// are. This differs from healpy's `get_interp_val`, which applies the weights to `UNSEEN`
+    /// values like any other, whenever one of the 4 values is `UNSEEN` or NaN.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidMapSize`] if `map` does not hold exactly one value per pixel
+    /// and [`crate::Error::InvalidFaceResolution`] if `N` does not support the resolution.
+    #[cfg(feature = "alloc")]
+    fn interpolate_angle<N: NumberingScheme, V: MapValue>(
+        &self,
+        map: &[V],
+        theta: f64,
+        phi: f64,
+    ) -> crate::Result<V> {
+        check_face_resolution::<N>(self.face_resolution())?;
+        if map.len() != self.total_pixels() as usize {
+            return Err(crate::Error::InvalidMapSize);
+        }
+        let (pixels, weights) = self.get_interpolation_weights::<N>(theta, phi);
+
+        let (mut sum, mut total) = (0.0, 0.0);
+        for (pixel, weight) in pixels.iter().zip(weights) {
+            let value = map[pixel.as_u64() as usize].to_f64();
+            if !value.is_nan() && !is_unseen(value) {
+                sum += weight * value;
+                total += weight;
+            }
+        }
+        Ok(V::from_f64(if total > 0.0 { sum / total } else { UNSEEN }))
+    }
+
+    /// Bilinearly interpolates a map holding one value per pixel at a point. See
+    /// [`Healpix::interpolate_angle`].
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidMapSize`] if `map` does not hold exactly one value per pixel
+    /// and [`crate::Error::InvalidFaceResolution`] if `N` does not support the resolution.
+    #[cfg(feature = "std")]
+    fn interpolate<N: NumberingScheme, T: Float, V: MapValue>(
+        &self,
+        map: &[V],
+        ra_dec: &RaDec<T>,
+    ) -> crate::Result<V> {
+        let (theta, phi) = ra_dec_to_angle(ra_dec);
+        self.interpolate_angle::<N, V>(map, theta, phi)
+    }
+
+    /// Returns the neighbours of a pixel in the compass order S, SW, W, NW, N, NE, E, SE.
+    ///
+    /// At the 8 base-face corners where only 7 neighbours exist, the missing one is `None`.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPixel`] if the pixel is out of bounds and
+    /// [`crate::Error::InvalidFaceResolution`] if `N` does not support the resolution.
+    fn neighbours<N: NumberingScheme>(
+        &self,
+        pixel: Pixel<N>,
+    ) -> crate::Result<[Option<Pixel<N>>; 8]> {
+        N::neighbours(self.face_resolution(), pixel)
+    }
+
+    /// Returns the `(theta, phi)` of the 4 corners of a pixel in the order N, W, S, E.
+    ///
+    /// At the poles the azimuth of a corner is undefined and reported as `phi = 0`.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPixel`] if the pixel is out of bounds and
+    /// [`crate::Error::InvalidFaceResolution`] if `N` does not support the resolution.
+    fn pixel_corners_angle<N: NumberingScheme>(
+        &self,
+        pixel: Pixel<N>,
+    ) -> crate::Result<[(f64, f64); 4]> {
+        N::pixel_corners(self.face_resolution(), pixel)
+    }
+
+    /// Returns the 4 corners of a pixel in the order N, W, S, E.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPixel`] if the pixel is out of bounds and
+    /// [`crate::Error::InvalidFaceResolution`] if `N` does not support the resolution.
+    #[cfg(feature = "std")]
+    fn pixel_corners<N: NumberingScheme, T: Float>(
+        &self,
+        pixel: Pixel<N>,
+    ) -> crate::Result<[RaDec<T>; 4]> {
+        let corners = N::pixel_corners(self.face_resolution(), pixel)?;
+        Ok(corners.map(|(theta, phi)| angle_to_ra_dec(theta, phi)))
+    }
+
+    /// Returns `4 * step` points `(theta, phi)` tracing the boundary of a pixel, starting at
+    /// its N corner and going through the W, S and E corners.
+    ///
+    /// Pixel edges are not great circles, so larger `step`s follow their curvature more
+    /// closely. A `step` of 1 returns the corners.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPixel`] if the pixel is out of bounds,
+    /// [`crate::Error::ResolutionTooLarge`] if the resolution exceeds
+    /// [`crate::DynamicHealpix::MAX_FACE_RESOLUTION`] and
+    /// [`crate::Error::InvalidFaceResolution`] if `N` does not support it otherwise.
+    #[cfg(feature = "alloc")]
+    fn pixel_boundary_angle<N: NumberingScheme>(
+        &self,
+        pixel: Pixel<N>,
+        step: usize,
+    ) -> crate::Result<Vec<(f64, f64)>> {
+        N::pixel_boundary(self.face_resolution(), pixel, step)
+    }
+
+    /// Returns `4 * step` points tracing the boundary of a pixel. See
+    /// [`Healpix::pixel_boundary_angle`].
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPixel`] if the pixel is out of bounds,
+    /// [`crate::Error::ResolutionTooLarge`] if the resolution exceeds
+    /// [`crate::DynamicHealpix::MAX_FACE_RESOLUTION`] and
+    /// [`crate::Error::InvalidFaceResolution`] if `N` does not support it otherwise.
+    #[cfg(feature = "std")]
+    fn pixel_boundary<N: NumberingScheme, T: Float>(
+        &self,
+        pixel: Pixel<N>,
+        step: usize,
+    ) -> crate::Result<Vec<RaDec<T>>> {
+        let points = N::pixel_boundary(self.face_resolution(), pixel, step)?;
+        Ok(points
+            .into_iter()
+            .map(|(theta, phi)| angle_to_ra_dec(theta, phi))
+            .collect())
+    }
+
+    /// Returns the pixels selected by a disc (cone) of angular `radius`, in radians, around
+    /// `(theta, phi)`, sorted by pixel index.
+    ///
+    /// With [`QueryMode::Exact`] only pixels whose centre falls inside the disc are returned,
+    /// with [`QueryMode::Inclusive`] every pixel overlapping the disc is returned.
+    ///
+    /// # Panics
+    /// Panics if `N` does not support the resolution; see [`crate::DynamicHealpix::new_for`].
+    #[cfg(feature = "alloc")]
+    fn query_disc_angle<N: NumberingScheme>(
+        &self,
+        theta: f64,
+        phi: f64,
+        radius: f64,
+        mode: QueryMode,
+    ) -> Vec<Pixel<N>> {
+        N::query_disc(self.face_resolution(), theta, phi, radius, mode)
+    }
+
+    /// Returns the pixels selected by a disc (cone) of angular `radius`, in radians, around
+    /// `centre`, sorted by pixel index. See [`Healpix::query_disc_angle`].
+    ///
+    /// # Panics
+    /// Panics if `N` does not support the resolution; see [`crate::DynamicHealpix::new_for`].
+    #[cfg(feature = "std")]
+    fn query_disc<N: NumberingScheme, T: Float>(
+        &self,
+        centre: &RaDec<T>,
+        radius: f64,
+        mode: QueryMode,
+    ) -> Vec<Pixel<N>> {
+        let (theta, phi) = ra_dec_to_angle(centre);
+        N::query_disc(self.face_resolution(), theta, phi, radius, mode)
+    }
+
+    /// Returns the pixels selected by a disc query around `(theta, phi)` as sorted,
+    /// non-overlapping ranges of [`crate::Nested`] pixel indices.
+    ///
+    /// The ranges are built by descending the nested quad-tree, so the cost grows with the
+    /// length of the disc boundary rather than with the number of pixels inside it.
+    ///
+    /// # Panics
+    /// Panics if the face resolution is not a power of two.
+    #[cfg(feature = "alloc")]
+    fn query_disc_ranges_angle(
+        &self,
+        theta: f64,
+        phi: f64,
+        radius: f64,
+        mode: QueryMode,
+    ) -> Vec<Range<u64>> {
+        assert_face_resolution::<Nested>(self.face_resolution());
+        query_disc_nested_ranges(self.face_resolution() as i64, theta, phi, radius, mode)
+    }
+
+    /// Returns the pixels selected by a disc query around `centre` as sorted, non-overlapping
+    /// ranges of [`crate::Nested`] pixel indices. See [`Healpix::query_disc_ranges_angle`].
+    ///
+    /// # Panics
+    /// Panics if the face resolution is not a power of two.
+    #[cfg(feature = "std")]
+    fn query_disc_ranges<T: Float>(
+        &self,
+        centre: &RaDec<T>,
+        radius: f64,
+        mode: QueryMode,
+    ) -> Vec<Range<u64>> {
+        let (theta, phi) = ra_dec_to_angle(centre);
+        self.query_disc_ranges_angle(theta, phi, radius, mode)
+    }
+
+    /// Returns the pixels inside a spherical polygon whose vertices, given as `(theta, phi)`,
+    /// are joined by great-circle edges, sorted by pixel index.
+    ///
+    /// The polygon may be non-convex, straddle RA = 0 or enclose a pole. Of the two regions
+    /// bounded by the edges, the smaller one is taken as the inside, so the winding order of
+    /// the vertices does not matter. With [`QueryMode::Exact`] only pixels whose centre is
+    /// inside are returned, with [`QueryMode::Inclusive`] every overlapping pixel is returned.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPolygon`] if fewer than 3 distinct vertices are given
+    /// and [`crate::Error::InvalidFaceResolution`] if `N` does not support the resolution.
+    #[cfg(feature = "alloc")]
+    fn query_polygon_angle<N: NumberingScheme>(
+        &self,
+        vertices: &[(f64, f64)],
+        mode: QueryMode,
+    ) -> crate::Result<Vec<Pixel<N>>> {
+        check_face_resolution::<N>(self.face_resolution())?;
+        let polygon = SphericalPolygon::new(
+            vertices
+                .iter()
+                .map(|&(theta, phi)| Vec3::from_angle(theta, phi)),
+        )
+        .ok_or(crate::Error::InvalidPolygon)?;
+        Ok(query_polygon(self.face_resolution(), &polygon, mode))
+    }
+
+    /// Returns the pixels inside a spherical polygon with RA/Dec vertices, sorted by pixel
+    /// index. See [`Healpix::query_polygon_angle`].
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPolygon`] if fewer than 3 distinct vertices are given
+    /// and [`crate::Error::InvalidFaceResolution`] if `N` does not support the resolution.
+    #[cfg(feature = "std")]
+    fn query_polygon<N: NumberingScheme, T: Float>(
+        &self,
+        vertices: &[RaDec<T>],
+        mode: QueryMode,
+    ) -> crate::Result<Vec<Pixel<N>>> {
+        let vertices: Vec<_> = vertices.iter().map(ra_dec_to_angle).collect();
+        self.query_polygon_angle(&vertices, mode)
+    }
+
+    /// Returns the pixels between the colatitudes `theta1` and `theta2`, in radians, sorted by
+    /// pixel index.
+    ///
+    /// If `theta1 > theta2` the strip wraps over the poles, selecting the pixels north of
+    /// `theta2` and south of `theta1`. With [`QueryMode::Exact`] only pixels whose centre is
+    /// inside the strip are returned, with [`QueryMode::Inclusive`] every overlapping pixel is
+    /// returned.
+    ///
+    /// # Panics
+    /// Panics if `N` does not support the resolution; see [`crate::DynamicHealpix::new_for`].
+    #[cfg(feature = "alloc")]
+    fn query_strip<N: NumberingScheme>(
+        &self,
+        theta1: f64,
+        theta2: f64,
+        mode: QueryMode,
+    ) -> Vec<Pixel<N>> {
+        assert_face_resolution::<N>(self.face_resolution());
+        let ranges = query_strip_ring_ranges(self.face_resolution() as i64, theta1, theta2, mode);
+        ring_to_scheme(self.face_resolution(), ranges.into_iter().flatten())
+    }
+
+    /// Returns the pixels of a strip query as ranges of [`crate::Ring`] pixel indices.
+    ///
+    /// Each latitude ring is a contiguous block in ring ordering, so a strip is a single range,
+    /// or two when it wraps over the poles. See [`Healpix::query_strip`].
+    #[cfg(feature = "alloc")]
+    fn query_strip_ranges(&self, theta1: f64, theta2: f64, mode: QueryMode) -> Vec<Range<u64>> {
+        query_strip_ring_ranges(self.face_resolution() as i64, theta1, theta2, mode)
+    }
+
+    /// Returns the pixels inside the box of azimuths `[phi_min, phi_max]` and colatitudes
+    /// `[theta_min, theta_max]`, in radians, sorted by pixel index.
+    ///
+    /// If `phi_min > phi_max` the azimuth range wraps through 0. With [`QueryMode::Exact`] only
+    /// pixels whose centre is inside the box are returned, with [`QueryMode::Inclusive`] every
+    /// overlapping pixel is returned.
+    ///
+    /// # Panics
+    /// Panics if `N` does not support the resolution; see [`crate::DynamicHealpix::new_for`].
+    #[cfg(feature = "alloc")]
+    fn query_box_angle<N: NumberingScheme>(
+        &self,
+        phi_min: f64,
+        phi_max: f64,
+        theta_min: f64,
+        theta_max: f64,
+        mode: QueryMode,
+    ) -> Vec<Pixel<N>> {
+        assert_face_resolution::<N>(self.face_resolution());
+        let pixels = query_box_ring(
+            self.face_resolution() as i64,
+            phi_min,
+            phi_max,
+            core::f64::consts::FRAC_PI_2 - theta_max,
+            core::f64::consts::FRAC_PI_2 - theta_min,
+            mode,
+        );
+        ring_to_scheme(self.face_resolution(), pixels)
+    }
+
+    /// Returns the pixels inside the RA/Dec box `[ra_min, ra_max] × [dec_min, dec_max]`, sorted
+    /// by pixel index. If `ra_min > ra_max` the RA range wraps through 0h. See
+    /// [`Healpix::query_box_angle`].
+    ///
+    /// # Panics
+    /// Panics if `N` does not support the resolution; see [`crate::DynamicHealpix::new_for`].
+    #[cfg(feature = "std")]
+    fn query_box<N: NumberingScheme, T: Float>(
+        &self,
+        ra_min: RightAscension<T>,
+        ra_max: RightAscension<T>,
+        dec_min: Declination<T>,
+        dec_max: Declination<T>,
+        mode: QueryMode,
+    ) -> Vec<Pixel<N>> {
+        let theta = |dec: Declination<T>| core::f64::consts::FRAC_PI_2 - dec.radians().to_f64();
+        self.query_box_angle(
+            ra_min.radians().to_f64(),
+            ra_max.radians().to_f64(),
+            theta(dec_max),
+            theta(dec_min),
+            mode,
+        )
+    }
+
+    /// Converts a pixel from numbering scheme `F` to the same pixel in scheme `T`.
+    ///
+    /// The conversion goes through the face coordinates both schemes share, so it is exact.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPixel`] if the pixel is out of bounds and
+    /// [`crate::Error::InvalidFaceResolution`] if either scheme does not support the resolution.
+    fn convert_pixel<F: NumberingScheme, T: NumberingScheme>(
+        &self,
+        pixel: Pixel<F>,
+    ) -> crate::Result<Pixel<T>> {
+        check_face_resolution::<T>(self.face_resolution())?;
+        let hpd = checked_hpd::<F>(self.face_resolution(), pixel.as_u64())?;
+        Ok(Pixel::from_u64(T::hpd_to_pixel(
+            self.face_resolution(),
+            hpd,
+        )))
+    }
+
+    /// Converts a [`Nested`] pixel to the same pixel in [`Ring`] ordering.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPixel`] if the pixel is out of bounds and
+    /// [`crate::Error::InvalidFaceResolution`] if the face resolution is not a power of two.
+    fn nest2ring(&self, pixel: Pixel<Nested>) -> crate::Result<Pixel<Ring>> {
+        self.convert_pixel(pixel)
+    }
+
+    /// Converts a [`Ring`] pixel to the same pixel in [`Nested`] ordering.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPixel`] if the pixel is out of bounds and
+    /// [`crate::Error::InvalidFaceResolution`] if the face resolution is not a power of two.
+    fn ring2nest(&self, pixel: Pixel<Ring>) -> crate::Result<Pixel<Nested>> {
+        self.convert_pixel(pixel)
+    }
+
+    /// Returns the base face of `pixel` and its position inside that face.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPixel`] if the pixel is out of bounds and
+    /// [`crate::Error::InvalidFaceResolution`] if `N` does not support the resolution.
+    fn pixel_to_face_coordinates<N: NumberingScheme>(
+        &self,
+        pixel: Pixel<N>,
+    ) -> crate::Result<FaceCoordinates> {
+        Ok(checked_hpd::<N>(self.face_resolution(), pixel.as_u64())?.into())
+    }
+
+    /// Returns the pixel at the given position inside a base face.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPixel`] if the face or position is off the grid and
+    /// [`crate::Error::InvalidFaceResolution`] if `N` does not support the resolution.
+    fn face_coordinates_to_pixel<N: NumberingScheme>(
+        &self,
+        coordinates: FaceCoordinates,
+    ) -> crate::Result<Pixel<N>> {
+        check_face_resolution::<N>(self.face_resolution())?;
+        let hpd = coordinates.to_hpd(self.face_resolution())?;
+        Ok(Pixel::from_u64(N::hpd_to_pixel(
+            self.face_resolution(),
+            hpd,
+        )))
+    }
+
+    /// Attaches the depth of this grid to a [`Nested`] pixel, so it can be navigated up and
+    /// down the quad-tree.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPixel`] if the pixel is out of bounds and
+    /// [`crate::Error::InvalidFaceResolution`] if the face resolution is not a power of two.
+    fn hierarchical_pixel(&self, pixel: Pixel<Nested>) -> crate::Result<HierarchicalPixel> {
+        check_face_resolution::<Nested>(self.face_resolution())?;
+        HierarchicalPixel::new(self.face_resolution().trailing_zeros() as u8, pixel)
+    }
+
+    /// Permutes a map holding one value per pixel from the ordering of scheme `F` to that of
+    /// scheme `T`, in place.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidMapSize`] if `map` does not hold exactly one value per pixel
+    /// and [`crate::Error::InvalidFaceResolution`] if either scheme does not support the
+    /// resolution.
+    #[cfg(feature = "alloc")]
+    fn reorder_map<F: NumberingScheme, T: NumberingScheme, V>(
+        &self,
+        map: &mut [V],
+    ) -> crate::Result<()> {
+        if map.len() != self.total_pixels() as usize {
+            return Err(crate::Error::InvalidMapSize);
+        }
+        check_face_resolution::<F>(self.face_resolution())?;
+        check_face_resolution::<T>(self.face_resolution())?;
+        reorder::<F, T, V>(self.face_resolution(), map);
+        Ok(())
+    }
+
+    /// Permutes a [`Nested`]-ordered map to [`Ring`] ordering, in place.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidMapSize`] if `map` does not hold exactly one value per pixel
+    /// and [`crate::Error::InvalidFaceResolution`] if the face resolution is not a power of two.
+    #[cfg(feature = "alloc")]
+    fn reorder_nest2ring<V>(&self, map: &mut [V]) -> crate::Result<()> {
+        self.reorder_map::<Nested, Ring, V>(map)
+    }
+
+    /// Permutes a [`Ring`]-ordered map to [`Nested`] ordering, in place.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidMapSize`] if `map` does not hold exactly one value per pixel
+    /// and [`crate::Error::InvalidFaceResolution`] if the face resolution is not a power of two.
+    #[cfg(feature = "alloc")]
+    fn reorder_ring2nest<V>(&self, map: &mut [V]) -> crate::Result<()> {
+        self.reorder_map::<Ring, Nested, V>(map)
+    }
+
+    /// Changes the resolution of a map holding one value per pixel, in the ordering of scheme
+    /// `N`, to `face_resolution`, returning the new map in the same ordering.
+    ///
+    /// Degrading combines the values of the sub-pixels of each output pixel and upgrading
+    /// spreads the value of each pixel over its sub-pixels, as selected by `mode`. `UNSEEN` and
+    /// NaN values are ignored when combining, and output pixels with no valid input are
+    /// `UNSEEN`. [`Ring`] maps are converted to [`Nested`] ordering internally.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidMapSize`] if `map` does not hold exactly one value per
+    /// pixel, [`crate::Error::InvalidFaceResolution`] if either resolution is not a power of two
+    /// and [`crate::Error::ResolutionTooLarge`] if `face_resolution` exceeds
+    /// [`crate::DynamicHealpix::MAX_FACE_RESOLUTION`].
+    #[cfg(feature = "alloc")]
+    fn ud_grade<N: NumberingScheme, T: MapValue>(
+        &self,
+        map: &[T],
+        face_resolution: u32,
+        mode: GradeMode,
+    ) -> crate::Result<Vec<T>> {
+        if map.len() != self.total_pixels() as usize {
+            return Err(crate::Error::InvalidMapSize);
+        }
+        let nside_in = self.face_resolution();
+        check_face_resolution::<Nested>(nside_in)?;
+        check_face_resolution::<Nested>(face_resolution)?;
+
+        let mut nested = map.to_vec();
+        reorder::<N, Nested, T>(nside_in, &mut nested);
+        let mut graded = ud_grade_nested(&nested, nside_in, face_resolution, mode);
+        reorder::<Nested, N, T>(face_resolution, &mut graded);
+        Ok(graded)
+    }
+
+    /// Estimates the angular power spectrum `C_l` of a [`Ring`]-ordered map holding one value
+    /// per pixel, like healpy's `anafast`.
+    ///
+    /// The map is analysed into spherical harmonic coefficients with [`crate::Alm`]'s
+    /// transforms, then `C_l` is averaged over `m`. `UNSEEN` and NaN pixels count as 0. See
+    /// [`SpectrumOptions`] for masking and pixel-window deconvolution.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidMapSize`] if the map or mask does not hold exactly one value
+    /// per pixel and [`crate::Error::EmptyMask`] if the mask is zero everywhere.
+    #[cfg(feature = "alloc")]
+    fn anafast(&self, map: &[f64], options: &SpectrumOptions) -> crate::Result<Vec<f64>> {
+        anafast(self.face_resolution(), map, None, options)
+    }
+
+    /// Estimates the cross power spectrum of two [`Ring`]-ordered maps, like healpy's
+    /// `anafast(map1, map2)`. See [`Healpix::anafast`].
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidMapSize`] if a map or the mask does not hold exactly one
+    /// value per pixel and [`crate::Error::EmptyMask`] if the mask is zero everywhere.
+    #[cfg(feature = "alloc")]
+    fn anafast_cross(
+        &self,
+        map1: &[f64],
+        map2: &[f64],
+        options: &SpectrumOptions,
+    ) -> crate::Result<Vec<f64>> {
+        anafast(self.face_resolution(), map1, Some(map2), options)
+    }
+
+    /// Returns the pixel window function `w_l` for `l` up to `lmax`: the attenuation of
+    /// multipole `l` caused by averaging the sky over pixels, like healpy's `pixwin`.
+    ///
+    /// It is computed by integrating over a few hundred pixels with Gauss-Legendre quadrature,
+    /// which has converged to about `1e-10`, rather than read from the precomputed tables healpy
+    /// ships. The cost is linear in `lmax` at any resolution.
+    #[cfg(feature = "alloc")]
+    fn pixel_window(&self, lmax: usize) -> Vec<f64> {
+        pixel_window(self.face_resolution(), lmax)
     }
 
     fn iter_pixels<N: NumberingScheme>(&self) -> impl Iterator<Item = Pixel<N>> + '_ {
-        (0..self.total_pixels()).map(|index| Pixel::from_u64(index as u64))
+        (0..self.total_pixels()).map(|index| Pixel::from_u64(index))
     }
 
+    #[cfg(feature = "std")]
     fn project_ra_dec<N: NumberingScheme, T: Float>(
         &self,
         pixel: Pixel<N>,
@@ -73,7 +855,13 @@ pub trait Healpix {
 
 #[cfg(test)]
 mod tests {
-    use crate::{ConstHealpix, Healpix, Nested};
+    use crate::math;
+    use crate::{
+        ConstHealpix, DynamicHealpix, Error, FractionalFaceCoordinates, Healpix, Nested,
+        NumberingScheme, Pixel, Ring,
+    };
+    #[cfg(feature = "std")]
+    use latlong::{Declination, RaDec, RightAscension};
 
     const HEALPIX: ConstHealpix<32> = ConstHealpix::new();
 
@@ -82,4 +870,551 @@ mod tests {
         let count = HEALPIX.iter_pixels::<Nested>().count();
         assert_eq!(count, HEALPIX.total_pixels() as usize);
     }
+
+    #[test]
+    fn ring_grids_allow_any_resolution() {
+        let healpix = DynamicHealpix::new_for::<Ring>(6).unwrap();
+        assert_eq!(healpix.total_pixels(), 432);
+        assert!(DynamicHealpix::new(6).is_err());
+        assert!(DynamicHealpix::new_for::<Ring>(0).is_err());
+        assert!(DynamicHealpix::new_for::<Nested>(1 << 10).is_ok());
+
+        for pixel in healpix.iter_pixels::<Ring>() {
+            for neighbour in healpix.neighbours(pixel).unwrap().into_iter().flatten() {
+                let back = healpix.neighbours(neighbour).unwrap();
+                assert!(back.contains(&Some(pixel)));
+            }
+        }
+
+        let pixel = Pixel::<Ring>::from_u64(100);
+        assert!(healpix.ring2nest(pixel).is_err());
+        assert!(
+            healpix
+                .pixel_to_angle(Pixel::<Nested>::from_u64(100))
+                .is_err()
+        );
+        assert!(healpix.neighbours(Pixel::<Nested>::from_u64(100)).is_err());
+        #[cfg(feature = "alloc")]
+        assert!(healpix.reorder_ring2nest(&mut [0u8; 432]).is_err());
+        let _ = ConstHealpix::<3000>::new_ring();
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn nested_operations_on_ring_grids_return_errors() {
+        use crate::QueryMode;
+        let healpix = DynamicHealpix::new_for::<Ring>(3).unwrap();
+        assert!(matches!(
+            healpix.interpolate_angle::<Nested, _>(&[0.0; 108], 1.0, 2.0),
+            Err(Error::InvalidFaceResolution)
+        ));
+        let vertices = [(0.5, 0.0), (1.0, 0.5), (1.0, 0.0)];
+        assert!(matches!(
+            healpix.query_polygon_angle::<Nested>(&vertices, QueryMode::Exact),
+            Err(Error::InvalidFaceResolution)
+        ));
+        assert!(matches!(
+            crate::HealpixMap::<f64, Nested>::filled(&healpix, 0.0),
+            Err(Error::InvalidFaceResolution)
+        ));
+        let ring = crate::HealpixMap::<f64, Ring>::filled(&healpix, 0.0).unwrap();
+        assert!(matches!(
+            ring.into_nested(),
+            Err(Error::InvalidFaceResolution)
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "face resolution")]
+    fn vec_to_pixel_panics_for_nested_on_ring_grids() {
+        let healpix = DynamicHealpix::new_for::<Ring>(3).unwrap();
+        let _: Pixel<Nested> = healpix.vec_to_pixel(&[0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn fixed_vec_to_pixel_should_match_angle_to_pixel() {
+        let scale = (1u64 << 62) as f64;
+        let points = 20_000;
+        for nside in [1, 2, 5, 64, 1 << 13, 1 << 29] {
+            let healpix = DynamicHealpix::new_for::<Ring>(nside).unwrap();
+            for i in 0..points {
+                // A Fibonacci lattice, which covers the sphere evenly without landing on the
+                // pixel boundaries.
+                let theta = math::acos(1.0 - 2.0 * (i as f64 + 0.5) / points as f64);
+                let phi =
+                    math::rem_euclid(i as f64 * 2.399_963_229_728_653, core::f64::consts::TAU);
+                let vector = [
+                    (math::sin(theta) * math::cos(phi) * scale) as i64,
+                    (math::sin(theta) * math::sin(phi) * scale) as i64,
+                    (math::cos(theta) * scale) as i64,
+                ];
+                if nside.is_power_of_two() {
+                    assert_eq!(
+                        healpix.fixed_vec_to_pixel::<Nested>(vector),
+                        healpix.angle_to_pixel::<Nested>(theta, phi),
+                        "nside {nside}, theta {theta}, phi {phi}"
+                    );
+                }
+                assert_eq!(
+                    healpix.fixed_vec_to_pixel::<Ring>(vector),
+                    healpix.angle_to_pixel::<Ring>(theta, phi),
+                    "nside {nside}, theta {theta}, phi {phi}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_vec_to_pixel_should_ignore_the_scale() {
+        for vector in [[3, 4, 5], [-7, 2, -1], [0, 0, 1], [0, -1, 0], [-1, 0, 0]] {
+            let pixel = HEALPIX.fixed_vec_to_pixel::<Nested>(vector);
+            let scaled = vector.map(|c| c << 40);
+            assert_eq!(HEALPIX.fixed_vec_to_pixel::<Nested>(scaled), pixel);
+            let [x, y, z] = vector.map(|c| c as f64);
+            assert_eq!(HEALPIX.vec_to_pixel::<Nested, _>(&[x, y, z]), pixel);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "zero vector")]
+    fn fixed_vec_to_pixel_panics_for_the_zero_vector() {
+        let _: Pixel<Nested> = HEALPIX.fixed_vec_to_pixel([0, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "face resolution")]
+    fn fixed_z_phi_to_pixel_panics_for_nested_on_ring_grids() {
+        let healpix = DynamicHealpix::new_for::<Ring>(3).unwrap();
+        let _: Pixel<Nested> = healpix.fixed_z_phi_to_pixel(0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "face resolution")]
+    fn interpolation_weights_panic_for_nested_on_ring_grids() {
+        let healpix = DynamicHealpix::new_for::<Ring>(3).unwrap();
+        let _ = healpix.get_interpolation_weights::<Nested>(1.0, 2.0);
+    }
+
+    #[test]
+    fn vectors_agree_with_angles() {
+        for pixel in HEALPIX.iter_pixels::<Ring>() {
+            let [x, y, z]: [f64; 3] = HEALPIX.pixel_to_vec(pixel).unwrap();
+            let (theta, phi) = HEALPIX.pixel_to_angle(pixel).unwrap();
+            assert!((z - math::cos(theta)).abs() < 1e-14);
+            assert!((x - math::sin(theta) * math::cos(phi)).abs() < 1e-14);
+            assert!((y - math::sin(theta) * math::sin(phi)).abs() < 1e-14);
+
+            assert_eq!(HEALPIX.vec_to_pixel::<Ring, _>(&[x, y, z]), pixel);
+            assert_eq!(
+                HEALPIX.vec_to_pixel::<Ring, _>(&[3.0 * x, 3.0 * y, 3.0 * z]),
+                pixel
+            );
+            let nested = HEALPIX.ring2nest(pixel).unwrap();
+            assert_eq!(HEALPIX.vec_to_pixel::<Nested, _>(&[x, y, z]), nested);
+        }
+        assert!(
+            HEALPIX
+                .pixel_to_vec::<Ring, [f64; 3]>(Pixel::from_u64(12 * 32 * 32))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn vectors_keep_precision_at_the_poles() {
+        let deepest = ConstHealpix::<{ 1 << 29 }>::new();
+        for pixel in [0, 1, 2, 3, (12 << 58) - 1].map(Pixel::<Ring>::from_u64) {
+            let v: [f64; 3] = deepest.pixel_to_vec(pixel).unwrap();
+            assert_eq!(deepest.vec_to_pixel::<Ring, _>(&v), pixel);
+        }
+        // A point 1e-12 rad from the pole, well inside the first ring of pixels.
+        let v = [1e-12, 1e-12, 1.0];
+        assert_eq!(deepest.vec_to_pixel::<Ring, _>(&v).as_u64(), 0);
+    }
+
+    #[test]
+    fn pixel_counts_reach_the_deepest_resolution() {
+        let deepest = ConstHealpix::<{ 1 << 29 }>::new();
+        assert_eq!(deepest.pixels_per_face(), 1 << 58);
+        assert_eq!(deepest.total_pixels(), 12 << 58);
+
+        let dynamic = DynamicHealpix::new(DynamicHealpix::MAX_FACE_RESOLUTION).unwrap();
+        assert_eq!(dynamic.total_pixels(), deepest.total_pixels());
+        assert!(DynamicHealpix::new(1 << 30).is_err());
+
+        let last = Pixel::<Nested>::from_u64(deepest.total_pixels() - 1);
+        let ring = deepest.nest2ring(last).unwrap();
+        assert_eq!(deepest.ring2nest(ring).unwrap(), last);
+        assert!(deepest.nest2ring(Pixel::from_u64(12 << 58)).is_err());
+    }
+
+    fn assert_neighbours_are_symmetric<N: NumberingScheme + core::fmt::Debug + Copy>() {
+        let mut missing = 0;
+        for pixel in HEALPIX.iter_pixels::<N>() {
+            for neighbour in HEALPIX.neighbours(pixel).unwrap() {
+                let Some(neighbour) = neighbour else {
+                    missing += 1;
+                    continue;
+                };
+                assert_ne!(neighbour, pixel);
+                assert!(
+                    HEALPIX
+                        .neighbours(neighbour)
+                        .unwrap()
+                        .contains(&Some(pixel)),
+                    "{:?} is not a neighbour of {:?}",
+                    pixel,
+                    neighbour
+                );
+            }
+        }
+        // 8 face corners, each touched by 3 pixels that lack one neighbour
+        assert_eq!(missing, 24);
+    }
+
+    #[test]
+    fn nested_neighbours_should_be_symmetric() {
+        assert_neighbours_are_symmetric::<Nested>();
+    }
+
+    #[test]
+    fn ring_neighbours_should_be_symmetric() {
+        assert_neighbours_are_symmetric::<Ring>();
+    }
+
+    #[test]
+    fn neighbours_should_follow_compass_order() {
+        // Pixel 0 in nested ordering is the southern corner of face 0.
+        let neighbours = HEALPIX.neighbours(Pixel::<Nested>::from_u64(0)).unwrap();
+        let face = |p: Option<Pixel<Nested>>| p.map(|p| p.as_u64() / HEALPIX.pixels_per_face());
+
+        assert_eq!(face(neighbours[0]), Some(8)); // S
+        assert_eq!(face(neighbours[1]), Some(4)); // SW
+        assert_eq!(face(neighbours[2]), Some(4)); // W
+        assert_eq!(face(neighbours[4]), Some(0)); // N
+        assert_eq!(face(neighbours[6]), Some(5)); // E
+        assert_eq!(neighbours[3], Some(Pixel::from_u64(2))); // NW
+        assert_eq!(neighbours[5], Some(Pixel::from_u64(1))); // NE
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn query_disc_should_agree_between_schemes() {
+        use crate::QueryMode;
+        use alloc::vec::Vec;
+        use latlong::{Declination, RaDec, RightAscension};
+
+        let centre = RaDec {
+            ra: RightAscension::from_degrees(350.0),
+            dec: Declination::from_degrees(-20.0),
+        };
+        let radius = 10f64.to_radians();
+
+        let nested = HEALPIX.query_disc::<Nested, f64>(&centre, radius, QueryMode::Exact);
+        let ring = HEALPIX.query_disc::<Ring, f64>(&centre, radius, QueryMode::Exact);
+        assert_eq!(nested.len(), ring.len());
+        assert!(!nested.is_empty());
+
+        let ranges = HEALPIX.query_disc_ranges(&centre, radius, QueryMode::Exact);
+        let from_ranges: Vec<_> = ranges.into_iter().flatten().map(Pixel::from_u64).collect();
+        assert_eq!(from_ranges, nested);
+
+        let inclusive = HEALPIX.query_disc::<Nested, f64>(&centre, radius, QueryMode::Inclusive);
+        assert!(nested.iter().all(|p| inclusive.contains(p)));
+        assert!(inclusive.len() > nested.len());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn query_disc_angle_should_select_the_same_pixels_in_both_schemes() {
+        use crate::QueryMode;
+        use alloc::vec::Vec;
+
+        let healpix = DynamicHealpix::new(4).unwrap();
+        // A fixed linear congruential generator, so that the discs are spread over the
+        // sphere but the test stays reproducible.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        for _ in 0..300 {
+            let theta = math::acos(1.0 - 2.0 * next());
+            let phi = core::f64::consts::TAU * next();
+            let radius = 0.5 * next();
+            for mode in [QueryMode::Exact, QueryMode::Inclusive] {
+                let mut nested: Vec<u64> = healpix
+                    .query_disc_angle::<Nested>(theta, phi, radius, mode)
+                    .into_iter()
+                    .map(|p| healpix.nest2ring(p).unwrap().as_u64())
+                    .collect();
+                nested.sort_unstable();
+                let ring: Vec<u64> = healpix
+                    .query_disc_angle::<Ring>(theta, phi, radius, mode)
+                    .into_iter()
+                    .map(|p| p.as_u64())
+                    .collect();
+                assert_eq!(nested, ring, "{mode:?} disc {:?}", (theta, phi, radius));
+            }
+        }
+    }
+
+    #[test]
+    fn nest2ring_should_preserve_pixel_centres() {
+        for pixel in HEALPIX.iter_pixels::<Nested>() {
+            let ring = HEALPIX.nest2ring(pixel).unwrap();
+            // Angles of Ring pixels follow the Ring formulas of HEALPix C++, which can round
+            // the azimuth differently, so compare the vectors both derive from the face.
+            assert_eq!(
+                HEALPIX.pixel_to_vec::<_, [f64; 3]>(pixel).unwrap(),
+                HEALPIX.pixel_to_vec::<_, [f64; 3]>(ring).unwrap()
+            );
+            assert_eq!(HEALPIX.ring2nest(ring).unwrap(), pixel);
+        }
+    }
+
+    /// The resolutions the consistency tests below cover exhaustively.
+    const CONSISTENCY_NSIDES: [u32; 7] = [1, 2, 4, 8, 16, 32, 64];
+
+    #[test]
+    fn nested_and_ring_should_agree_on_a_fine_grid() {
+        for nside in CONSISTENCY_NSIDES {
+            let healpix = DynamicHealpix::new(nside).unwrap();
+            // 4 points per pixel row in z and 8 per pixel column in phi, plus the poles and
+            // the face corners that lie on the grid.
+            let (nz, nphi) = (16 * nside, 32 * nside);
+            for i in 0..=nz {
+                let theta = math::acos(1.0 - 2.0 * i as f64 / nz as f64);
+                for j in 0..nphi {
+                    let phi = core::f64::consts::TAU * j as f64 / nphi as f64;
+                    let nested: Pixel<Nested> = healpix.angle_to_pixel(theta, phi);
+                    let ring: Pixel<Ring> = healpix.angle_to_pixel(theta, phi);
+                    assert_eq!(
+                        healpix.nest2ring(nested).unwrap(),
+                        ring,
+                        "nside {nside}, theta {theta}, phi {phi}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pixels_should_contain_points_near_their_corners() {
+        for nside in CONSISTENCY_NSIDES {
+            let healpix = DynamicHealpix::new(nside).unwrap();
+            for pixel in healpix.iter_pixels::<Nested>() {
+                let coordinates = healpix.pixel_to_face_coordinates(pixel).unwrap();
+                let ring = healpix.nest2ring(pixel).unwrap();
+                for (dx, dy) in [
+                    (0.5, 0.5),
+                    (0.02, 0.02),
+                    (0.98, 0.02),
+                    (0.02, 0.98),
+                    (0.98, 0.98),
+                ] {
+                    let point = FractionalFaceCoordinates {
+                        face: coordinates.face,
+                        x: (coordinates.x as f64 + dx) / nside as f64,
+                        y: (coordinates.y as f64 + dy) / nside as f64,
+                    };
+                    let (theta, phi) = point.to_angle().unwrap();
+                    assert_eq!(healpix.angle_to_pixel::<Nested>(theta, phi), pixel);
+                    assert_eq!(healpix.angle_to_pixel::<Ring>(theta, phi), ring);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nest2ring_should_reject_invalid_pixels() {
+        let pixel = Pixel::<Nested>::from_u64(HEALPIX.total_pixels());
+        assert!(HEALPIX.nest2ring(pixel).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn reorder_map_should_reject_maps_of_the_wrong_size() {
+        let mut map = [0.0; 12];
+        assert!(HEALPIX.reorder_nest2ring(&mut map).is_err());
+    }
+
+    #[test]
+    fn neighbours_should_reject_invalid_pixels() {
+        let pixel = Pixel::<Ring>::from_u64(HEALPIX.total_pixels());
+        assert!(HEALPIX.neighbours(pixel).is_err());
+    }
+
+    #[test]
+    fn try_angle_to_pixel_should_reject_invalid_angles() {
+        for (theta, phi) in [
+            (f64::NAN, 1.0),
+            (1.0, f64::NAN),
+            (1.0, f64::INFINITY),
+            (-1e-12, 1.0),
+            (3.2, 1.0),
+        ] {
+            assert!(matches!(
+                HEALPIX.try_angle_to_pixel::<Ring>(theta, phi),
+                Err(Error::InvalidAngle)
+            ));
+        }
+        for (theta, phi) in [(0.0, 0.0), (core::f64::consts::PI, -7.0), (1.0, 100.0)] {
+            let pixel: Pixel<Nested> = HEALPIX.try_angle_to_pixel(theta, phi).unwrap();
+            assert_eq!(pixel, HEALPIX.angle_to_pixel(theta, phi));
+        }
+
+        let ring_only = DynamicHealpix::new_for::<Ring>(6).unwrap();
+        assert!(ring_only.try_angle_to_pixel::<Ring>(1.0, 2.0).is_ok());
+        assert!(matches!(
+            ring_only.try_angle_to_pixel::<Nested>(1.0, 2.0),
+            Err(Error::InvalidFaceResolution)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn try_ra_dec_to_pixel_should_reject_invalid_coordinates() {
+        let ra_dec = |ra, dec| RaDec {
+            ra: RightAscension::from_degrees(ra),
+            dec: Declination::from_degrees(dec),
+        };
+        for (ra, dec) in [(f64::NAN, 0.0), (f64::INFINITY, 0.0), (10.0, f64::NAN)] {
+            assert!(matches!(
+                HEALPIX.try_ra_dec_to_pixel::<Nested, f64>(&ra_dec(ra, dec)),
+                Err(Error::InvalidRaDec)
+            ));
+        }
+        assert!(matches!(
+            HEALPIX.try_ra_dec_to_pixel::<Ring, f64>(&ra_dec(10.0, 90.5)),
+            Err(Error::InvalidRaDec)
+        ));
+        for (ra, dec) in [(0.0, 90.0), (-30.0, -90.0), (370.0, 12.5)] {
+            let pixel: Pixel<Ring> = HEALPIX.try_ra_dec_to_pixel(&ra_dec(ra, dec)).unwrap();
+            assert_eq!(pixel, HEALPIX.ra_dec_to_pixel(&ra_dec(ra, dec)));
+        }
+        let pole = RaDec {
+            ra: RightAscension::from_degrees(0.0f32),
+            dec: Declination::from_degrees(90.0f32),
+        };
+        assert!(HEALPIX.try_ra_dec_to_pixel::<Nested, f32>(&pole).is_ok());
+    }
+
+    #[test]
+    fn try_vec_to_pixel_should_reject_vectors_without_direction() {
+        for vector in [[0.0; 3], [f64::NAN, 0.0, 1.0], [0.0, f64::INFINITY, 0.0]] {
+            assert!(matches!(
+                HEALPIX.try_vec_to_pixel::<Nested, [f64; 3]>(&vector),
+                Err(Error::InvalidAngle)
+            ));
+        }
+        let vector = [0.0, 0.0, -3.0];
+        let pixel: Pixel<Ring> = HEALPIX.try_vec_to_pixel(&vector).unwrap();
+        assert_eq!(pixel, HEALPIX.vec_to_pixel(&vector));
+    }
+
+    #[test]
+    fn both_schemes_should_validate_pixels_alike() {
+        let total = HEALPIX.total_pixels();
+        assert!(matches!(
+            HEALPIX.pixel_to_angle(Pixel::<Ring>::from_u64(total)),
+            Err(Error::InvalidPixel)
+        ));
+        assert!(matches!(
+            HEALPIX.pixel_to_angle(Pixel::<Nested>::from_u64(total)),
+            Err(Error::InvalidPixel)
+        ));
+        assert!(matches!(
+            Ring::pixel_to_angle::<Ring>(0, Pixel::from_u64(0)),
+            Err(Error::InvalidFaceResolution)
+        ));
+        assert!(matches!(
+            Nested::pixel_to_angle::<Nested>(0, Pixel::from_u64(0)),
+            Err(Error::InvalidFaceResolution)
+        ));
+        for scheme_error in [
+            Ring::pixel_to_angle::<Ring>(1 << 30, Pixel::from_u64(0)),
+            Nested::pixel_to_angle::<Nested>(1 << 30, Pixel::from_u64(0)),
+        ] {
+            assert!(matches!(scheme_error, Err(Error::ResolutionTooLarge)));
+        }
+    }
+
+    #[test]
+    fn resolutions_beyond_the_deepest_should_be_too_large() {
+        assert!(matches!(
+            DynamicHealpix::new(1 << 30),
+            Err(Error::ResolutionTooLarge)
+        ));
+        assert!(matches!(
+            DynamicHealpix::new_for::<Ring>(u32::MAX),
+            Err(Error::ResolutionTooLarge)
+        ));
+        assert!(matches!(
+            DynamicHealpix::new(3),
+            Err(Error::InvalidFaceResolution)
+        ));
+        #[cfg(feature = "alloc")]
+        assert!(matches!(
+            HEALPIX.ud_grade::<Nested, f64>(&[0.0; 12 * 32 * 32], 1 << 30, crate::GradeMode::Mean),
+            Err(Error::ResolutionTooLarge)
+        ));
+    }
+
+    #[test]
+    fn pixel_corners_should_follow_compass_order() {
+        use core::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+        let healpix = ConstHealpix::<1>::new();
+        let corners = healpix
+            .pixel_corners_angle(Pixel::<Nested>::from_u64(0))
+            .unwrap();
+        let expected = [
+            (0.0, 0.0),
+            (math::acos(2.0f64 / 3.0), 0.0),
+            (FRAC_PI_2, FRAC_PI_4),
+            (math::acos(2.0f64 / 3.0), FRAC_PI_2),
+        ];
+        for (corner, expected) in corners.iter().zip(expected) {
+            assert!((corner.0 - expected.0).abs() < 1e-12, "{:?}", corners);
+            assert!((corner.1 - expected.1).abs() < 1e-12, "{:?}", corners);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn pixel_corners_should_agree_between_schemes() {
+        for pixel in HEALPIX.iter_pixels::<Nested>() {
+            let ring = HEALPIX.nest2ring(pixel).unwrap();
+            let corners = HEALPIX.pixel_corners::<_, f64>(pixel).unwrap();
+            assert_eq!(corners, HEALPIX.pixel_corners(ring).unwrap());
+        }
+        let pixel = Pixel::<Ring>::from_u64(HEALPIX.total_pixels());
+        assert!(HEALPIX.pixel_corners_angle(pixel).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn pixel_boundary_should_enclose_the_pixel() {
+        use crate::vec3::Vec3;
+
+        for pixel in HEALPIX.iter_pixels::<Ring>().step_by(7) {
+            let corners = HEALPIX.pixel_corners_angle(pixel).unwrap();
+            let boundary = HEALPIX.pixel_boundary_angle(pixel, 4).unwrap();
+            assert_eq!(boundary.len(), 16);
+            for (i, corner) in corners.iter().enumerate() {
+                assert_eq!(boundary[4 * i], *corner);
+            }
+
+            // Points just inside the boundary belong to the pixel.
+            let (theta, phi) = HEALPIX.pixel_to_angle(pixel).unwrap();
+            let centre = Vec3::from_angle(theta, phi);
+            for (theta, phi) in boundary {
+                let point = Vec3::from_angle(theta, phi);
+                let (theta, phi) = (point * 0.999 + centre * 0.001).normalized().to_angle();
+                assert_eq!(HEALPIX.angle_to_pixel::<Ring>(theta, phi), pixel);
+            }
+        }
+    }
 }
@@ -0,0 +1,400 @@
+use crate::dynamic_healpix::DynamicHealpix;
+use crate::fits::{self, BinTable, Column, Value};
+use crate::healpix::Healpix;
+use crate::healpix_map::{HealpixMap, UNSEEN};
+use crate::nested::Nested;
+use crate::numbering_scheme::{NumberingScheme, check_face_resolution};
+use crate::ring::Ring;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Number of values per table row in written files, as healpy does for large maps.
+const ROW_LENGTH: usize = 1024;
+
+/// A set of maps of one resolution, as stored in the binary table of a HEALPix FITS file.
+///
+/// Files hold one map per column, such as the `I_STOKES`, `Q_STOKES` and `U_STOKES` columns
+/// of a polarization map. The file's `ORDERING` keyword is matched to the scheme `N`: maps
+/// stored in the other ordering are reordered when read, so reading a `RING` file as
+/// `HealpixFits<Nested>` yields [`Nested`] maps.
+///
+/// # Example
+/// ```rust
+/// use realpix::{DynamicHealpix, HealpixFits, HealpixMap, Nested, Ring};
+///
+/// let healpix = DynamicHealpix::new(16).unwrap();
+/// let map = HealpixMap::<f64, Ring>::filled(&healpix, 2.7).unwrap();
+/// let file = HealpixFits {
+///     maps: vec![("TEMPERATURE".to_string(), map)],
+///     coordinate_system: Some("G".to_string()),
+/// };
+/// let bytes = file.to_bytes().unwrap();
+///
+/// let read = HealpixFits::<Nested>::from_bytes(&bytes).unwrap();
+/// assert_eq!(read.map("TEMPERATURE").unwrap().values()[0], 2.7);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealpixFits<N: NumberingScheme> {
+    /// The maps with their column names, in column order.
+    pub maps: Vec<(String, HealpixMap<f64, N>)>,
+
+    /// The `COORDSYS` keyword: `G` for galactic, `E` for ecliptic or `C` for celestial.
+    pub coordinate_system: Option<String>,
+}
+
+impl<N: NumberingScheme> HealpixFits<N> {
+    /// Returns the map in the column named `name`, ignoring case.
+    pub fn map(&self, name: &str) -> Option<&HealpixMap<f64, N>> {
+        self.maps
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, map)| map)
+    }
+
+    /// Parses a HEALPix FITS file, reading every column of its first binary table.
+    ///
+    /// Both the `IMPLICIT` indexing scheme, with one value per pixel, and the `EXPLICIT` one,
+    /// where a `PIXEL` column lists the pixels present, are understood. Pixels missing from an
+    /// explicit file and values equal to the `BAD_DATA` keyword are [`UNSEEN`].
+    ///
+    /// The header of an `EXPLICIT` file alone sets the size of the map, so its `LASTPIX` and
+    /// `OBS_NPIX` keywords, when present, must agree with `NSIDE` and the number of rows.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidFits`] if the file has no binary table, lacks the
+    /// `ORDERING` and `NSIDE` keywords or has an unknown `ORDERING`, if an `EXPLICIT` file has
+    /// no `PIXEL` column, a value column of another length, inconsistent `LASTPIX` or
+    /// `OBS_NPIX` keywords or a map too large to allocate,
+    /// [`crate::Error::InvalidFaceResolution`] if `NSIDE` is not a power of two for a
+    /// [`Nested`] file or map, [`crate::Error::InvalidMapSize`] if a column does not hold one
+    /// value per pixel and [`crate::Error::InvalidPixel`] for an explicit pixel out of bounds.
+    pub fn from_bytes(bytes: &[u8]) -> crate::Result<Self> {
+        let table = BinTable::parse_first(bytes)?;
+        let header = &table.header;
+        if header
+            .get_text("PIXTYPE")
+            .is_some_and(|pixtype| pixtype != "HEALPIX")
+        {
+            return Err(crate::Error::InvalidFits);
+        }
+        let ordering = header
+            .get_text("ORDERING")
+            .ok_or(crate::Error::InvalidFits)?;
+        let nside = header
+            .get_integer("NSIDE")
+            .and_then(|nside| u32::try_from(nside).ok())
+            .ok_or(crate::Error::InvalidFits)?;
+        let healpix = match ordering {
+            "RING" => DynamicHealpix::new_for::<Ring>(nside)?,
+            "NESTED" | "NEST" => DynamicHealpix::new(nside)?,
+            _ => return Err(crate::Error::InvalidFits),
+        };
+        check_face_resolution::<N>(nside)?;
+        let total_pixels = healpix.total_pixels() as usize;
+        let bad_data = header.get_float("BAD_DATA").unwrap_or(UNSEEN);
+
+        let explicit = header
+            .get_text("INDXSCHM")
+            .is_some_and(|scheme| scheme == "EXPLICIT");
+        let pixels = if explicit {
+            let index = table
+                .column_index("PIXEL")
+                .ok_or(crate::Error::InvalidFits)?;
+            let pixels = table.read_integer_column(index)?;
+            let last_pixel = header.get_integer("LASTPIX");
+            if last_pixel.is_some_and(|last| last != total_pixels as i64 - 1)
+                || header
+                    .get_integer("OBS_NPIX")
+                    .is_some_and(|observed| observed != pixels.len() as i64)
+            {
+                return Err(crate::Error::InvalidFits);
+            }
+            Some((index, pixels))
+        } else {
+            None
+        };
+
+        let mut maps = Vec::new();
+        for (index, column) in table.columns.iter().enumerate() {
+            let mut values = table.read_column(index)?;
+            if let Some((pixel_index, pixels)) = &pixels {
+                if index == *pixel_index {
+                    continue;
+                }
+                if values.len() != pixels.len() {
+                    return Err(crate::Error::InvalidFits);
+                }
+                let mut full = Vec::new();
+                full.try_reserve_exact(total_pixels)
+                    .map_err(|_| crate::Error::InvalidFits)?;
+                full.resize(total_pixels, UNSEEN);
+                for (pixel, value) in pixels.iter().zip(values) {
+                    let slot = usize::try_from(*pixel)
+                        .ok()
+                        .and_then(|pixel| full.get_mut(pixel))
+                        .ok_or(crate::Error::InvalidPixel)?;
+                    *slot = value;
+                }
+                values = full;
+            }
+            for value in &mut values {
+                if (*value - bad_data).abs() <= 1e-5 * bad_data.abs() {
+                    *value = UNSEEN;
+                }
+            }
+
+            let map = if ordering == "RING" {
+                HealpixMap::<f64, Ring>::from_vec(&healpix, values)?.reorder::<N>()?
+            } else {
+                HealpixMap::<f64, Nested>::from_vec(&healpix, values)?.reorder::<N>()?
+            };
+            maps.push((column.name.clone(), map));
+        }
+
+        Ok(Self {
+            maps,
+            coordinate_system: header.get_text("COORDSYS").map(ToString::to_string),
+        })
+    }
+
+    /// Serializes the maps as a HEALPix FITS file with one double-precision column per map,
+    /// in the ordering of `N` and the `IMPLICIT` indexing scheme.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidMapSize`] if there are no maps or their resolutions
+    /// differ.
+    pub fn to_bytes(&self) -> crate::Result<Vec<u8>> {
+        let nside = self
+            .maps
+            .first()
+            .ok_or(crate::Error::InvalidMapSize)?
+            .1
+            .face_resolution();
+        if self.maps.iter().any(|(_, m)| m.face_resolution() != nside) {
+            return Err(crate::Error::InvalidMapSize);
+        }
+        let total_pixels = 12 * nside as usize * nside as usize;
+        let row_length = if total_pixels.is_multiple_of(ROW_LENGTH) {
+            ROW_LENGTH
+        } else {
+            total_pixels
+        };
+
+        let columns = self
+            .maps
+            .iter()
+            .map(|(name, _)| Column::new(name, row_length, 'D'))
+            .collect();
+        let mut table = BinTable::new(columns);
+        let mut row = Vec::with_capacity(table.row_width());
+        for start in (0..total_pixels).step_by(row_length) {
+            row.clear();
+            for (_, map) in &self.maps {
+                for value in &map.values()[start..start + row_length] {
+                    row.extend(value.to_be_bytes());
+                }
+            }
+            table.push_row(&row);
+        }
+
+        let text = |s: &str| Value::Text(s.to_string());
+        let header = &mut table.header;
+        header.set("EXTNAME", text("xtension"));
+        header.set("PIXTYPE", text("HEALPIX"));
+        header.set("ORDERING", text(N::ORDERING));
+        if let Some(coordinate_system) = &self.coordinate_system {
+            header.set("COORDSYS", text(coordinate_system));
+        }
+        header.set("NSIDE", Value::Integer(nside as i64));
+        header.set("FIRSTPIX", Value::Integer(0));
+        header.set("LASTPIX", Value::Integer(total_pixels as i64 - 1));
+        header.set("INDXSCHM", text("IMPLICIT"));
+        header.set("OBJECT", text("FULLSKY"));
+        header.set("BAD_DATA", Value::Float(UNSEEN));
+
+        let mut bytes = Vec::new();
+        fits::write_empty_primary(&mut bytes);
+        table.write(&mut bytes);
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Pixel;
+    use crate::fits::replace_card;
+    use alloc::vec;
+
+    // Written by `tests/fixtures/healpix/generate.py` in the layout of `healpy.write_map`.
+    const IQU_RING: &[u8] = include_bytes!("../tests/fixtures/healpix/iqu_ring_nside2.fits");
+    const PARTIAL_NESTED: &[u8] =
+        include_bytes!("../tests/fixtures/healpix/partial_nested_nside4.fits");
+
+    #[test]
+    fn reads_polarization_maps() {
+        let file = HealpixFits::<Ring>::from_bytes(IQU_RING).unwrap();
+        assert_eq!(file.coordinate_system.as_deref(), Some("G"));
+        let names: Vec<_> = file.maps.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["I_STOKES", "Q_STOKES", "U_STOKES"]);
+
+        // The fixture holds I = p, Q = p / 2 and U = -p for ring pixel p.
+        for (pixel, i) in file.map("i_stokes").unwrap().iter() {
+            let p = pixel.as_u64() as f64;
+            assert_eq!(*i, p);
+            assert_eq!(file.map("Q_STOKES").unwrap()[pixel], p / 2.0);
+            assert_eq!(file.map("U_STOKES").unwrap()[pixel], -p);
+        }
+    }
+
+    #[test]
+    fn reading_converts_to_the_requested_ordering() {
+        let ring = HealpixFits::<Ring>::from_bytes(IQU_RING).unwrap();
+        let nested = HealpixFits::<Nested>::from_bytes(IQU_RING).unwrap();
+        for ((_, ring), (_, nested)) in ring.maps.into_iter().zip(nested.maps) {
+            assert_eq!(ring.into_nested().unwrap(), nested);
+        }
+    }
+
+    #[test]
+    fn reads_explicit_partial_maps() {
+        let file = HealpixFits::<Nested>::from_bytes(PARTIAL_NESTED).unwrap();
+        assert_eq!(file.maps.len(), 1);
+        let signal = file.map("SIGNAL").unwrap();
+        assert_eq!(signal.face_resolution(), 4);
+
+        let seen: Vec<_> = signal.iter_seen().collect();
+        assert_eq!(
+            seen,
+            [
+                (Pixel::from_u64(0), 1.5),
+                (Pixel::from_u64(5), -2.0),
+                (Pixel::from_u64(100), 3.25),
+            ]
+        );
+        assert!(signal.is_unseen(Pixel::from_u64(191)));
+    }
+
+    #[test]
+    fn rejects_explicit_maps_the_data_does_not_describe() {
+        let explicit = |cards: &[(&str, &str)]| {
+            let mut bytes = PARTIAL_NESTED.to_vec();
+            for (keyword, card) in cards {
+                replace_card(&mut bytes, keyword, card);
+            }
+            HealpixFits::<Nested>::from_bytes(&bytes)
+        };
+        assert!(explicit(&[]).is_ok());
+        // A lying NSIDE must not be allocated blindly.
+        assert!(matches!(
+            explicit(&[("NSIDE", "NSIDE   =            536870912")]),
+            Err(crate::Error::InvalidFits)
+        ));
+        assert!(matches!(
+            explicit(&[
+                ("NSIDE", "NSIDE   =                16384"),
+                ("OBJECT", "LASTPIX =                  191"),
+            ]),
+            Err(crate::Error::InvalidFits)
+        ));
+        assert!(explicit(&[("OBJECT", "LASTPIX =                  191")]).is_ok());
+        assert!(matches!(
+            explicit(&[("OBJECT", "OBS_NPIX=                    5")]),
+            Err(crate::Error::InvalidFits)
+        ));
+        assert!(matches!(
+            explicit(&[("TTYPE1", "TTYPE1  = 'INDEX   '")]),
+            Err(crate::Error::InvalidFits)
+        ));
+        assert!(matches!(
+            explicit(&[
+                ("ORDERING", "ORDERING= 'SPIRAL  '"),
+                ("NSIDE", "NSIDE   = 3")
+            ]),
+            Err(crate::Error::InvalidFits)
+        ));
+    }
+
+    #[test]
+    fn rejects_explicit_columns_of_different_lengths() {
+        let mut table = BinTable::new(vec![
+            Column::new("PIXEL", 1, 'K'),
+            Column::new("SIGNAL", 2, 'D'),
+        ]);
+        let mut row = Vec::new();
+        row.extend_from_slice(&3i64.to_be_bytes());
+        row.extend_from_slice(&1.0f64.to_be_bytes());
+        row.extend_from_slice(&2.0f64.to_be_bytes());
+        table.push_row(&row);
+        table
+            .header
+            .set("PIXTYPE", Value::Text("HEALPIX".to_string()));
+        table
+            .header
+            .set("ORDERING", Value::Text("NESTED".to_string()));
+        table.header.set("NSIDE", Value::Integer(1));
+        table
+            .header
+            .set("INDXSCHM", Value::Text("EXPLICIT".to_string()));
+        let mut bytes = Vec::new();
+        fits::write_empty_primary(&mut bytes);
+        table.write(&mut bytes);
+        assert!(matches!(
+            HealpixFits::<Nested>::from_bytes(&bytes),
+            Err(crate::Error::InvalidFits)
+        ));
+    }
+
+    #[test]
+    fn written_maps_round_trip() {
+        for file in [IQU_RING, PARTIAL_NESTED] {
+            let nested = HealpixFits::<Nested>::from_bytes(file).unwrap();
+            let bytes = nested.to_bytes().unwrap();
+            assert_eq!(HealpixFits::<Nested>::from_bytes(&bytes).unwrap(), nested);
+
+            let ring = HealpixFits::<Ring>::from_bytes(file).unwrap();
+            assert_eq!(HealpixFits::<Ring>::from_bytes(&bytes).unwrap(), ring);
+        }
+
+        // Large maps are split into rows of 1024 values.
+        let healpix = DynamicHealpix::new(32).unwrap();
+        let mut map = HealpixMap::<f64, Ring>::unseen(&healpix).unwrap();
+        map[Pixel::from_u64(12287)] = 1.0;
+        let file = HealpixFits {
+            maps: vec![("T".to_string(), map)],
+            coordinate_system: None,
+        };
+        let bytes = file.to_bytes().unwrap();
+        assert_eq!(BinTable::parse_first(&bytes).unwrap().rows, 12);
+        assert_eq!(HealpixFits::from_bytes(&bytes).unwrap(), file);
+    }
+
+    #[test]
+    fn ring_maps_of_any_resolution_round_trip() {
+        let healpix = DynamicHealpix::new_for::<Ring>(3).unwrap();
+        let mut map = HealpixMap::<f64, Ring>::filled(&healpix, 0.0).unwrap();
+        for (pixel, value) in map.iter_mut() {
+            *value = pixel.as_u64() as f64;
+        }
+        let file = HealpixFits {
+            maps: vec![("T".to_string(), map)],
+            coordinate_system: None,
+        };
+        let bytes = file.to_bytes().unwrap();
+        assert_eq!(HealpixFits::<Ring>::from_bytes(&bytes).unwrap(), file);
+        assert!(HealpixFits::<Nested>::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(HealpixFits::<Ring>::from_bytes(b"").is_err());
+        let moc: crate::Moc = "3/1".parse().unwrap();
+        assert!(HealpixFits::<Ring>::from_bytes(&moc.to_fits()).is_err());
+
+        let empty = HealpixFits::<Ring> {
+            maps: Vec::new(),
+            coordinate_system: None,
+        };
+        assert!(empty.to_bytes().is_err());
+    }
+}
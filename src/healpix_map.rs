@@ -0,0 +1,396 @@
+use crate::grade_mode::GradeMode;
+use crate::healpix::Healpix;
+use crate::map_value::MapValue;
+use crate::nested::Nested;
+use crate::numbering_scheme::{NumberingScheme, check_face_resolution};
+use crate::pixel::Pixel;
+use crate::reorder::reorder;
+use crate::ring::Ring;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ops::{Index, IndexMut};
+#[cfg(feature = "std")]
+use latlong::{Float, RaDec};
+
+/// The HEALPix sentinel for pixels without data, as written by HEALPix, healpy and the
+/// HEALPix FITS files.
+pub const UNSEEN: f64 = -1.6375e30;
+
+/// Returns `true` if `value` is [`UNSEEN`], within the relative tolerance of `1e-5` used by
+/// healpy, so that values that went through `f32` still match.
+pub(crate) fn is_unseen(value: f64) -> bool {
+    (value - UNSEEN).abs() <= 1e-5 * UNSEEN.abs()
+}
+
+/// A map holding one value per pixel of a HEALPix grid, stored in the ordering of scheme `N`.
+///
+/// The ordering is part of the type, so a [`Ring`] map can only be indexed by
+/// `Pixel<Ring>` and must be reordered explicitly before being combined with a [`Nested`] one.
+/// The map is itself a [`Healpix`] grid of its resolution.
+///
+/// # Example
+/// ```rust
+/// use realpix::{DynamicHealpix, HealpixMap, Nested, Pixel, Ring};
+///
+/// let healpix = DynamicHealpix::new(4).unwrap();
+/// let mut map = HealpixMap::<f64, Nested>::unseen(&healpix).unwrap();
+/// map[Pixel::from_u64(0)] = 1.0;
+///
+/// let map = map.reorder::<Ring>().unwrap();
+/// assert_eq!(map.iter_seen().count(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealpixMap<T, N: NumberingScheme> {
+    face_resolution: u32,
+    values: Vec<T>,
+    scheme: PhantomData<N>,
+}
+
+impl<T, N: NumberingScheme> HealpixMap<T, N> {
+    /// Creates a map of the resolution of `healpix` with every pixel set to `value`.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidFaceResolution`] if `N` does not support the resolution,
+    /// as for a [`Nested`] map on a grid made with [`crate::DynamicHealpix::new_for`] for
+    /// [`Ring`].
+    pub fn filled(healpix: &impl Healpix, value: T) -> crate::Result<Self>
+    where
+        T: Clone,
+    {
+        check_face_resolution::<N>(healpix.face_resolution())?;
+        Ok(Self {
+            face_resolution: healpix.face_resolution(),
+            values: vec![value; healpix.total_pixels() as usize],
+            scheme: PhantomData,
+        })
+    }
+
+    /// Wraps values already in the ordering of `N`, one per pixel of `healpix`.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidMapSize`] if `values` does not hold exactly one value per
+    /// pixel and [`crate::Error::InvalidFaceResolution`] if `N` does not support the resolution.
+    pub fn from_vec(healpix: &impl Healpix, values: Vec<T>) -> crate::Result<Self> {
+        if values.len() != healpix.total_pixels() as usize {
+            return Err(crate::Error::InvalidMapSize);
+        }
+        check_face_resolution::<N>(healpix.face_resolution())?;
+        Ok(Self {
+            face_resolution: healpix.face_resolution(),
+            values,
+            scheme: PhantomData,
+        })
+    }
+
+    /// Returns the values, indexed by pixel in the ordering of `N`.
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Returns the values mutably, indexed by pixel in the ordering of `N`.
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    /// Unwraps the values, indexed by pixel in the ordering of `N`.
+    pub fn into_vec(self) -> Vec<T> {
+        self.values
+    }
+
+    /// Returns the value of `pixel`, or `None` if it is out of bounds.
+    pub fn get(&self, pixel: Pixel<N>) -> Option<&T> {
+        self.values.get(pixel.as_u64() as usize)
+    }
+
+    /// Returns the value of `pixel` mutably, or `None` if it is out of bounds.
+    pub fn get_mut(&mut self, pixel: Pixel<N>) -> Option<&mut T> {
+        self.values.get_mut(pixel.as_u64() as usize)
+    }
+
+    /// Returns the pixel containing the point.
+    #[cfg(feature = "std")]
+    pub fn pixel_at<F: Float>(&self, ra_dec: &RaDec<F>) -> Pixel<N> {
+        let theta = core::f64::consts::FRAC_PI_2 - ra_dec.dec.radians().to_f64();
+        let phi = ra_dec.ra.radians().to_f64();
+        N::angle_to_pixel(self.face_resolution, theta, phi)
+    }
+
+    /// Returns the value of the pixel containing the point.
+    #[cfg(feature = "std")]
+    pub fn value_at<F: Float>(&self, ra_dec: &RaDec<F>) -> &T {
+        &self[self.pixel_at(ra_dec)]
+    }
+
+    /// Returns the value of the pixel containing the point mutably.
+    #[cfg(feature = "std")]
+    pub fn value_at_mut<F: Float>(&mut self, ra_dec: &RaDec<F>) -> &mut T {
+        let pixel = self.pixel_at(ra_dec);
+        &mut self[pixel]
+    }
+
+    /// Iterates over `(pixel, value)` pairs in pixel order.
+    pub fn iter(&self) -> impl Iterator<Item = (Pixel<N>, &T)> + '_ {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (Pixel::from_u64(i as u64), v))
+    }
+
+    /// Iterates over `(pixel, value)` pairs in pixel order, with mutable values.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Pixel<N>, &mut T)> + '_ {
+        self.values
+            .iter_mut()
+            .enumerate()
+            .map(|(i, v)| (Pixel::from_u64(i as u64), v))
+    }
+
+    /// Converts the map to the ordering of scheme `M`, permuting the values in place.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidFaceResolution`] if `M` does not support the resolution:
+    /// [`Nested`] needs a power of two.
+    pub fn reorder<M: NumberingScheme>(self) -> crate::Result<HealpixMap<T, M>> {
+        check_face_resolution::<M>(self.face_resolution)?;
+        Ok(self.reorder_unchecked())
+    }
+
+    /// Converts the map to the ordering of scheme `M`, which must support the resolution.
+    fn reorder_unchecked<M: NumberingScheme>(mut self) -> HealpixMap<T, M> {
+        reorder::<N, M, T>(self.face_resolution, &mut self.values);
+        HealpixMap {
+            face_resolution: self.face_resolution,
+            values: self.values,
+            scheme: PhantomData,
+        }
+    }
+}
+
+impl<T> HealpixMap<T, Ring> {
+    /// Converts a [`Ring`] map to [`Nested`] ordering.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidFaceResolution`] if the face resolution is not a power
+    /// of two.
+    pub fn into_nested(self) -> crate::Result<HealpixMap<T, Nested>> {
+        self.reorder()
+    }
+}
+
+impl<T> HealpixMap<T, Nested> {
+    /// Converts a [`Nested`] map to [`Ring`] ordering, which supports every resolution a
+    /// [`Nested`] map can have.
+    pub fn into_ring(self) -> HealpixMap<T, Ring> {
+        self.reorder_unchecked()
+    }
+}
+
+impl<T: MapValue, N: NumberingScheme> HealpixMap<T, N> {
+    /// Creates a map with every pixel set to [`UNSEEN`].
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidFaceResolution`] if `N` does not support the resolution.
+    pub fn unseen(healpix: &impl Healpix) -> crate::Result<Self> {
+        Self::filled(healpix, T::from_f64(UNSEEN))
+    }
+
+    /// Returns `true` if `pixel` holds [`UNSEEN`] or is out of bounds.
+    pub fn is_unseen(&self, pixel: Pixel<N>) -> bool {
+        self.get(pixel).is_none_or(|v| is_unseen(v.to_f64()))
+    }
+
+    /// Returns this map at the resolution of `healpix`. See [`Healpix::ud_grade`].
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidFaceResolution`] if either resolution is not a power of
+    /// two.
+    pub fn ud_grade(&self, healpix: &impl Healpix, mode: GradeMode) -> crate::Result<Self> {
+        let values =
+            Healpix::ud_grade::<N, T>(self, &self.values, healpix.face_resolution(), mode)?;
+        Self::from_vec(healpix, values)
+    }
+
+    /// Iterates over the `(pixel, value)` pairs whose value is not [`UNSEEN`].
+    pub fn iter_seen(&self) -> impl Iterator<Item = (Pixel<N>, T)> + '_ {
+        self.iter()
+            .map(|(pixel, v)| (pixel, *v))
+            .filter(|(_, v)| !is_unseen(v.to_f64()))
+    }
+
+    /// Bilinearly interpolates the map at `(theta, phi)`. See [`Healpix::interpolate_angle`].
+    pub fn interpolate_angle(&self, theta: f64, phi: f64) -> T {
+        Healpix::interpolate_angle::<N, T>(self, &self.values, theta, phi)
+            .expect("a map holds one value per pixel")
+    }
+
+    /// Bilinearly interpolates the map at a point. See [`Healpix::interpolate`].
+    #[cfg(feature = "std")]
+    pub fn interpolate<F: Float>(&self, ra_dec: &RaDec<F>) -> T {
+        Healpix::interpolate::<N, F, T>(self, &self.values, ra_dec)
+            .expect("a map holds one value per pixel")
+    }
+}
+
+impl<T, N: NumberingScheme> Healpix for HealpixMap<T, N> {
+    fn face_resolution(&self) -> u32 {
+        self.face_resolution
+    }
+
+    fn pixels_per_face(&self) -> u64 {
+        self.face_resolution as u64 * self.face_resolution as u64
+    }
+
+    fn total_pixels(&self) -> u64 {
+        12 * self.pixels_per_face()
+    }
+}
+
+impl<T, N: NumberingScheme> Index<Pixel<N>> for HealpixMap<T, N> {
+    type Output = T;
+
+    /// # Panics
+    /// Panics if the pixel is out of bounds.
+    fn index(&self, pixel: Pixel<N>) -> &T {
+        &self.values[pixel.as_u64() as usize]
+    }
+}
+
+impl<T, N: NumberingScheme> IndexMut<Pixel<N>> for HealpixMap<T, N> {
+    /// # Panics
+    /// Panics if the pixel is out of bounds.
+    fn index_mut(&mut self, pixel: Pixel<N>) -> &mut T {
+        &mut self.values[pixel.as_u64() as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DynamicHealpix;
+    use crate::math;
+    #[cfg(feature = "std")]
+    use latlong::{Declination, RightAscension};
+
+    #[cfg(feature = "std")]
+    fn ra_dec(ra: f64, dec: f64) -> RaDec<f64> {
+        RaDec {
+            ra: RightAscension::from_degrees(ra),
+            dec: Declination::from_degrees(dec),
+        }
+    }
+
+    /// Returns the `(theta, phi)` of a point given in RA/Dec degrees.
+    fn angle(ra: f64, dec: f64) -> (f64, f64) {
+        ((90.0 - dec).to_radians(), ra.to_radians())
+    }
+
+    #[test]
+    fn from_vec_rejects_maps_of_the_wrong_size() {
+        let healpix = DynamicHealpix::new(2).unwrap();
+        assert!(HealpixMap::<u8, Ring>::from_vec(&healpix, vec![0; 47]).is_err());
+        assert!(HealpixMap::<u8, Ring>::from_vec(&healpix, vec![0; 48]).is_ok());
+    }
+
+    #[test]
+    fn reorder_keeps_values_on_their_pixels() {
+        let healpix = DynamicHealpix::new(8).unwrap();
+        let values: Vec<u64> = (0..healpix.total_pixels()).collect();
+        let nested = HealpixMap::<u64, Nested>::from_vec(&healpix, values).unwrap();
+
+        let ring = nested.clone().into_ring();
+        for (pixel, value) in nested.iter() {
+            assert_eq!(ring[healpix.nest2ring(pixel).unwrap()], *value);
+        }
+        assert_eq!(ring.into_nested().unwrap(), nested);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn lookup_by_ra_dec_agrees_between_orderings() {
+        let healpix = DynamicHealpix::new(16).unwrap();
+        let mut ring = HealpixMap::<f64, Ring>::filled(&healpix, 0.0).unwrap();
+        for (pixel, value) in ring.iter_mut() {
+            *value = pixel.as_u64() as f64;
+        }
+        let nested = ring.clone().into_nested().unwrap();
+
+        for point in [ra_dec(0.0, 90.0), ra_dec(123.0, -45.0), ra_dec(359.9, 10.0)] {
+            let pixel = ring.pixel_at(&point);
+            assert_eq!(pixel, healpix.ra_dec_to_pixel(&point));
+            assert_eq!(ring.value_at(&point), nested.value_at(&point));
+
+            let (theta, phi) = angle(point.ra.degrees(), point.dec.degrees());
+            assert_eq!(ring.interpolate(&point), ring.interpolate_angle(theta, phi));
+        }
+    }
+
+    #[test]
+    fn unseen_pixels_are_skipped() {
+        let healpix = DynamicHealpix::new(1).unwrap();
+        let mut map = HealpixMap::<f32, Nested>::unseen(&healpix).unwrap();
+        assert!(map.is_unseen(Pixel::from_u64(3)));
+        assert!(map.is_unseen(Pixel::from_u64(12)));
+
+        let (theta, phi) = angle(45.0, 60.0);
+        let pixel = map.angle_to_pixel(theta, phi);
+        map[pixel] = 2.5;
+        map[Pixel::from_u64(11)] = -1.0;
+        let seen: Vec<_> = map.iter_seen().collect();
+        assert_eq!(
+            seen,
+            [(Pixel::from_u64(0), 2.5), (Pixel::from_u64(11), -1.0)]
+        );
+    }
+
+    #[test]
+    fn ud_grade_keeps_the_ordering() {
+        let coarse = DynamicHealpix::new(2).unwrap();
+        let fine = DynamicHealpix::new(8).unwrap();
+        let mut map = HealpixMap::<f64, Ring>::unseen(&coarse).unwrap();
+        let (theta, phi) = angle(200.0, -30.0);
+        let pixel = map.angle_to_pixel(theta, phi);
+        map[pixel] = 3.0;
+
+        let upgraded = map.ud_grade(&fine, GradeMode::Mean).unwrap();
+        assert_eq!(upgraded.face_resolution(), 8);
+        assert_eq!(upgraded[upgraded.angle_to_pixel(theta, phi)], 3.0);
+        assert_eq!(upgraded.iter_seen().count(), 16);
+        assert_eq!(upgraded.ud_grade(&coarse, GradeMode::Mean).unwrap(), map);
+    }
+
+    #[test]
+    fn interpolation_is_smooth_and_skips_unseen() {
+        let healpix = DynamicHealpix::new(16).unwrap();
+        let mut ring = HealpixMap::<f64, Ring>::filled(&healpix, 0.0).unwrap();
+        for (pixel, value) in ring.iter_mut() {
+            let (theta, phi) = healpix.pixel_to_angle(pixel).unwrap();
+            *value = math::cos(theta) + math::sin(theta) * math::cos(phi);
+        }
+        let nested = ring.clone().into_nested().unwrap();
+
+        for (theta, phi) in [angle(0.0, 89.0), angle(123.4, -45.6), angle(359.9, 10.0)] {
+            let exact = math::cos(theta) + math::sin(theta) * math::cos(phi);
+            let interpolated = ring.interpolate_angle(theta, phi);
+            assert!(
+                (interpolated - exact).abs() < 1e-2,
+                "{interpolated} {exact}"
+            );
+            assert!((nested.interpolate_angle(theta, phi) - interpolated).abs() < 1e-12);
+        }
+
+        let (theta, phi) = angle(123.4, -45.6);
+        let (pixels, _) = healpix.get_interpolation_weights::<Ring>(theta, phi);
+        let mut cut = ring.clone();
+        for pixel in &pixels[1..] {
+            cut[*pixel] = UNSEEN;
+        }
+        assert_eq!(cut.interpolate_angle(theta, phi), ring[pixels[0]]);
+        cut[pixels[0]] = UNSEEN;
+        assert!(is_unseen(cut.interpolate_angle(theta, phi)));
+        assert!(
+            healpix
+                .interpolate_angle::<Ring, _>(&[0.0; 3], theta, phi)
+                .is_err()
+        );
+    }
+}
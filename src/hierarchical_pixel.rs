@@ -0,0 +1,149 @@
+use crate::nested::Nested;
+use crate::pixel::Pixel;
+use core::ops::Range;
+
+/// A [`Nested`] pixel together with the depth of the quad-tree it belongs to.
+///
+/// A nested pixel index alone does not say which resolution it refers to. At depth `d` the
+/// grid has `nside = 2^d` and `12 * 4^d` pixels, and pixel `p` covers the pixels
+/// `4p .. 4p + 4` at depth `d + 1`.
+///
+/// # Example
+/// ```rust
+/// use realpix::{HierarchicalPixel, Nested, Pixel};
+///
+/// let pixel = HierarchicalPixel::new(3, Pixel::<Nested>::from_u64(42)).unwrap();
+/// assert_eq!(pixel.parent().unwrap().pixel().as_u64(), 10);
+/// assert_eq!(pixel.descendants(5).unwrap(), 672..688);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct HierarchicalPixel {
+    depth: u8,
+    pixel: Pixel<Nested>,
+}
+
+impl HierarchicalPixel {
+    /// The deepest supported depth, where `nside = 2^29`.
+    pub const MAX_DEPTH: u8 = 29;
+
+    /// Creates a pixel at the given depth.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidDepth`] if `depth` exceeds [`Self::MAX_DEPTH`], and
+    /// [`crate::Error::InvalidPixel`] if the pixel does not exist at that depth.
+    pub fn new(depth: u8, pixel: Pixel<Nested>) -> crate::Result<Self> {
+        if depth > Self::MAX_DEPTH {
+            return Err(crate::Error::InvalidDepth);
+        }
+        if pixel.as_u64() >= 12 << (2 * depth) {
+            return Err(crate::Error::InvalidPixel);
+        }
+        Ok(Self { depth, pixel })
+    }
+
+    /// Returns the depth of the pixel.
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Returns the nested pixel at this pixel's depth.
+    pub fn pixel(&self) -> Pixel<Nested> {
+        self.pixel
+    }
+
+    /// Returns the face resolution (`nside`) of this pixel's depth.
+    pub fn face_resolution(&self) -> u32 {
+        1 << self.depth
+    }
+
+    /// Returns the pixel one level up in the quad-tree, or `None` for a base pixel.
+    pub fn parent(&self) -> Option<Self> {
+        self.ancestor(self.depth.checked_sub(1)?)
+    }
+
+    /// Returns the pixel at `depth` that contains this pixel, or `None` if `depth` is finer
+    /// than this pixel's depth.
+    pub fn ancestor(&self, depth: u8) -> Option<Self> {
+        let shift = 2 * self.depth.checked_sub(depth)?;
+        Some(Self {
+            depth,
+            pixel: Pixel::from_u64(self.pixel.as_u64() >> shift),
+        })
+    }
+
+    /// Returns the 4 pixels one level down in the quad-tree, or `None` at [`Self::MAX_DEPTH`].
+    pub fn children(&self) -> Option<[Self; 4]> {
+        if self.depth >= Self::MAX_DEPTH {
+            return None;
+        }
+        let first = self.pixel.as_u64() << 2;
+        Some(core::array::from_fn(|i| Self {
+            depth: self.depth + 1,
+            pixel: Pixel::from_u64(first + i as u64),
+        }))
+    }
+
+    /// Returns the nested indices of the `4^k` pixels at `depth = self.depth() + k` covered by
+    /// this pixel, or `None` if `depth` is coarser than this pixel's depth or exceeds
+    /// [`Self::MAX_DEPTH`].
+    pub fn descendants(&self, depth: u8) -> Option<Range<u64>> {
+        if depth > Self::MAX_DEPTH {
+            return None;
+        }
+        let shift = 2 * depth.checked_sub(self.depth)?;
+        let first = self.pixel.as_u64() << shift;
+        Some(first..first + (1 << shift))
+    }
+
+    /// Returns `true` if `other` is this pixel or one of its descendants.
+    pub fn contains(&self, other: &HierarchicalPixel) -> bool {
+        other.ancestor(self.depth) == Some(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(depth: u8, index: u64) -> HierarchicalPixel {
+        HierarchicalPixel::new(depth, Pixel::from_u64(index)).unwrap()
+    }
+
+    #[test]
+    fn new_rejects_pixels_outside_the_depth() {
+        assert!(HierarchicalPixel::new(0, Pixel::from_u64(11)).is_ok());
+        assert!(HierarchicalPixel::new(0, Pixel::from_u64(12)).is_err());
+        assert!(HierarchicalPixel::new(30, Pixel::from_u64(0)).is_err());
+    }
+
+    #[test]
+    fn children_and_parent_are_inverse() {
+        let p = pixel(4, 1234);
+        for child in p.children().unwrap() {
+            assert_eq!(child.depth(), 5);
+            assert_eq!(child.parent(), Some(p));
+            assert!(p.contains(&child));
+        }
+        assert_eq!(pixel(0, 7).parent(), None);
+    }
+
+    #[test]
+    fn ancestor_walks_up_several_levels() {
+        let p = pixel(10, 0b1011_0110_0001);
+        assert_eq!(p.ancestor(8), Some(pixel(8, 0b1011_0110)));
+        assert_eq!(p.ancestor(10), Some(p));
+        assert_eq!(p.ancestor(11), None);
+    }
+
+    #[test]
+    fn descendants_cover_the_children_of_children() {
+        let p = pixel(2, 100);
+        let descendants = p.descendants(4).unwrap();
+        assert_eq!(descendants, 1600..1616);
+        for index in descendants {
+            assert_eq!(pixel(4, index).ancestor(2), Some(p));
+        }
+        assert_eq!(p.descendants(1), None);
+        assert_eq!(pixel(29, 5).children(), None);
+    }
+}
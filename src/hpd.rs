@@ -1,5 +1,94 @@
+/// Discrete face coordinates of a pixel: the base face `f` and the position
+/// `(x, y)` of the pixel inside that face.
+///
+/// `x` grows towards the north-east and `y` towards the north-west, so
+/// `(0, 0)` is the southernmost pixel of a face.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Hpd {
     pub x: i64,
     pub y: i64,
     pub f: i32,
 }
+
+/// Offsets of the 8 neighbours in face coordinates, in the order
+/// S, SW, W, NW, N, NE, E, SE.
+const NB_XOFFSET: [i64; 8] = [-1, -1, -1, 0, 1, 1, 1, 0];
+const NB_YOFFSET: [i64; 8] = [-1, 0, 1, 1, 1, 0, -1, -1];
+
+/// Face of the neighbour when leaving a face, indexed by the direction the
+/// face was left in (see `Hpd::neighbours`) and the current face. `-1` marks
+/// the face corners where no neighbouring face exists.
+///
+/// Precomputed constants from the original HEALPix C++ code.
+static NB_FACEARRAY: [[i32; 12]; 9] = [
+    [8, 9, 10, 11, -1, -1, -1, -1, 10, 11, 8, 9], // S
+    [5, 6, 7, 4, 8, 9, 10, 11, 9, 10, 11, 8],     // SE
+    [-1, -1, -1, -1, 5, 6, 7, 4, -1, -1, -1, -1], // E
+    [4, 5, 6, 7, 11, 8, 9, 10, 11, 8, 9, 10],     // SW
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],       // center
+    [1, 2, 3, 0, 0, 1, 2, 3, 5, 6, 7, 4],         // NE
+    [-1, -1, -1, -1, 7, 4, 5, 6, -1, -1, -1, -1], // W
+    [3, 0, 1, 2, 3, 0, 1, 2, 4, 5, 6, 7],         // NW
+    [2, 3, 0, 1, -1, -1, -1, -1, 0, 1, 2, 3],     // N
+];
+
+/// Coordinate flips needed when entering the neighbouring face, indexed by
+/// direction and face row (north, equatorial, south). Bit 1 flips `x`, bit 2
+/// flips `y` and bit 4 swaps `x` and `y`.
+static NB_SWAPARRAY: [[u8; 3]; 9] = [
+    [0, 0, 3], // S
+    [0, 0, 6], // SE
+    [0, 0, 0], // E
+    [0, 0, 5], // SW
+    [0, 0, 0], // center
+    [5, 0, 0], // NE
+    [0, 0, 0], // W
+    [6, 0, 0], // NW
+    [3, 0, 0], // N
+];
+
+impl Hpd {
+    /// Returns the face coordinates of the 8 neighbours of this pixel in the
+    /// order S, SW, W, NW, N, NE, E, SE.
+    ///
+    /// At the 8 face corners where only 7 neighbours exist the missing one is `None`.
+    pub fn neighbours(&self, nside: i64) -> [Option<Hpd>; 8] {
+        let mut result = [None; 8];
+        for (i, neighbour) in result.iter_mut().enumerate() {
+            let mut x = self.x + NB_XOFFSET[i];
+            let mut y = self.y + NB_YOFFSET[i];
+            let mut nbnum = 4;
+            if x < 0 {
+                x += nside;
+                nbnum -= 1;
+            } else if x >= nside {
+                x -= nside;
+                nbnum += 1;
+            }
+            if y < 0 {
+                y += nside;
+                nbnum -= 3;
+            } else if y >= nside {
+                y -= nside;
+                nbnum += 3;
+            }
+
+            let f = NB_FACEARRAY[nbnum][self.f as usize];
+            if f < 0 {
+                continue;
+            }
+            let bits = NB_SWAPARRAY[nbnum][(self.f >> 2) as usize];
+            if bits & 1 != 0 {
+                x = nside - x - 1;
+            }
+            if bits & 2 != 0 {
+                y = nside - y - 1;
+            }
+            if bits & 4 != 0 {
+                core::mem::swap(&mut x, &mut y);
+            }
+            *neighbour = Some(Hpd { x, y, f });
+        }
+        result
+    }
+}
@@ -0,0 +1,157 @@
+use crate::math;
+use crate::ring::Ring;
+use core::f64::consts::{PI, TAU};
+
+/// Returns the 4 [`Ring`] pixels surrounding `(theta, phi)` and their bilinear weights, as
+/// HEALPix's `get_interpol` does.
+///
+/// The first two pixels bracket `phi` on the ring north of the point, and the last two on the
+/// ring south of it. Above the first ring and below the last one, the missing ring is replaced
+/// by the 4 pixels around the pole, sharing the weight given to the pole equally.
+pub(crate) fn interpolation_weights(nside: u32, theta: f64, phi: f64) -> ([u64; 4], [f64; 4]) {
+    let nside = nside as i64;
+    let total_pixels = 12 * nside * nside;
+    let phi = math::rem_euclid(phi, TAU);
+    let ring1 = Ring::ring_above(nside, math::cos(theta));
+    let ring2 = ring1 + 1;
+
+    let mut pixels = [0; 4];
+    let mut weights = [0.0; 4];
+    // Brackets `phi` between two pixels of `ring`, returning the colatitude of the ring.
+    let mut bracket = |ring: i64, slot: usize| {
+        let info = Ring::ring_info(nside, ring);
+        let shift = if info.shifted { 0.5 } else { 0.0 };
+        let dphi = TAU / info.pixel_count as f64;
+        let offset = phi / dphi - shift;
+        let i1 = if offset < 0.0 {
+            offset as i64 - 1
+        } else {
+            offset as i64
+        };
+        let w = (phi - (i1 as f64 + shift) * dphi) / dphi;
+        let i2 = if i1 + 1 >= info.pixel_count {
+            i1 + 1 - info.pixel_count
+        } else {
+            i1 + 1
+        };
+        let i1 = if i1 < 0 { i1 + info.pixel_count } else { i1 };
+        pixels[slot] = (info.first_pixel + i1) as u64;
+        pixels[slot + 1] = (info.first_pixel + i2) as u64;
+        weights[slot] = 1.0 - w;
+        weights[slot + 1] = w;
+        ring_theta(nside, ring)
+    };
+
+    let theta1 = (ring1 > 0).then(|| bracket(ring1, 0));
+    let theta2 = (ring2 < 4 * nside).then(|| bracket(ring2, 2));
+
+    match (theta1, theta2) {
+        (None, Some(theta2)) => {
+            let w = theta / theta2;
+            let pole = (1.0 - w) * 0.25;
+            weights = [pole, pole, weights[2] * w + pole, weights[3] * w + pole];
+            pixels[0] = (pixels[2] + 2) & 3;
+            pixels[1] = (pixels[3] + 2) & 3;
+        }
+        (Some(theta1), None) => {
+            let w = (theta - theta1) / (PI - theta1);
+            let pole = w * 0.25;
+            weights = [
+                weights[0] * (1.0 - w) + pole,
+                weights[1] * (1.0 - w) + pole,
+                pole,
+                pole,
+            ];
+            pixels[2] = ((pixels[0] + 2) & 3) + total_pixels as u64 - 4;
+            pixels[3] = ((pixels[1] + 2) & 3) + total_pixels as u64 - 4;
+        }
+        (Some(theta1), Some(theta2)) => {
+            let w = (theta - theta1) / (theta2 - theta1);
+            weights[0] *= 1.0 - w;
+            weights[1] *= 1.0 - w;
+            weights[2] *= w;
+            weights[3] *= w;
+        }
+        (None, None) => unreachable!("every point lies next to at least one ring"),
+    }
+    (pixels, weights)
+}
+
+/// Returns the colatitude of the centres of `ring`, as HEALPix C++'s `get_ring_info2` does: from
+/// the sine and cosine near the poles, where `acos` loses precision.
+fn ring_theta(nside: i64, ring: i64) -> f64 {
+    let north_ring = if ring > 2 * nside {
+        4 * nside - ring
+    } else {
+        ring
+    };
+    let theta = if north_ring < nside {
+        let tmp = (north_ring * north_ring) as f64 * (4.0 / (12 * nside * nside) as f64);
+        math::atan2(math::sqrt(tmp * (2.0 - tmp)), 1.0 - tmp)
+    } else {
+        math::acos(Ring::ring_info(nside, north_ring).z)
+    };
+    if north_ring == ring {
+        theta
+    } else {
+        PI - theta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::numbering_scheme::NumberingScheme;
+    use crate::pixel::Pixel;
+
+    #[test]
+    fn weights_sum_to_one() {
+        for nside in [1, 2, 8] {
+            for i in 0..=40 {
+                let theta = PI * i as f64 / 40.0;
+                let (_, weights) = interpolation_weights(nside, theta, 1.3 * i as f64);
+                assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+                assert!(weights.iter().all(|w| (0.0..=1.0).contains(w)));
+            }
+        }
+    }
+
+    #[test]
+    fn pixel_centres_get_all_the_weight() {
+        let nside = 4;
+        for pixel in 0..12 * nside as u64 * nside as u64 {
+            let (theta, phi) = Ring::pixel_to_angle::<Ring>(nside, Pixel::from_u64(pixel)).unwrap();
+            let (pixels, weights) = interpolation_weights(nside, theta, phi);
+            let weight: f64 = pixels
+                .iter()
+                .zip(weights)
+                .filter(|(p, _)| **p == pixel)
+                .map(|(_, w)| w)
+                .sum();
+            assert!((weight - 1.0).abs() < 1e-9, "pixel {pixel}: {weight}");
+        }
+    }
+
+    #[test]
+    fn polar_points_share_weight_across_the_pole() {
+        // Above the first ring, pixels 3 and 0 bracket phi and the opposite pixels 1 and 2
+        // stand in for the missing ring.
+        let (pixels, weights) = interpolation_weights(1, 0.1, 0.2);
+        assert_eq!(pixels, [1, 2, 3, 0]);
+        let theta2 = math::acos(2.0f64 / 3.0);
+        let w = 0.1 / theta2;
+        let first = 0.2 / (PI / 2.0) - 0.5 + 1.0;
+        let expected = [
+            (1.0 - w) / 4.0,
+            (1.0 - w) / 4.0,
+            (1.0 - first) * w + (1.0 - w) / 4.0,
+            first * w + (1.0 - w) / 4.0,
+        ];
+        for (w, e) in weights.iter().zip(expected) {
+            assert!((w - e).abs() < 1e-12);
+        }
+
+        let (pixels, _) = interpolation_weights(1, 3.0, 4.0);
+        assert_eq!(pixels, [10, 11, 8, 9]);
+    }
+}
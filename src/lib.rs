@@ -1,28 +1,104 @@
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
 #[cfg(feature = "std")]
 extern crate std;
 #[cfg(feature = "std")]
 extern crate std as core;
 
+#[cfg(feature = "alloc")]
+mod alm;
+mod cartesian_vector;
+#[cfg(test)]
+mod compatibility;
+mod complex;
 mod const_healpix;
 mod dynamic_healpix;
 mod error;
+mod face_coordinates;
+#[cfg(feature = "alloc")]
+mod fits;
+mod fixed_point;
+mod fractional_face_coordinates;
+#[cfg(feature = "std")]
 mod gnomonic_project;
+#[cfg(feature = "alloc")]
+mod grade_mode;
 mod healpix;
+#[cfg(feature = "fits")]
+mod healpix_fits;
+#[cfg(feature = "alloc")]
+mod healpix_map;
+mod hierarchical_pixel;
 mod hpd;
+mod interpolation;
+#[cfg(feature = "alloc")]
+mod map_value;
+mod math;
+#[cfg(feature = "alloc")]
+mod moc;
+#[cfg(feature = "alloc")]
+mod moc_ascii;
+#[cfg(feature = "alloc")]
+mod moc_fits;
+#[cfg(feature = "alloc")]
+mod moc_json;
 mod nested;
 mod numbering_scheme;
 mod pixel;
+#[cfg(feature = "alloc")]
+mod power_spectrum;
+#[cfg(feature = "alloc")]
+mod query_box;
+#[cfg(feature = "alloc")]
+mod query_disc;
+mod query_mode;
+#[cfg(feature = "alloc")]
+mod query_strip;
+#[cfg(feature = "alloc")]
+mod reorder;
 mod result;
 mod ring;
+#[cfg(feature = "alloc")]
+mod sht;
+#[cfg(feature = "alloc")]
+mod spectrum_options;
+#[cfg(feature = "alloc")]
+mod spherical_polygon;
+#[cfg(feature = "alloc")]
+mod ud_grade;
+mod unique_pixel;
+#[cfg(feature = "alloc")]
+mod vec3;
 
+#[cfg(feature = "alloc")]
+pub use self::alm::Alm;
+pub use self::cartesian_vector::CartesianVector;
+pub use self::complex::Complex;
 pub use self::const_healpix::ConstHealpix;
 pub use self::dynamic_healpix::DynamicHealpix;
 pub use self::error::Error;
+pub use self::face_coordinates::FaceCoordinates;
+pub use self::fractional_face_coordinates::FractionalFaceCoordinates;
+#[cfg(feature = "alloc")]
+pub use self::grade_mode::GradeMode;
 pub use self::healpix::Healpix;
+#[cfg(feature = "fits")]
+pub use self::healpix_fits::HealpixFits;
+#[cfg(feature = "alloc")]
+pub use self::healpix_map::{HealpixMap, UNSEEN};
+pub use self::hierarchical_pixel::HierarchicalPixel;
+#[cfg(feature = "alloc")]
+pub use self::map_value::MapValue;
+#[cfg(feature = "alloc")]
+pub use self::moc::Moc;
 pub use self::nested::Nested;
 pub use self::numbering_scheme::NumberingScheme;
 pub use self::pixel::Pixel;
+pub use self::query_mode::QueryMode;
 pub use self::result::Result;
 pub use self::ring::Ring;
+#[cfg(feature = "alloc")]
+pub use self::spectrum_options::SpectrumOptions;
+pub use self::unique_pixel::UniquePixel;
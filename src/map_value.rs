@@ -0,0 +1,36 @@
+/// A numeric type that can be stored in a HEALPix map, such as [`crate::HealpixMap`] or the
+/// slices [`crate::Healpix::ud_grade`] works on.
+///
+/// Values are combined in `f64` and converted back, and [`crate::UNSEEN`] marks pixels with
+/// no data. It is implemented for `f32` and `f64`.
+pub trait MapValue: Copy {
+    /// Returns the value as an `f64`.
+    fn to_f64(self) -> f64;
+
+    /// Creates a value from an `f64`, rounding it if needed.
+    fn from_f64(value: f64) -> Self;
+}
+
+impl MapValue for f64 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    #[inline]
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+impl MapValue for f32 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    #[inline]
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
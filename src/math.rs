@@ -0,0 +1,65 @@
+//! Floating-point functions that are only inherent to `f64` with `std`.
+//!
+//! With the `std` feature these forward to the standard library, which uses the platform's
+//! math routines. Without it they fall back to `libm`, so the crate builds on targets with no
+//! operating system. Clippy rejects the `std`-only methods elsewhere in the crate (see
+//! `clippy.toml`), so new code cannot bypass these wrappers by accident.
+#![allow(clippy::disallowed_methods)]
+
+macro_rules! unary {
+    ($($(#[$attr:meta])* $name:ident),* $(,)?) => {$(
+        $(#[$attr])*
+        #[inline]
+        pub(crate) fn $name(x: f64) -> f64 {
+            #[cfg(feature = "std")]
+            return x.$name();
+            #[cfg(not(feature = "std"))]
+            return libm::$name(x);
+        }
+    )*};
+}
+
+macro_rules! binary {
+    ($($(#[$attr:meta])* $name:ident => $libm:ident),* $(,)?) => {$(
+        $(#[$attr])*
+        #[inline]
+        pub(crate) fn $name(x: f64, y: f64) -> f64 {
+            #[cfg(feature = "std")]
+            return x.$name(y);
+            #[cfg(not(feature = "std"))]
+            return libm::$libm(x, y);
+        }
+    )*};
+}
+
+unary!(
+    acos,
+    #[cfg(feature = "alloc")]
+    asin,
+    #[cfg(feature = "alloc")]
+    ceil,
+    cos,
+    floor,
+    sin,
+    sqrt,
+);
+
+binary!(
+    /// Returns the four-quadrant arctangent of `y / x`, called as `atan2(y, x)`.
+    atan2 => atan2,
+    hypot => hypot,
+    #[cfg(feature = "alloc")]
+    powf => pow,
+);
+
+/// Returns the least non-negative remainder of `x` modulo `y`.
+#[inline]
+pub(crate) fn rem_euclid(x: f64, y: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.rem_euclid(y);
+    #[cfg(not(feature = "std"))]
+    {
+        let r = x % y;
+        if r < 0.0 { r + y.abs() } else { r }
+    }
+}
@@ -0,0 +1,517 @@
+use crate::hierarchical_pixel::HierarchicalPixel;
+use crate::nested::Nested;
+use crate::numbering_scheme::NumberingScheme;
+use crate::pixel::Pixel;
+use crate::query_disc::query_disc_nested_ranges;
+use crate::query_mode::QueryMode;
+use alloc::vec::Vec;
+use core::ops::Range;
+#[cfg(feature = "std")]
+use latlong::{Float, RaDec};
+
+const MAX_DEPTH: u8 = HierarchicalPixel::MAX_DEPTH;
+
+/// Number of pixels at [`HierarchicalPixel::MAX_DEPTH`].
+const MAX_DEPTH_PIXELS: u64 = 12 << (2 * MAX_DEPTH);
+
+/// A Multi-Order Coverage map: a set of [`Nested`] pixels at mixed depths.
+///
+/// The set is stored normalized, as sorted and non-adjacent ranges of pixel indices at
+/// [`HierarchicalPixel::MAX_DEPTH`], so two MOCs covering the same area have the same
+/// [`Moc::ranges`] regardless of how they were built. The MOC's `depth` is the finest depth it
+/// resolves. It is written out by every serialization, so MOCs are only equal when their depths
+/// are too.
+///
+/// # Example
+/// ```rust
+/// use realpix::{Moc, QueryMode};
+///
+/// let moc = Moc::from_cone_angle(8, 1.2, 0.3, 0.1, QueryMode::Inclusive).unwrap();
+/// assert!(moc.contains_angle(1.2, 0.3));
+/// ```
+///
+/// MOCs can be exchanged with other tools in the IVOA MOC formats: the ASCII form through
+/// [`core::fmt::Display`] and [`core::str::FromStr`], and the JSON and FITS forms through
+/// [`Moc::to_json`], [`Moc::from_json`], [`Moc::to_fits`] and [`Moc::from_fits`].
+///
+/// ```rust
+/// use realpix::Moc;
+///
+/// let a: Moc = "3/1-5 4/100".parse().unwrap();
+/// let b = Moc::from_json(r#"{"3":[5,6]}"#).unwrap();
+/// assert_eq!(a.union(&b).to_string(), "3/1-6 4/100");
+/// assert_eq!(Moc::from_fits(&a.to_fits()).unwrap(), a);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Moc {
+    depth: u8,
+    ranges: Vec<Range<u64>>,
+}
+
+impl Moc {
+    /// Creates an empty MOC resolving pixels down to `depth`.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidDepth`] if `depth` exceeds [`HierarchicalPixel::MAX_DEPTH`].
+    pub fn new(depth: u8) -> crate::Result<Self> {
+        if depth > MAX_DEPTH {
+            return Err(crate::Error::InvalidDepth);
+        }
+        Ok(Self {
+            depth,
+            ranges: Vec::new(),
+        })
+    }
+
+    /// Creates a MOC covering the whole sky.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidDepth`] if `depth` exceeds [`HierarchicalPixel::MAX_DEPTH`].
+    pub fn full(depth: u8) -> crate::Result<Self> {
+        let mut moc = Self::new(depth)?;
+        moc.ranges.push(0..MAX_DEPTH_PIXELS);
+        Ok(moc)
+    }
+
+    /// Creates a MOC from pixels at a single depth, such as the output of
+    /// [`crate::Healpix::iter_pixels`] for a grid with `nside = 2^depth`.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidDepth`] if `depth` is too deep and
+    /// [`crate::Error::InvalidPixel`] if a pixel does not exist at `depth`.
+    pub fn from_pixels(
+        depth: u8,
+        pixels: impl IntoIterator<Item = Pixel<Nested>>,
+    ) -> crate::Result<Self> {
+        let cells = pixels
+            .into_iter()
+            .map(|pixel| HierarchicalPixel::new(depth, pixel))
+            .collect::<crate::Result<Vec<_>>>()?;
+        Self::from_cells_at_depth(depth, cells)
+    }
+
+    /// Creates a MOC from pixels at mixed depths. The MOC's depth is the deepest pixel's depth.
+    pub fn from_cells(cells: impl IntoIterator<Item = HierarchicalPixel>) -> Self {
+        let mut depth = 0;
+        let ranges = cells
+            .into_iter()
+            .map(|cell| {
+                depth = depth.max(cell.depth());
+                cell.descendants(MAX_DEPTH).unwrap()
+            })
+            .collect();
+        Self {
+            depth,
+            ranges: normalize(ranges),
+        }
+    }
+
+    /// Creates a MOC from pixels at mixed depths, resolving down to `depth` or to the deepest
+    /// pixel's depth, whichever is finer.
+    pub(crate) fn from_cells_at_depth(
+        depth: u8,
+        cells: impl IntoIterator<Item = HierarchicalPixel>,
+    ) -> crate::Result<Self> {
+        if depth > MAX_DEPTH {
+            return Err(crate::Error::InvalidDepth);
+        }
+        let mut moc = Self::from_cells(cells);
+        moc.depth = moc.depth.max(depth);
+        Ok(moc)
+    }
+
+    /// Creates a MOC from ranges of [`Nested`] pixel indices at [`HierarchicalPixel::MAX_DEPTH`],
+    /// in any order and possibly overlapping, resolving down to `depth`.
+    pub(crate) fn from_ranges_at_depth(depth: u8, ranges: Vec<Range<u64>>) -> crate::Result<Self> {
+        if depth > MAX_DEPTH {
+            return Err(crate::Error::InvalidDepth);
+        }
+        Ok(Self {
+            depth,
+            ranges: normalize(ranges),
+        })
+    }
+
+    /// Creates a MOC of the pixels at `depth` selected by a disc of angular `radius`, in
+    /// radians, around `(theta, phi)`. See [`crate::Healpix::query_disc_angle`].
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidDepth`] if `depth` exceeds [`HierarchicalPixel::MAX_DEPTH`].
+    pub fn from_cone_angle(
+        depth: u8,
+        theta: f64,
+        phi: f64,
+        radius: f64,
+        mode: QueryMode,
+    ) -> crate::Result<Self> {
+        if depth > MAX_DEPTH {
+            return Err(crate::Error::InvalidDepth);
+        }
+        let shift = 2 * (MAX_DEPTH - depth);
+        let ranges = query_disc_nested_ranges(1 << depth, theta, phi, radius, mode)
+            .into_iter()
+            .map(|r| r.start << shift..r.end << shift)
+            .collect();
+        Ok(Self { depth, ranges })
+    }
+
+    /// Creates a MOC of the pixels at `depth` selected by a disc of angular `radius`, in
+    /// radians, around `centre`. See [`Self::from_cone_angle`].
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidDepth`] if `depth` exceeds [`HierarchicalPixel::MAX_DEPTH`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use realpix::{Moc, QueryMode};
+    /// use latlong::{Declination, RaDec, RightAscension};
+    ///
+    /// let centre = RaDec {
+    ///     ra: RightAscension::from_degrees(10.0),
+    ///     dec: Declination::from_degrees(20.0),
+    /// };
+    /// let moc = Moc::from_cone(8, &centre, 0.1, QueryMode::Inclusive).unwrap();
+    /// assert!(moc.contains_ra_dec(&centre));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_cone<T: Float>(
+        depth: u8,
+        centre: &RaDec<T>,
+        radius: f64,
+        mode: QueryMode,
+    ) -> crate::Result<Self> {
+        let theta = core::f64::consts::FRAC_PI_2 - centre.dec.radians().to_f64();
+        let phi = centre.ra.radians().to_f64();
+        Self::from_cone_angle(depth, theta, phi, radius, mode)
+    }
+
+    /// Returns the finest depth this MOC resolves.
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Returns the sorted, non-adjacent ranges of [`Nested`] pixel indices at
+    /// [`HierarchicalPixel::MAX_DEPTH`] covered by this MOC.
+    pub fn ranges(&self) -> &[Range<u64>] {
+        &self.ranges
+    }
+
+    /// Returns `true` if the MOC covers no part of the sky.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns the largest pixels that exactly make up this MOC, sorted by the index of their
+    /// first descendant.
+    pub fn cells(&self) -> impl Iterator<Item = HierarchicalPixel> + '_ {
+        self.ranges.iter().flat_map(|range| RangeCells {
+            start: range.start,
+            end: range.end,
+        })
+    }
+
+    /// Returns the nested indices of [`Self::cells`] grouped by depth, from the coarsest depth
+    /// to [`Self::depth`], each sorted. This is the layout used by the MOC file formats.
+    pub(crate) fn cells_by_depth(&self) -> Vec<Vec<u64>> {
+        let mut by_depth = alloc::vec![Vec::new(); self.depth as usize + 1];
+        for cell in self.cells() {
+            by_depth[cell.depth() as usize].push(cell.pixel().as_u64());
+        }
+        for indices in &mut by_depth {
+            indices.sort_unstable();
+        }
+        by_depth
+    }
+
+    /// Returns the fraction of the sky covered by this MOC, in `[0, 1]`.
+    pub fn sky_fraction(&self) -> f64 {
+        let covered: u64 = self.ranges.iter().map(|r| r.end - r.start).sum();
+        covered as f64 / MAX_DEPTH_PIXELS as f64
+    }
+
+    /// Returns the area covered by this MOC, in steradians.
+    pub fn area(&self) -> f64 {
+        self.sky_fraction() * 4.0 * core::f64::consts::PI
+    }
+
+    /// Returns `true` if the whole of `pixel` is covered by this MOC.
+    pub fn contains_pixel(&self, pixel: &HierarchicalPixel) -> bool {
+        let range = pixel.descendants(MAX_DEPTH).unwrap();
+        let i = self.ranges.partition_point(|r| r.end <= range.start);
+        self.ranges
+            .get(i)
+            .is_some_and(|r| r.start <= range.start && range.end <= r.end)
+    }
+
+    /// Returns `true` if any part of `pixel` is covered by this MOC.
+    pub fn intersects_pixel(&self, pixel: &HierarchicalPixel) -> bool {
+        let range = pixel.descendants(MAX_DEPTH).unwrap();
+        let i = self.ranges.partition_point(|r| r.end <= range.start);
+        self.ranges.get(i).is_some_and(|r| r.start < range.end)
+    }
+
+    /// Returns `true` if the point `(theta, phi)` lies in a pixel covered by this MOC.
+    pub fn contains_angle(&self, theta: f64, phi: f64) -> bool {
+        let pixel = Nested::angle_to_pixel(1 << self.depth, theta, phi);
+        HierarchicalPixel::new(self.depth, pixel).is_ok_and(|p| self.contains_pixel(&p))
+    }
+
+    /// Returns `true` if the point lies in a pixel covered by this MOC.
+    #[cfg(feature = "std")]
+    pub fn contains_ra_dec<T: Float>(&self, ra_dec: &RaDec<T>) -> bool {
+        let theta = core::f64::consts::FRAC_PI_2 - ra_dec.dec.radians().to_f64();
+        let phi = ra_dec.ra.radians().to_f64();
+        self.contains_angle(theta, phi)
+    }
+
+    /// Returns the MOC covering the sky covered by either MOC.
+    pub fn union(&self, other: &Moc) -> Moc {
+        let mut ranges = Vec::with_capacity(self.ranges.len() + other.ranges.len());
+        ranges.extend(self.ranges.iter().cloned());
+        ranges.extend(other.ranges.iter().cloned());
+        Moc {
+            depth: self.depth.max(other.depth),
+            ranges: normalize(ranges),
+        }
+    }
+
+    /// Returns the MOC covering the sky covered by both MOCs.
+    pub fn intersection(&self, other: &Moc) -> Moc {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let a = &self.ranges[i];
+            let b = &other.ranges[j];
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Moc {
+            depth: self.depth.max(other.depth),
+            ranges,
+        }
+    }
+
+    /// Returns the MOC covering the sky covered by this MOC but not by `other`.
+    pub fn difference(&self, other: &Moc) -> Moc {
+        self.intersection(&other.complement())
+    }
+
+    /// Returns the MOC covering the sky not covered by this MOC.
+    pub fn complement(&self) -> Moc {
+        let mut ranges = Vec::with_capacity(self.ranges.len() + 1);
+        let mut start = 0;
+        for range in &self.ranges {
+            if range.start > start {
+                ranges.push(start..range.start);
+            }
+            start = range.end;
+        }
+        if start < MAX_DEPTH_PIXELS {
+            ranges.push(start..MAX_DEPTH_PIXELS);
+        }
+        Moc {
+            depth: self.depth,
+            ranges,
+        }
+    }
+
+    /// Returns this MOC at a coarser `depth`, where every pixel that is partially covered
+    /// becomes fully covered. Depths finer than the MOC's depth leave it unchanged.
+    pub fn degrade(&self, depth: u8) -> Moc {
+        if depth >= self.depth {
+            return self.clone();
+        }
+        let shift = 2 * (MAX_DEPTH - depth);
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|r| (r.start >> shift) << shift..r.end.div_ceil(1 << shift) << shift)
+            .collect();
+        Moc {
+            depth,
+            ranges: normalize(ranges),
+        }
+    }
+}
+
+impl FromIterator<HierarchicalPixel> for Moc {
+    fn from_iter<I: IntoIterator<Item = HierarchicalPixel>>(iter: I) -> Self {
+        Self::from_cells(iter)
+    }
+}
+
+/// Sorts ranges and merges overlapping or adjacent ones.
+fn normalize(mut ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+    ranges.sort_unstable_by_key(|r| r.start);
+    let mut merged: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+    for range in ranges.into_iter().filter(|r| r.start < r.end) {
+        match merged.last_mut() {
+            Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Splits a range at [`HierarchicalPixel::MAX_DEPTH`] into the largest aligned pixels.
+struct RangeCells {
+    start: u64,
+    end: u64,
+}
+
+impl Iterator for RangeCells {
+    type Item = HierarchicalPixel;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            return None;
+        }
+        // The largest block both aligned at `start` and fitting before `end`, in whole levels.
+        let aligned = self.start.trailing_zeros().min(2 * MAX_DEPTH as u32) / 2;
+        let fitting = (63 - (self.end - self.start).leading_zeros()) / 2;
+        let levels = aligned.min(fitting);
+        let depth = MAX_DEPTH - levels as u8;
+        let pixel = Pixel::from_u64(self.start >> (2 * levels));
+        self.start += 1 << (2 * levels);
+        Some(HierarchicalPixel::new(depth, pixel).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(depth: u8, index: u64) -> HierarchicalPixel {
+        HierarchicalPixel::new(depth, Pixel::from_u64(index)).unwrap()
+    }
+
+    /// Returns the `(theta, phi)` of a point given in RA/Dec degrees.
+    fn angle(ra: f64, dec: f64) -> (f64, f64) {
+        ((90.0 - dec).to_radians(), ra.to_radians())
+    }
+
+    fn cone(depth: u8, (theta, phi): (f64, f64), radius: f64, mode: QueryMode) -> Moc {
+        Moc::from_cone_angle(depth, theta, phi, radius, mode).unwrap()
+    }
+
+    #[test]
+    fn mocs_are_normalized() {
+        let children = Moc::from_cells(cell(3, 17).children().unwrap());
+        let parent = Moc::from_cells([cell(3, 17)]);
+        assert_eq!(children.ranges(), parent.ranges());
+        assert_ne!(children.depth(), parent.depth());
+        assert_ne!(children, parent);
+        assert_eq!(children, parent.union(&Moc::new(4).unwrap()));
+        assert_eq!(children.cells().collect::<Vec<_>>(), [cell(3, 17)]);
+    }
+
+    #[test]
+    fn cells_split_ranges_into_the_largest_pixels() {
+        let moc = Moc::from_cells([cell(2, 5), cell(2, 6), cell(2, 7), cell(1, 2), cell(3, 4)]);
+        let cells: Vec<_> = moc.cells().collect();
+        assert_eq!(
+            cells,
+            [cell(3, 4), cell(2, 5), cell(2, 6), cell(2, 7), cell(1, 2)]
+        );
+        assert_eq!(Moc::from_cells(cells), moc);
+    }
+
+    #[test]
+    fn set_operations_follow_set_algebra() {
+        let a = cone(6, angle(10.0, 10.0), 0.2, QueryMode::Exact);
+        let b = cone(6, angle(20.0, 15.0), 0.2, QueryMode::Exact);
+
+        let union = a.union(&b);
+        let intersection = a.intersection(&b);
+        let difference = a.difference(&b);
+        assert!(!intersection.is_empty());
+        assert_eq!(difference.union(&intersection), a);
+        assert!(difference.intersection(&b).is_empty());
+        assert_eq!(
+            union.sky_fraction(),
+            a.sky_fraction() + b.sky_fraction() - intersection.sky_fraction()
+        );
+        assert_eq!(a.complement().complement(), a);
+        assert_eq!(a.union(&a.complement()), Moc::full(6).unwrap());
+    }
+
+    #[test]
+    fn containment_tests_points_and_pixels() {
+        let moc = Moc::from_cells([cell(4, 100)]);
+        assert!(moc.contains_pixel(&cell(4, 100)));
+        assert!(moc.contains_pixel(&cell(6, 1600)));
+        assert!(!moc.contains_pixel(&cell(3, 25)));
+        assert!(moc.intersects_pixel(&cell(3, 25)));
+        assert!(!moc.intersects_pixel(&cell(4, 101)));
+
+        let (theta, phi) = angle(200.0, -45.0);
+        let cone = cone(8, (theta, phi), 0.05, QueryMode::Inclusive);
+        assert!(cone.contains_angle(theta, phi));
+        let (theta, phi) = angle(20.0, 45.0);
+        assert!(!cone.contains_angle(theta, phi));
+    }
+
+    #[test]
+    fn degrade_covers_the_original() {
+        let moc = cone(10, angle(0.0, 89.0), 0.03, QueryMode::Exact);
+        let degraded = moc.degrade(5);
+        assert_eq!(degraded.depth(), 5);
+        assert_eq!(degraded.intersection(&moc), moc);
+        assert!(degraded.cells().all(|c| c.depth() <= 5));
+    }
+
+    #[test]
+    fn area_of_the_full_sky_is_4_pi() {
+        let full = Moc::full(3).unwrap();
+        assert_eq!(full.sky_fraction(), 1.0);
+        assert!((full.area() - 4.0 * core::f64::consts::PI).abs() < 1e-12);
+        assert_eq!(full.cells().count(), 12);
+    }
+
+    #[test]
+    fn from_pixels_rejects_pixels_outside_the_depth() {
+        assert!(Moc::from_pixels(1, [Pixel::from_u64(48)]).is_err());
+        let moc = Moc::from_pixels(1, [Pixel::from_u64(4), Pixel::from_u64(5)]).unwrap();
+        assert_eq!(moc.depth(), 1);
+        assert_eq!(moc.sky_fraction(), 2.0 / 48.0);
+    }
+
+    #[test]
+    fn from_cone_rejects_depths_beyond_the_maximum() {
+        let (theta, phi) = angle(0.0, 0.0);
+        assert!(Moc::from_cone_angle(MAX_DEPTH, theta, phi, 1e-6, QueryMode::Exact).is_ok());
+        assert!(matches!(
+            Moc::from_cone_angle(MAX_DEPTH + 1, theta, phi, 0.1, QueryMode::Exact),
+            Err(crate::Error::InvalidDepth)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn ra_dec_cones_agree_with_angles() {
+        use latlong::{Declination, RightAscension};
+
+        let centre = RaDec {
+            ra: RightAscension::from_degrees(200.0),
+            dec: Declination::from_degrees(-45.0),
+        };
+        let moc = Moc::from_cone(8, &centre, 0.05, QueryMode::Inclusive).unwrap();
+        assert_eq!(
+            moc,
+            cone(8, angle(200.0, -45.0), 0.05, QueryMode::Inclusive)
+        );
+        assert!(moc.contains_ra_dec(&centre));
+        assert!(matches!(
+            Moc::from_cone(MAX_DEPTH + 1, &centre, 0.1, QueryMode::Exact),
+            Err(crate::Error::InvalidDepth)
+        ));
+    }
+}
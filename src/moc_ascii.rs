@@ -0,0 +1,150 @@
+use crate::hierarchical_pixel::HierarchicalPixel;
+use crate::moc::Moc;
+use crate::pixel::Pixel;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/// Writes the MOC in the IVOA MOC 2.0 ASCII serialization, such as `3/1-3 9 4/40 41 8/`.
+///
+/// Pixels are grouped by depth, from the coarsest, and consecutive indices are collapsed into
+/// ranges. A trailing `depth/` with no pixels records the MOC's depth when no pixel is that
+/// deep.
+impl fmt::Display for Moc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut separator = "";
+        for (depth, indices) in self.cells_by_depth().iter().enumerate() {
+            if indices.is_empty() && depth != self.depth() as usize {
+                continue;
+            }
+            write!(f, "{}{}/", separator, depth)?;
+            separator = " ";
+
+            let mut i = 0;
+            while i < indices.len() {
+                let mut j = i;
+                while j + 1 < indices.len() && indices[j + 1] == indices[j] + 1 {
+                    j += 1;
+                }
+                if i > 0 {
+                    f.write_str(" ")?;
+                }
+                if j == i {
+                    write!(f, "{}", indices[i])?;
+                } else {
+                    write!(f, "{}-{}", indices[i], indices[j])?;
+                }
+                i = j + 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses the IVOA MOC ASCII serialization.
+///
+/// Both the MOC 2.0 form (`3/1-3 9 4/40`) and the MOC 1.x form (`3/1-3,9 4/40`) are
+/// accepted, with an optional `s` prefix on the depths.
+impl FromStr for Moc {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        let mut depth = None;
+        let mut max_depth = 0;
+        let mut ranges = Vec::new();
+
+        for token in s.split(|c: char| c.is_whitespace() || c == ',') {
+            let token = match token.split_once('/') {
+                Some((d, rest)) => {
+                    let d: u8 = d
+                        .strip_prefix('s')
+                        .unwrap_or(d)
+                        .parse()
+                        .map_err(|_| crate::Error::InvalidMoc)?;
+                    if d > HierarchicalPixel::MAX_DEPTH {
+                        return Err(crate::Error::InvalidDepth);
+                    }
+                    depth = Some(d);
+                    max_depth = max_depth.max(d);
+                    rest
+                }
+                None => token,
+            };
+            if token.is_empty() {
+                continue;
+            }
+
+            let depth = depth.ok_or(crate::Error::InvalidMoc)?;
+            let (first, last) = match token.split_once('-') {
+                Some((first, last)) => (parse_index(first)?, parse_index(last)?),
+                None => (parse_index(token)?, parse_index(token)?),
+            };
+            if first > last {
+                return Err(crate::Error::InvalidMoc);
+            }
+            // Validating the last index also validates every index before it. The range is
+            // shifted to the maximum depth as a whole, so its length does not matter.
+            HierarchicalPixel::new(depth, Pixel::from_u64(last))?;
+            let shift = 2 * (HierarchicalPixel::MAX_DEPTH - depth);
+            ranges.push(first << shift..(last + 1) << shift);
+        }
+
+        if depth.is_none() {
+            return Err(crate::Error::InvalidMoc);
+        }
+        Moc::from_ranges_at_depth(max_depth, ranges)
+    }
+}
+
+fn parse_index(s: &str) -> crate::Result<u64> {
+    s.parse().map_err(|_| crate::Error::InvalidMoc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    // Written by `tests/fixtures/moc/generate.py` from the IVOA MOC 1.1 recommendation.
+    const FIXTURE: &str = include_str!("../tests/fixtures/moc/small.txt");
+
+    #[test]
+    fn fixture_round_trips() {
+        let moc: Moc = FIXTURE.parse().unwrap();
+        assert_eq!(moc.depth(), 8);
+        assert_eq!(moc.cells().count(), 6);
+        assert_eq!(moc.to_string(), FIXTURE.trim_end());
+    }
+
+    #[test]
+    fn parse_accepts_moc_1_syntax_and_normalizes() {
+        let moc: Moc = "3/1-5 4/12".parse().unwrap();
+        assert_eq!(moc.to_string(), "3/1-5 4/");
+        let old: Moc = "s3/1-3,4,5\n4/12".parse().unwrap();
+        assert_eq!(old, moc);
+        let empty: Moc = "5/".parse().unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.depth(), 5);
+    }
+
+    #[test]
+    fn parse_rejects_malformed_text() {
+        assert!("".parse::<Moc>().is_err());
+        assert!("12".parse::<Moc>().is_err());
+        assert!("3/5-1".parse::<Moc>().is_err());
+        assert!("3/x".parse::<Moc>().is_err());
+        assert!("1/48".parse::<Moc>().is_err());
+        assert!("30/0".parse::<Moc>().is_err());
+    }
+
+    #[test]
+    fn parse_does_not_expand_ranges_into_pixels() {
+        let moc: Moc = "29/0-1000000000000".parse().unwrap();
+        assert_eq!(moc.depth(), 29);
+        assert_eq!(moc.ranges().len(), 1);
+        assert_eq!(moc.ranges()[0], 0..1_000_000_000_001);
+
+        let all: Moc = "29/0-3458764513820540927".parse().unwrap();
+        assert_eq!(all, Moc::full(29).unwrap());
+    }
+}
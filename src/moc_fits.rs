@@ -0,0 +1,115 @@
+use crate::fits::{self, BinTable, Column, Value};
+use crate::moc::Moc;
+use crate::unique_pixel::UniquePixel;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+impl Moc {
+    /// Serializes the MOC as an IVOA MOC 2.0 FITS file: an empty primary HDU followed by a
+    /// binary table with one `UNIQ` column of 64-bit [`UniquePixel`] values.
+    pub fn to_fits(&self) -> Vec<u8> {
+        let mut uniq: Vec<UniquePixel> = self.cells().map(UniquePixel::from).collect();
+        uniq.sort_unstable();
+
+        let mut table = BinTable::new(alloc::vec![Column::new("UNIQ", 1, 'K')]);
+        for value in uniq {
+            table.push_row(&value.as_u64().to_be_bytes());
+        }
+        let text = |s: &str| Value::Text(s.to_string());
+        table.header.set("PIXTYPE", text("HEALPIX"));
+        table.header.set("ORDERING", text("NUNIQ"));
+        table.header.set("COORDSYS", text("C"));
+        table.header.set("MOCVERS", text("2.0"));
+        table.header.set("MOCDIM", text("SPACE"));
+        table
+            .header
+            .set("MOCORD_S", Value::Integer(self.depth() as i64));
+
+        let mut bytes = Vec::new();
+        fits::write_empty_primary(&mut bytes);
+        table.write(&mut bytes);
+        bytes
+    }
+
+    /// Parses a MOC FITS file, reading the NUNIQ values of the first binary table. Both the
+    /// MOC 2.0 `MOCORD_S` and the MOC 1.x `MOCORDER` depth keywords are understood.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidFits`] if the file has no binary table of NUNIQ values,
+    /// and [`crate::Error::InvalidDepth`] or [`crate::Error::InvalidPixel`] for values beyond
+    /// [`crate::HierarchicalPixel::MAX_DEPTH`].
+    pub fn from_fits(bytes: &[u8]) -> crate::Result<Self> {
+        let table = BinTable::parse_first(bytes)?;
+        if table
+            .header
+            .get_text("ORDERING")
+            .is_some_and(|ordering| ordering != "NUNIQ")
+        {
+            return Err(crate::Error::InvalidFits);
+        }
+        let column = table
+            .column_index("UNIQ")
+            .or_else(|| (table.columns.len() == 1).then_some(0))
+            .ok_or(crate::Error::InvalidFits)?;
+
+        let cells = table
+            .read_integer_column(column)?
+            .into_iter()
+            .map(|uniq| {
+                let uniq = u64::try_from(uniq).map_err(|_| crate::Error::InvalidPixel)?;
+                Ok(UniquePixel::from_u64(uniq)?.into())
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let depth = table
+            .header
+            .get_integer("MOCORD_S")
+            .or_else(|| table.header.get_integer("MOCORDER"))
+            .unwrap_or(0);
+        let depth = u8::try_from(depth).map_err(|_| crate::Error::InvalidDepth)?;
+        Moc::from_cells_at_depth(depth, cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Written by `tests/fixtures/moc/generate.py` from the IVOA MOC 1.1 recommendation.
+    const FIXTURE: &[u8] = include_bytes!("../tests/fixtures/moc/small.fits");
+
+    #[test]
+    fn fixture_round_trips() {
+        let moc = Moc::from_fits(FIXTURE).unwrap();
+        let ascii: Moc = include_str!("../tests/fixtures/moc/small.txt")
+            .parse()
+            .unwrap();
+        assert_eq!(moc, ascii);
+        assert_eq!(Moc::from_fits(&moc.to_fits()).unwrap(), moc);
+    }
+
+    #[test]
+    fn merged_mocs_round_trip() {
+        let a: Moc = "2/3 5/1000-1010".parse().unwrap();
+        let b: Moc = "1/0 29/1234567890123".parse().unwrap();
+        for moc in [a.union(&b), a.intersection(&b), a.difference(&b)] {
+            assert_eq!(Moc::from_fits(&moc.to_fits()).unwrap(), moc);
+        }
+    }
+
+    #[test]
+    fn nuniq_values_encode_depth_and_pixel() {
+        let moc: Moc = "0/11 1/0".parse().unwrap();
+        let table = BinTable::parse_first(&moc.to_fits()).unwrap();
+        assert_eq!(table.read_integer_column(0).unwrap(), [15, 16]);
+        assert_eq!(table.header.get_integer("MOCORD_S"), Some(1));
+    }
+
+    #[test]
+    fn parse_rejects_other_files() {
+        assert!(Moc::from_fits(b"").is_err());
+        let mut primary_only = Vec::new();
+        fits::write_empty_primary(&mut primary_only);
+        assert!(Moc::from_fits(&primary_only).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::hierarchical_pixel::HierarchicalPixel;
+use crate::moc::Moc;
+use crate::pixel::Pixel;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+impl Moc {
+    /// Serializes the MOC in the JSON form used by Aladin and mocpy, mapping each depth to
+    /// its pixel indices, such as `{"3":[1,2,3,9],"4":[40,41],"8":[]}`.
+    ///
+    /// An empty list records the MOC's depth when no pixel is that deep.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{");
+        for (depth, indices) in self.cells_by_depth().iter().enumerate() {
+            if indices.is_empty() && depth != self.depth() as usize {
+                continue;
+            }
+            if json.len() > 1 {
+                json.push(',');
+            }
+            write!(json, "\"{}\":[", depth).unwrap();
+            for (i, index) in indices.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write!(json, "{}", index).unwrap();
+            }
+            json.push(']');
+        }
+        json.push('}');
+        json
+    }
+
+    /// Parses the JSON form written by [`Self::to_json`]. The MOC's depth is the deepest
+    /// depth listed, even with no pixels.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidMoc`] if the text is not a JSON object of integer
+    /// arrays keyed by depth, [`crate::Error::InvalidDepth`] for a depth beyond
+    /// [`HierarchicalPixel::MAX_DEPTH`] and [`crate::Error::InvalidPixel`] for a pixel that does
+    /// not exist at its depth.
+    pub fn from_json(json: &str) -> crate::Result<Self> {
+        let mut parser = Parser {
+            bytes: json.as_bytes(),
+            position: 0,
+        };
+        let mut max_depth = 0;
+        let mut cells = Vec::new();
+
+        parser.expect(b'{')?;
+        if !parser.accept(b'}') {
+            loop {
+                parser.expect(b'"')?;
+                let depth = parser.integer()?;
+                parser.expect(b'"')?;
+                parser.expect(b':')?;
+                if depth > HierarchicalPixel::MAX_DEPTH as u64 {
+                    return Err(crate::Error::InvalidDepth);
+                }
+                let depth = depth as u8;
+                max_depth = max_depth.max(depth);
+
+                parser.expect(b'[')?;
+                if !parser.accept(b']') {
+                    loop {
+                        let index = parser.integer()?;
+                        cells.push(HierarchicalPixel::new(depth, Pixel::from_u64(index))?);
+                        if parser.accept(b']') {
+                            break;
+                        }
+                        parser.expect(b',')?;
+                    }
+                }
+
+                if parser.accept(b'}') {
+                    break;
+                }
+                parser.expect(b',')?;
+            }
+        }
+        parser.skip_whitespace();
+        if parser.position != parser.bytes.len() {
+            return Err(crate::Error::InvalidMoc);
+        }
+        Moc::from_cells_at_depth(max_depth, cells)
+    }
+}
+
+/// A cursor over the small subset of JSON the MOC form needs.
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|b| b.is_ascii_whitespace())
+        {
+            self.position += 1;
+        }
+    }
+
+    /// Consumes `byte` if it is the next non-whitespace byte.
+    fn accept(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        if self.bytes.get(self.position) == Some(&byte) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> crate::Result<()> {
+        if self.accept(byte) {
+            Ok(())
+        } else {
+            Err(crate::Error::InvalidMoc)
+        }
+    }
+
+    fn integer(&mut self) -> crate::Result<u64> {
+        self.skip_whitespace();
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|b| b.is_ascii_digit())
+        {
+            self.position += 1;
+        }
+        core::str::from_utf8(&self.bytes[start..self.position])
+            .unwrap()
+            .parse()
+            .map_err(|_| crate::Error::InvalidMoc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Written by `tests/fixtures/moc/generate.py` from the IVOA MOC 1.1 recommendation.
+    const FIXTURE: &str = include_str!("../tests/fixtures/moc/small.json");
+
+    #[test]
+    fn fixture_round_trips() {
+        let moc = Moc::from_json(FIXTURE).unwrap();
+        assert_eq!(moc.depth(), 8);
+        assert_eq!(moc.to_json(), FIXTURE.trim_end());
+        let ascii: Moc = include_str!("../tests/fixtures/moc/small.txt")
+            .parse()
+            .unwrap();
+        assert_eq!(moc, ascii);
+    }
+
+    #[test]
+    fn parse_tolerates_whitespace_and_rejects_malformed_json() {
+        let moc = Moc::from_json(" { \"3\" : [ 1 , 2 ] ,\n \"4\": [] }\n").unwrap();
+        assert_eq!(moc.to_json(), "{\"3\":[1,2],\"4\":[]}");
+        assert!(Moc::from_json("{}").unwrap().is_empty());
+
+        assert!(Moc::from_json("").is_err());
+        assert!(Moc::from_json("{\"3\":[1,]}").is_err());
+        assert!(Moc::from_json("{\"3\":[1]").is_err());
+        assert!(Moc::from_json("{\"3\":[1]} x").is_err());
+        assert!(Moc::from_json("{\"1\":[48]}").is_err());
+        assert!(Moc::from_json("{\"30\":[]}").is_err());
+    }
+}
@@ -1,121 +1,114 @@
-use crate::numbering_scheme::NumberingScheme;
+use crate::dynamic_healpix::DynamicHealpix;
+use crate::hpd::Hpd;
+use crate::numbering_scheme::{NumberingScheme, assert_face_resolution, checked_hpd};
 use crate::pixel::Pixel;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg(feature = "alloc")]
+use crate::query_disc::query_disc_nested_ranges;
+#[cfg(feature = "alloc")]
+use crate::query_mode::QueryMode;
+use crate::ring::Ring;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Nested;
 
+impl Nested {
+    /// Convert a global nested pixel index to discrete face coordinates (`Hpd`).
+    pub(crate) fn nest2hpd(nside: i64, pix: i64) -> Hpd {
+        let pixels_per_face = nside * nside;
+        let (x, y) = deinterleave((pix % pixels_per_face) as u64);
+        Hpd {
+            x: x as i64,
+            y: y as i64,
+            f: (pix / pixels_per_face) as i32,
+        }
+    }
+
+    /// Convert a discrete face-coordinate (`Hpd`) to a global nested pixel index.
+    pub(crate) fn hpd2nest(nside: i64, h: Hpd) -> i64 {
+        h.f as i64 * nside * nside + interleave(h.x as u32, h.y as u32) as i64
+    }
+}
+
 impl NumberingScheme for Nested {
+    const ORDERING: &'static str = "NESTED";
+
+    fn supports_face_resolution(face_resolution: u32) -> bool {
+        face_resolution.is_power_of_two() && face_resolution <= DynamicHealpix::MAX_FACE_RESOLUTION
+    }
+
     fn angle_to_pixel<N: NumberingScheme>(face_resolution: u32, theta: f64, phi: f64) -> Pixel<N> {
-        let z = theta.cos();
-        let za = z.abs();
-        let nside = face_resolution as f64;
-        let phi = phi.rem_euclid(core::f64::consts::TAU);
-
-        let (face, ix, iy) = if za <= 2.0 / 3.0 {
-            // Equatorial region: 4 faces
-            let tt = phi / (core::f64::consts::PI / 2.0); // [0,4)
-            let face = 4 + tt.floor() as u32;
-            let f = tt - tt.floor();
-
-            // Map to face coordinates
-            let x = nside * f;
-            let y = nside * (0.75 * (1.0 - z) - f);
-
-            // Clamp to valid range
-            let ix = x.floor().max(0.0).min(nside - 1.0) as u32;
-            let iy = y.floor().max(0.0).min(nside - 1.0) as u32;
-
-            (face, ix, iy)
-        } else {
-            // Polar caps: 0-3 = north, 8-11 = south
-            let tmp = nside * (3.0 * (1.0 - za)).sqrt();
-            let f = (phi / (core::f64::consts::PI / 2.0)).fract();
-            let ix = (f * tmp).floor().max(0.0).min(nside - 1.0) as u32;
-            let iy = (tmp - ix as f64 - 1.0).floor().max(0.0).min(nside - 1.0) as u32;
-
-            let face = if z > 0.0 {
-                // North polar cap
-                (f * 4.0).floor() as u32
-            } else {
-                // South polar cap
-                8 + (f * 4.0).floor() as u32
-            };
-
-            (face, ix, iy)
-        };
-
-        Pixel::from_u64(
-            (face as u64) * ((face_resolution * face_resolution) as u64) + interleave(ix, iy),
-        )
+        assert_face_resolution::<Self>(face_resolution);
+        // Both schemes number the same pixels, so they share the face coordinates of a point.
+        let hpd = Ring::ang2hpd(face_resolution, theta, phi);
+        Pixel::from_u64(Self::hpd2nest(face_resolution as i64, hpd) as u64)
     }
 
     fn pixel_to_angle<N: NumberingScheme>(
         face_resolution: u32,
         pixel: Pixel<N>,
     ) -> crate::Result<(f64, f64)> {
-        let total_pixels = (12 * face_resolution * face_resolution) as u64;
-        let pixel_per_face = (face_resolution * face_resolution) as u64;
-        let pixel = pixel.as_u64();
-        if pixel >= total_pixels {
-            return Err(crate::Error::InvalidPixel);
-        }
+        let hpd = checked_hpd::<Self>(face_resolution, pixel.as_u64())?;
+        Ok(Ring::hpd2ang(face_resolution as i64, &hpd))
+    }
+
+    fn pixel_to_hpd(face_resolution: u32, pixel: u64) -> Hpd {
+        Self::nest2hpd(face_resolution as i64, pixel as i64)
+    }
+
+    fn hpd_to_pixel(face_resolution: u32, hpd: Hpd) -> u64 {
+        Self::hpd2nest(face_resolution as i64, hpd) as u64
+    }
 
-        let face = (pixel / pixel_per_face) as u32;
-        let ipf = (pixel % pixel_per_face) as u32;
-        let (ix, iy) = deinterleave(ipf);
-
-        let nside = face_resolution as f64;
-        let x = (ix as f64 + 0.5) / nside;
-        let y = (iy as f64 + 0.5) / nside;
-
-        let (z, phi) = if face < 4 {
-            // North polar cap
-            let z = 1.0 - (x + y).powi(2) / 3.0;
-            let phi = (core::f64::consts::PI / 2.0) * (x - y) / (x + y);
-            (z, phi)
-        } else if face < 8 {
-            // Equatorial region
-            let z = (2.0 / 3.0) * (2.0 - (x + y));
-            let phi = (core::f64::consts::PI / 2.0) * (x - y)
-                + (face as f64 - 4.0) * (core::f64::consts::PI / 2.0);
-            (z, phi)
-        } else {
-            // South polar cap
-            let z = -1.0 + (x + y).powi(2) / 3.0;
-            let phi = (core::f64::consts::PI / 2.0) * (x - y) / (x + y);
-            (z, phi)
-        };
-
-        Ok((z.acos(), phi.rem_euclid(core::f64::consts::TAU)))
+    #[cfg(feature = "alloc")]
+    fn query_disc<N: NumberingScheme>(
+        face_resolution: u32,
+        theta: f64,
+        phi: f64,
+        radius: f64,
+        mode: QueryMode,
+    ) -> Vec<Pixel<N>> {
+        assert_face_resolution::<Self>(face_resolution);
+        query_disc_nested_ranges(face_resolution as i64, theta, phi, radius, mode)
+            .into_iter()
+            .flatten()
+            .map(Pixel::from_u64)
+            .collect()
     }
 }
 
+/// Interleaves the bits of `x` and `y`, with `x` in the even bits.
 #[inline]
 fn interleave(x: u32, y: u32) -> u64 {
     spread_bits(x) | (spread_bits(y) << 1)
 }
 
 #[inline]
-fn deinterleave(v: u32) -> (u32, u32) {
+fn deinterleave(v: u64) -> (u32, u32) {
     (compact_bits(v), compact_bits(v >> 1))
 }
 
+/// Moves bit `i` of `x` to bit `2i`.
 #[inline]
 fn spread_bits(x: u32) -> u64 {
-    let mut r = 0u64;
-    for i in 0..16 {
-        r |= ((x as u64 >> i) & 1) << (2 * i);
-    }
-    r
+    let mut r = x as u64;
+    r = (r | (r << 16)) & 0x0000_ffff_0000_ffff;
+    r = (r | (r << 8)) & 0x00ff_00ff_00ff_00ff;
+    r = (r | (r << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    r = (r | (r << 2)) & 0x3333_3333_3333_3333;
+    (r | (r << 1)) & 0x5555_5555_5555_5555
 }
 
+/// Moves bit `2i` of `x` to bit `i`, dropping the odd bits.
 #[inline]
-fn compact_bits(x: u32) -> u32 {
-    let mut r = 0u32;
-    for i in 0..16 {
-        r |= ((x >> (2 * i)) & 1) << i;
-    }
-    r
+fn compact_bits(x: u64) -> u32 {
+    let mut r = x & 0x5555_5555_5555_5555;
+    r = (r | (r >> 1)) & 0x3333_3333_3333_3333;
+    r = (r | (r >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+    r = (r | (r >> 4)) & 0x00ff_00ff_00ff_00ff;
+    r = (r | (r >> 8)) & 0x0000_ffff_0000_ffff;
+    (r | (r >> 16)) as u32
 }
 
 #[cfg(test)]
@@ -216,4 +209,55 @@ mod tests {
         let bad_pixel = Pixel::<N>::from_u64(total);
         assert!(Nested::pixel_to_angle::<N>(nside, bad_pixel).is_err());
     }
+
+    #[test]
+    fn interleave_uses_all_32_bits() {
+        for (x, y) in [
+            (0, 0),
+            (1, 0),
+            (0, 1),
+            (u32::MAX, 0),
+            (0x1234_5678, 0x9abc_def0),
+        ] {
+            let v = interleave(x, y);
+            assert_eq!(deinterleave(v), (x, y));
+        }
+        assert_eq!(interleave(u32::MAX, 0), 0x5555_5555_5555_5555);
+    }
+
+    #[test]
+    fn deepest_resolution_round_trips() {
+        let nside = 1i64 << 29;
+        for h in [
+            Hpd { x: 0, y: 0, f: 0 },
+            Hpd {
+                x: nside - 1,
+                y: 12345,
+                f: 5,
+            },
+            Hpd {
+                x: nside - 1,
+                y: nside - 1,
+                f: 11,
+            },
+        ] {
+            let pixel = Nested::hpd2nest(nside, h);
+            assert_eq!(Nested::nest2hpd(nside, pixel), h);
+        }
+        assert_eq!(
+            Nested::hpd2nest(
+                nside,
+                Hpd {
+                    x: nside - 1,
+                    y: nside - 1,
+                    f: 11
+                }
+            ),
+            12 * nside * nside - 1
+        );
+
+        let total = 12 * (nside as u64) * (nside as u64);
+        let bad_pixel = Pixel::<N>::from_u64(total);
+        assert!(Nested::pixel_to_angle::<N>(nside as u32, bad_pixel).is_err());
+    }
 }
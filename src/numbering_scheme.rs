@@ -1,4 +1,12 @@
+use crate::dynamic_healpix::DynamicHealpix;
+use crate::hpd::Hpd;
 use crate::pixel::Pixel;
+#[cfg(feature = "alloc")]
+use crate::query_mode::QueryMode;
+use crate::ring::Ring;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::f64::consts::PI;
 
 /// A trait representing a numbering scheme for mapping between spherical angles
 /// (given as theta and phi) and pixel indices or coordinates on a face
@@ -11,9 +19,181 @@ use crate::pixel::Pixel;
 /// The trait is generic over the implementing type `N`, which should also implement
 /// the `NumberingScheme` trait.
 pub trait NumberingScheme: PartialEq {
+    /// The value of the HEALPix FITS `ORDERING` keyword for this scheme.
+    const ORDERING: &'static str;
+
+    /// Returns `true` if this scheme can number the pixels of a grid with `face_resolution`
+    /// pixels along each side of a base face.
+    ///
+    /// [`crate::Ring`] works for any resolution from 1 to
+    /// [`crate::DynamicHealpix::MAX_FACE_RESOLUTION`], while [`crate::Nested`] also needs a
+    /// power of two.
+    fn supports_face_resolution(face_resolution: u32) -> bool {
+        (1..=DynamicHealpix::MAX_FACE_RESOLUTION).contains(&face_resolution)
+    }
+
     fn angle_to_pixel<N: NumberingScheme>(pixels_per_face: u32, theta: f64, phi: f64) -> Pixel<N>;
     fn pixel_to_angle<N: NumberingScheme>(
         pixels_per_face: u32,
         pixel: Pixel<N>,
     ) -> crate::Result<(f64, f64)>;
+
+    /// Like [`Self::angle_to_pixel`], but rejects inputs that name no point on the sphere
+    /// instead of hashing them to an arbitrary pixel.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidAngle`] unless `theta` lies within `[0, π]` and `phi` is
+    /// finite, [`crate::Error::ResolutionTooLarge`] if `face_resolution` exceeds
+    /// [`crate::DynamicHealpix::MAX_FACE_RESOLUTION`] and [`crate::Error::InvalidFaceResolution`]
+    /// if this scheme does not support it otherwise.
+    fn try_angle_to_pixel<N: NumberingScheme>(
+        face_resolution: u32,
+        theta: f64,
+        phi: f64,
+    ) -> crate::Result<Pixel<N>> {
+        check_face_resolution::<Self>(face_resolution)?;
+        check_angle(theta, phi)?;
+        Ok(Self::angle_to_pixel(face_resolution, theta, phi))
+    }
+
+    /// Converts a pixel index in this scheme to discrete face coordinates.
+    fn pixel_to_hpd(face_resolution: u32, pixel: u64) -> Hpd;
+
+    /// Converts discrete face coordinates to a pixel index in this scheme.
+    fn hpd_to_pixel(face_resolution: u32, hpd: Hpd) -> u64;
+
+    /// Returns the neighbours of `pixel` in the order S, SW, W, NW, N, NE, E, SE.
+    ///
+    /// Missing neighbours at the 8 face corners where only 7 exist are `None`.
+    fn neighbours<N: NumberingScheme>(
+        face_resolution: u32,
+        pixel: Pixel<N>,
+    ) -> crate::Result<[Option<Pixel<N>>; 8]> {
+        let hpd = checked_hpd::<Self>(face_resolution, pixel.as_u64())?;
+        Ok(hpd
+            .neighbours(face_resolution as i64)
+            .map(|n| n.map(|h| Pixel::from_u64(Self::hpd_to_pixel(face_resolution, h)))))
+    }
+
+    /// Returns the `(theta, phi)` of the corners of `pixel` in the order N, W, S, E.
+    fn pixel_corners<N: NumberingScheme>(
+        face_resolution: u32,
+        pixel: Pixel<N>,
+    ) -> crate::Result<[(f64, f64); 4]> {
+        let hpd = checked_hpd::<Self>(face_resolution, pixel.as_u64())?;
+        Ok(core::array::from_fn(|corner| {
+            edge_point(face_resolution, &hpd, corner, 0, 1)
+        }))
+    }
+
+    /// Returns `4 * step` points `(theta, phi)` along the boundary of `pixel`, going N, W, S, E.
+    ///
+    /// Each edge contributes its starting corner followed by `step - 1` points spaced evenly
+    /// in face coordinates. Pixel edges are not great circles, so this traces their true
+    /// shape. A `step` of 0 is treated as 1, which returns the corners.
+    #[cfg(feature = "alloc")]
+    fn pixel_boundary<N: NumberingScheme>(
+        face_resolution: u32,
+        pixel: Pixel<N>,
+        step: usize,
+    ) -> crate::Result<Vec<(f64, f64)>> {
+        let hpd = checked_hpd::<Self>(face_resolution, pixel.as_u64())?;
+        let step = step.max(1);
+        Ok((0..4)
+            .flat_map(|edge| (0..step).map(move |i| (edge, i)))
+            .map(|(edge, i)| edge_point(face_resolution, &hpd, edge, i, step))
+            .collect())
+    }
+
+    /// Returns the pixels selected by a disc of angular `radius` (in radians) around
+    /// `(theta, phi)`, sorted by pixel index.
+    #[cfg(feature = "alloc")]
+    fn query_disc<N: NumberingScheme>(
+        face_resolution: u32,
+        theta: f64,
+        phi: f64,
+        radius: f64,
+        mode: QueryMode,
+    ) -> Vec<Pixel<N>>;
+}
+
+/// Checks that scheme `S` can number a grid of `face_resolution`.
+///
+/// # Errors
+/// Returns [`crate::Error::ResolutionTooLarge`] if `face_resolution` exceeds
+/// [`DynamicHealpix::MAX_FACE_RESOLUTION`], and [`crate::Error::InvalidFaceResolution`] if `S`
+/// does not support it otherwise.
+pub(crate) fn check_face_resolution<S: NumberingScheme + ?Sized>(
+    face_resolution: u32,
+) -> crate::Result<()> {
+    if face_resolution > DynamicHealpix::MAX_FACE_RESOLUTION {
+        Err(crate::Error::ResolutionTooLarge)
+    } else if !S::supports_face_resolution(face_resolution) {
+        Err(crate::Error::InvalidFaceResolution)
+    } else {
+        Ok(())
+    }
+}
+
+/// Panics unless scheme `S` can number a grid of `face_resolution`, with the message of the
+/// error [`check_face_resolution`] returns. For operations that cannot report an error.
+#[track_caller]
+pub(crate) fn assert_face_resolution<S: NumberingScheme + ?Sized>(face_resolution: u32) {
+    if let Err(error) = check_face_resolution::<S>(face_resolution) {
+        panic!("{error}");
+    }
+}
+
+/// Checks that `theta` is a colatitude within `[0, π]` and `phi` a finite azimuth. Any
+/// finite `phi` wraps around to `[0, 2π)`.
+pub(crate) fn check_angle(theta: f64, phi: f64) -> crate::Result<()> {
+    if (0.0..=PI).contains(&theta) && phi.is_finite() {
+        Ok(())
+    } else {
+        Err(crate::Error::InvalidAngle)
+    }
+}
+
+/// Converts `pixel` to face coordinates, checking that it exists at this resolution.
+pub(crate) fn checked_hpd<S: NumberingScheme + ?Sized>(
+    face_resolution: u32,
+    pixel: u64,
+) -> crate::Result<Hpd> {
+    check_face_resolution::<S>(face_resolution)?;
+    let total_pixels = 12 * (face_resolution as u64) * (face_resolution as u64);
+    if pixel >= total_pixels {
+        return Err(crate::Error::InvalidPixel);
+    }
+    Ok(S::pixel_to_hpd(face_resolution, pixel))
+}
+
+/// Returns the `(z, phi, sin(theta))` of point `i` of `step` along edge `edge` of a pixel, where
+/// the edges run N→W, W→S, S→E and E→N, as [`Ring::face_to_loc_sth`] gives them.
+///
+/// The face coordinates are computed as in `boundaries` of the HEALPix C++ library, from the
+/// pixel centre, so the points are bit-for-bit the ones it reports.
+pub(crate) fn edge_loc(
+    face_resolution: u32,
+    hpd: &Hpd,
+    edge: usize,
+    i: usize,
+    step: usize,
+) -> (f64, f64, Option<f64>) {
+    let nside = face_resolution as f64;
+    let (dc, d) = (0.5 / nside, 1.0 / (step as f64 * nside));
+    let (xc, yc) = ((hpd.x as f64 + 0.5) / nside, (hpd.y as f64 + 0.5) / nside);
+    let i = i as f64;
+    let (x, y) = match edge {
+        0 => (xc + dc - i * d, yc + dc),
+        1 => (xc - dc, yc + dc - i * d),
+        2 => (xc - dc + i * d, yc - dc),
+        _ => (xc + dc, yc - dc + i * d),
+    };
+    Ring::face_to_loc_sth(hpd.f, x, y)
+}
+
+/// Returns the `(theta, phi)` of the point [`edge_loc`] gives.
+fn edge_point(face_resolution: u32, hpd: &Hpd, edge: usize, i: usize, step: usize) -> (f64, f64) {
+    let (z, phi, sin_theta) = edge_loc(face_resolution, hpd, edge, i, step);
+    (Ring::loc_to_theta(z, sin_theta), phi)
 }
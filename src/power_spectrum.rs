@@ -0,0 +1,654 @@
+use crate::healpix_map::is_unseen;
+use crate::math;
+use crate::numbering_scheme::NumberingScheme;
+use crate::ring::Ring;
+use crate::sht::map2alm;
+use crate::spectrum_options::SpectrumOptions;
+use crate::vec3::Vec3;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Gauss-Legendre nodes per side when integrating over a pixel for its window function.
+const QUADRATURE_ORDER: usize = 8;
+
+/// Pixels sampled on each half of a quarter of a long ring for a pixel window.
+const RING_SAMPLES: usize = 12;
+
+/// Polar rings whose pixel windows are summed ring by ring; the sum over the rings between them
+/// and ring `nside` is interpolated through [`CAP_SAMPLES`] of those rings.
+const POLAR_RINGS: i64 = 32;
+
+/// Rings sampled between [`POLAR_RINGS`] and ring `nside` for a pixel window.
+const CAP_SAMPLES: usize = 16;
+
+/// Estimates the cross power spectrum of two Ring-ordered maps, or the power spectrum of
+/// `map1` alone. See [`crate::Healpix::anafast`].
+pub(crate) fn anafast(
+    nside: u32,
+    map1: &[f64],
+    map2: Option<&[f64]>,
+    options: &SpectrumOptions,
+) -> crate::Result<Vec<f64>> {
+    let total_pixels = 12 * nside as usize * nside as usize;
+    let sizes = [Some(map1), map2, options.mask];
+    if sizes.iter().flatten().any(|m| m.len() != total_pixels) {
+        return Err(crate::Error::InvalidMapSize);
+    }
+
+    // Check the mask before the transforms, which are the expensive part.
+    let f_sky = match options.mask {
+        Some(mask) => {
+            let f_sky = mask.iter().map(|w| w * w).sum::<f64>() / total_pixels as f64;
+            if f_sky <= 0.0 {
+                return Err(crate::Error::EmptyMask);
+            }
+            f_sky
+        }
+        None => 1.0,
+    };
+
+    let lmax = options.lmax.unwrap_or(3 * nside as usize - 1);
+    let prepare = |map: &[f64]| -> Vec<f64> {
+        let weight = |pixel: usize| options.mask.map_or(1.0, |mask| mask[pixel]);
+        map.iter()
+            .enumerate()
+            .map(|(pixel, v)| {
+                if v.is_nan() || is_unseen(*v) {
+                    0.0
+                } else {
+                    v * weight(pixel)
+                }
+            })
+            .collect()
+    };
+    let nside_i = nside as i64;
+    let alm1 = map2alm(nside_i, &prepare(map1), lmax, options.iterations);
+    let mut spectrum = match map2 {
+        Some(map2) => {
+            let alm2 = map2alm(nside_i, &prepare(map2), lmax, options.iterations);
+            alm1.cross_spectrum(&alm2)
+        }
+        None => alm1.spectrum(),
+    };
+
+    if options.mask.is_some() {
+        spectrum.iter_mut().for_each(|c| *c /= f_sky);
+    }
+    if options.pixel_window {
+        for (c, w) in spectrum.iter_mut().zip(pixel_window(nside, lmax)) {
+            *c /= w * w;
+        }
+    }
+    Ok(spectrum)
+}
+
+/// Returns the pixel window `w_l` for `l` up to `lmax`. See [`crate::Healpix::pixel_window`].
+///
+/// `w_l²` is the mean over pixels of `∫∫ P_l(a·b) da db / Ω²`, with both points running over
+/// the pixel of area `Ω`. The HEALPix projection is equal-area, so the integrals are taken over
+/// the face coordinates of the pixel. The projection has a kink where the polar caps meet the
+/// equatorial zone, which runs along a diagonal of the pixels on it, so each pixel is split into
+/// the two triangles either side of that diagonal and each is integrated with a Gauss-Legendre
+/// product rule. The mean is taken over the few hundred pixels [`window_samples`] picks at any
+/// resolution, so the cost grows only with `lmax`.
+pub(crate) fn pixel_window(nside: u32, lmax: usize) -> Vec<f64> {
+    integrate_pixel_window(nside, lmax, QUADRATURE_ORDER)
+}
+
+/// Computes [`pixel_window`] with `order` quadrature nodes along each side of a pixel.
+fn integrate_pixel_window(nside: u32, lmax: usize, order: usize) -> Vec<f64> {
+    let rule = pixel_rule(order);
+    let mut sums = vec![0.0; lmax + 1];
+    for (pixel, weight) in window_samples(nside) {
+        add_pixel_window(nside, pixel, &rule, weight, &mut sums);
+    }
+
+    let total_pixels = 12.0 * nside as f64 * nside as f64;
+    sums.into_iter()
+        .map(|sum| math::sqrt(sum / total_pixels))
+        .collect()
+}
+
+/// Returns Ring pixels and weights such that `Σ weight w(pixel)` is the sum over every pixel of
+/// `w(pixel) = ∫∫ P_l(a·b) da db / Ω²`, evaluating it on a number of pixels that does not grow
+/// with `nside`.
+///
+/// Southern rings mirror northern ones, and the quarters of a ring are rotated copies of each
+/// other. Below the polar caps, where the projection is linear in azimuth, all the pixels of a
+/// ring are rotated copies too. Everywhere else `w` varies smoothly with the position of the
+/// pixel, except near the poles and on ring `nside`, which still reaches into the caps, so the
+/// remaining sums are interpolated with [`smooth_sum_rule`].
+fn window_samples(nside: u32) -> Vec<(u64, f64)> {
+    let nside = nside as i64;
+    let mut samples = Vec::new();
+    let mut add_polar_ring = |ring: i64, weight: f64| {
+        let first_pixel = Ring::ring_info(nside, ring).first_pixel;
+        for (offset, ring_weight) in smooth_sum_rule(ring as usize, RING_SAMPLES, true) {
+            let pixel = (first_pixel + offset as i64) as u64;
+            samples.push((pixel, 8.0 * weight * ring_weight));
+        }
+    };
+
+    let polar_rings = POLAR_RINGS.min(nside - 1);
+    for ring in 1..=polar_rings {
+        add_polar_ring(ring, 1.0);
+    }
+    if nside - 1 > polar_rings {
+        let count = (nside - 1 - polar_rings) as usize;
+        for (offset, weight) in smooth_sum_rule(count, CAP_SAMPLES, false) {
+            add_polar_ring(polar_rings + 1 + offset as i64, weight);
+        }
+    }
+    add_polar_ring(nside, 1.0);
+
+    // The 2 nside - 1 equatorial rings, symmetric about the equator.
+    for (offset, weight) in smooth_sum_rule(2 * nside as usize - 1, RING_SAMPLES, true) {
+        let info = Ring::ring_info(nside, nside + 1 + offset as i64);
+        samples.push((info.first_pixel as u64, weight * info.pixel_count as f64));
+    }
+    samples
+}
+
+/// Returns `(j, weight)` pairs such that `Σ weight f(j)` is the sum of `f(j)` for `j` in
+/// `0..count`, for a smooth `f`, and one symmetric under `j → count - 1 - j` if `symmetric`.
+///
+/// Short sums are taken term by term, pairing mirrored terms. Longer ones interpolate `f`, or
+/// its even part about the middle, by a polynomial through `samples` points near its Chebyshev
+/// nodes, and sum that polynomial instead.
+fn smooth_sum_rule(count: usize, samples: usize, symmetric: bool) -> Vec<(usize, f64)> {
+    let centre = (count - 1) as f64 / 2.0;
+    let (terms, variable): (usize, &dyn Fn(f64) -> f64) = if symmetric {
+        (count.div_ceil(2), &|x| (centre - x) * (centre - x))
+    } else {
+        (count, &|x| x)
+    };
+    if terms <= 4 * samples {
+        let mirrored = |j: usize| {
+            if symmetric && 2 * j + 1 != count {
+                2.0
+            } else {
+                1.0
+            }
+        };
+        return (0..terms).map(|j| (j, mirrored(j))).collect();
+    }
+
+    // Nodes on the half or the whole of [-1, 1], which are at least one term apart here.
+    let nodes_on = if symmetric { 4 * samples } else { 2 * samples };
+    let nodes: Vec<usize> = (0..samples)
+        .map(|k| {
+            let angle = core::f64::consts::PI * (2 * k + 1) as f64 / nodes_on as f64;
+            (centre - centre * math::cos(angle) + 0.5) as usize
+        })
+        .collect();
+    nodes
+        .iter()
+        .map(|&node| {
+            let basis = |x: f64| {
+                nodes
+                    .iter()
+                    .filter(|&&other| other != node)
+                    .map(|&other| {
+                        (variable(x) - variable(other as f64))
+                            / (variable(node as f64) - variable(other as f64))
+                    })
+                    .product::<f64>()
+            };
+            let sum = discrete_gauss(count, samples)
+                .iter()
+                .map(|(x, weight)| weight * basis(*x))
+                .sum();
+            (node, sum)
+        })
+        .collect()
+}
+
+/// Returns the `(node, weight)` pairs of the `order`-point Gauss rule for sums over the
+/// integers in `0..count`, which is exact for polynomials of degree below `2 order`.
+///
+/// The nodes are the roots of the discrete Chebyshev polynomial of degree `order`, found as in
+/// [`gauss_legendre`] on `[-1, 1]` from the three-term recurrence of those polynomials.
+fn discrete_gauss(count: usize, order: usize) -> Vec<(f64, f64)> {
+    let n = count as f64;
+    let half = (n - 1.0) / 2.0;
+    // Recurrence coefficients of the monic polynomials, for the nodes scaled to [-1, 1].
+    let beta = |m: usize| {
+        let m = m as f64;
+        m * m * (n * n - m * m) / ((4.0 * m * m - 1.0) * 4.0 * half * half)
+    };
+    let evaluate = |y: f64| {
+        let (mut previous, mut current) = (0.0, 1.0);
+        let (mut previous_derivative, mut derivative) = (0.0, 0.0);
+        let mut inverse_weight = 1.0 / n;
+        let mut norm = n;
+        for m in 0..order {
+            let next = y * current - beta(m) * previous;
+            let next_derivative = current + y * derivative - beta(m) * previous_derivative;
+            (previous, current) = (current, next);
+            (previous_derivative, derivative) = (derivative, next_derivative);
+            if m + 1 < order {
+                norm *= beta(m + 1);
+                inverse_weight += current * current / norm;
+            }
+        }
+        (current, derivative, inverse_weight)
+    };
+
+    (0..order)
+        .map(|i| {
+            let mut y = math::cos(core::f64::consts::PI * (i as f64 + 0.75) / (order as f64 + 0.5));
+            for _ in 0..100 {
+                let (value, derivative, _) = evaluate(y);
+                let step = value / derivative;
+                y -= step;
+                if step.abs() <= 1e-16 {
+                    break;
+                }
+            }
+            (half + half * y, 1.0 / evaluate(y).2)
+        })
+        .collect()
+}
+
+/// Adds `weight * ∫∫ P_l(a·b) da db / Ω²` over the Ring `pixel` to `sums[l]`, integrating with
+/// the quadrature `rule` given as `(x, y, weight)` points on the unit square of the pixel.
+fn add_pixel_window(
+    nside: u32,
+    pixel: u64,
+    rule: &[(f64, f64, f64)],
+    weight: f64,
+    sums: &mut [f64],
+) {
+    let hpd = Ring::pixel_to_hpd(nside, pixel);
+    let points: Vec<_> = rule
+        .iter()
+        .map(|(u, v, weight)| {
+            let x = (hpd.x as f64 + u) / nside as f64;
+            let y = (hpd.y as f64 + v) / nside as f64;
+            let (z, phi) = Ring::face_to_loc(hpd.f, x, y);
+            (Vec3::from_z_phi(z, phi), *weight)
+        })
+        .collect();
+
+    // Run the Legendre recurrence for every pair of points at once, one `l` at a time.
+    let mut cosines = Vec::with_capacity(points.len() * (points.len() + 1) / 2);
+    let mut weights = Vec::with_capacity(cosines.capacity());
+    for (a, (first, weight_a)) in points.iter().enumerate() {
+        for (b, (second, weight_b)) in points.iter().enumerate().skip(a) {
+            let pairs = if a == b { 1.0 } else { 2.0 };
+            cosines.push(first.dot(second).clamp(-1.0, 1.0));
+            weights.push(weight * pairs * weight_a * weight_b);
+        }
+    }
+    let mut previous = vec![1.0; cosines.len()];
+    let mut current = cosines.clone();
+    for (l, sum) in sums.iter_mut().enumerate() {
+        if l >= 2 {
+            let (a, b) = ((2 * l - 1) as f64 / l as f64, (l - 1) as f64 / l as f64);
+            for ((p, q), x) in previous.iter_mut().zip(&current).zip(&cosines) {
+                *p = a * x * q - b * *p;
+            }
+            core::mem::swap(&mut previous, &mut current);
+        }
+        let p = if l == 0 { &previous } else { &current };
+        *sum += dot(&weights, p);
+    }
+}
+
+/// Returns the dot product of `a` and `b`, with independent partial sums so that it pipelines.
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    let mut partial = [0.0; 4];
+    let chunks = a.chunks_exact(4).zip(b.chunks_exact(4));
+    for (a, b) in chunks {
+        for k in 0..4 {
+            partial[k] += a[k] * b[k];
+        }
+    }
+    let tail = a.len() - a.len() % 4;
+    let rest: f64 = a[tail..].iter().zip(&b[tail..]).map(|(a, b)| a * b).sum();
+    partial.iter().sum::<f64>() + rest
+}
+
+/// Returns `(x, y, weight)` quadrature points on the unit square: an `order × order`
+/// Gauss-Legendre rule on each of the triangles below and above the diagonal `x + y = 1`.
+///
+/// Each triangle is mapped from a square by collapsing one side onto the corner `(0, 0)` or
+/// `(1, 1)`, where a pixel may touch a pole. The mapping then runs in polar coordinates
+/// around the pole, which removes the singularity of the projection there.
+fn pixel_rule(order: usize) -> Vec<(f64, f64, f64)> {
+    let line = gauss_legendre(order);
+    let mut rule = Vec::with_capacity(2 * order * order);
+    for (s, weight_s) in &line {
+        for (t, weight_t) in &line {
+            let (x, y, weight) = (s * (1.0 - t), s * t, weight_s * weight_t * s);
+            rule.push((x, y, weight));
+            rule.push((1.0 - x, 1.0 - y, weight));
+        }
+    }
+    rule
+}
+
+/// Returns the `(node, weight)` pairs of the `order`-point Gauss-Legendre rule on `[0, 1]`.
+fn gauss_legendre(order: usize) -> Vec<(f64, f64)> {
+    let mut p = vec![0.0; order + 1];
+    let mut rule = Vec::with_capacity(order);
+    for i in 0..order {
+        // Newton's method on P_order, from the usual estimate of its i-th root.
+        let mut x = math::cos(core::f64::consts::PI * (i as f64 + 0.75) / (order as f64 + 0.5));
+        let mut derivative = 1.0;
+        for _ in 0..100 {
+            legendre_polynomials(x, &mut p);
+            derivative = order as f64 * (x * p[order] - p[order - 1]) / (x * x - 1.0);
+            let step = p[order] / derivative;
+            x -= step;
+            if step.abs() <= 1e-16 {
+                break;
+            }
+        }
+        rule.push((
+            (1.0 - x) / 2.0,
+            1.0 / ((1.0 - x * x) * derivative * derivative),
+        ));
+    }
+    rule
+}
+
+/// Fills `p` with the Legendre polynomials `P_0(x)` to `P_lmax(x)`.
+fn legendre_polynomials(x: f64, p: &mut [f64]) {
+    for l in 0..p.len() {
+        p[l] = match l {
+            0 => 1.0,
+            1 => x,
+            _ => ((2 * l - 1) as f64 * x * p[l - 1] - (l - 1) as f64 * p[l - 2]) / l as f64,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alm::Alm;
+    use crate::complex::Complex;
+    use crate::dynamic_healpix::DynamicHealpix;
+    use crate::healpix::Healpix;
+    use crate::healpix_map::UNSEEN;
+
+    /// Returns a band-limited map with a known spectrum, and that spectrum.
+    fn band_limited(nside: u32, lmax: usize, seed: u64) -> (Vec<f64>, Vec<f64>) {
+        let mut state = seed;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+        };
+        let mut alm = Alm::new(lmax);
+        for m in 0..=lmax {
+            for l in m.max(1)..=lmax {
+                let im = if m == 0 { 0.0 } else { next() };
+                alm[(l, m)] = Complex::new(next(), im) * (1.0 / l as f64);
+            }
+        }
+        let healpix = DynamicHealpix::new(nside).unwrap();
+        (alm.alm2map(&healpix).into_vec(), alm.spectrum())
+    }
+
+    fn options(lmax: usize) -> SpectrumOptions<'static> {
+        SpectrumOptions {
+            lmax: Some(lmax),
+            ..SpectrumOptions::default()
+        }
+    }
+
+    #[test]
+    fn recovers_the_spectrum_of_band_limited_maps() {
+        let healpix = DynamicHealpix::new(16).unwrap();
+        let (map, expected) = band_limited(16, 8, 1);
+        let spectrum = healpix.anafast(&map, &options(8)).unwrap();
+        for (c, e) in spectrum.iter().zip(&expected) {
+            assert!((c - e).abs() <= 1e-6 * expected[1], "{c} != {e}");
+        }
+
+        let full = healpix.anafast(&map, &SpectrumOptions::default()).unwrap();
+        assert_eq!(full.len(), 48);
+    }
+
+    #[test]
+    fn cross_spectra_are_symmetric() {
+        let healpix = DynamicHealpix::new(8).unwrap();
+        let (a, spectrum) = band_limited(8, 6, 2);
+        let (b, _) = band_limited(8, 6, 3);
+        let ab = healpix.anafast_cross(&a, &b, &options(6)).unwrap();
+        let ba = healpix.anafast_cross(&b, &a, &options(6)).unwrap();
+        assert_eq!(ab, ba);
+
+        let aa = healpix.anafast_cross(&a, &a, &options(6)).unwrap();
+        assert_eq!(aa, healpix.anafast(&a, &options(6)).unwrap());
+        for (c, e) in aa.iter().zip(&spectrum) {
+            assert!((c - e).abs() <= 1e-6 * spectrum[1]);
+        }
+    }
+
+    #[test]
+    fn masked_spectra_are_corrected_for_sky_fraction() {
+        let nside = 16;
+        let healpix = DynamicHealpix::new(nside).unwrap();
+        let (map, expected) = band_limited(nside, 4, 4);
+        // Cut a band around the equator, leaving 80% of the sky.
+        let mask: Vec<f64> = healpix
+            .iter_pixels::<Ring>()
+            .map(|pixel| {
+                let (theta, _) = healpix.pixel_to_angle(pixel).unwrap();
+                (math::cos(theta).abs() > 0.2) as u8 as f64
+            })
+            .collect();
+        let options = SpectrumOptions {
+            mask: Some(&mask),
+            ..SpectrumOptions::default()
+        };
+
+        // Mode coupling moves power between multipoles, but the corrected total is kept.
+        let spectrum = healpix.anafast(&map, &options).unwrap();
+        let power = |s: &[f64]| {
+            s.iter()
+                .enumerate()
+                .map(|(l, c)| (2 * l + 1) as f64 * c)
+                .sum::<f64>()
+        };
+        let ratio = power(&spectrum) / power(&expected);
+        assert!((ratio - 1.0).abs() < 0.05, "{ratio}");
+
+        // UNSEEN pixels count as 0, like masked ones.
+        let mut cut = map.clone();
+        let mut unmasked = mask.clone();
+        for (pixel, weight) in unmasked.iter_mut().enumerate().filter(|(p, _)| p % 7 == 0) {
+            cut[pixel] = UNSEEN;
+            *weight = 0.0;
+        }
+        let zeroed: Vec<f64> = map
+            .iter()
+            .zip(&cut)
+            .map(|(m, c)| if *c == UNSEEN { 0.0 } else { *m })
+            .collect();
+        assert_eq!(
+            healpix.anafast(&cut, &options).unwrap(),
+            healpix.anafast(&zeroed, &options).unwrap()
+        );
+
+        let empty = vec![0.0; mask.len()];
+        let options = SpectrumOptions {
+            mask: Some(&empty),
+            ..options
+        };
+        assert!(healpix.anafast(&map, &options).is_err());
+        assert!(
+            healpix
+                .anafast(&map[1..], &SpectrumOptions::default())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn pixel_window_resembles_a_top_hat() {
+        let nside = 8;
+        let healpix = DynamicHealpix::new(nside).unwrap();
+        let window = healpix.pixel_window(3 * nside as usize - 1);
+        assert!((window[0] - 1.0).abs() < 1e-12);
+        assert!(window.windows(2).all(|w| w[1] < w[0]));
+
+        // A disc of the same area has a window close to that of a pixel at low l.
+        let cos_radius = 1.0 - 2.0 / healpix.total_pixels() as f64;
+        let mut p = vec![0.0; window.len() + 1];
+        legendre_polynomials(cos_radius, &mut p);
+        for l in 1..nside as usize {
+            let disc = (p[l - 1] - p[l + 1]) / ((2 * l + 1) as f64 * (1.0 - cos_radius));
+            assert!((window[l] - disc).abs() < 0.01, "{l}: {} {disc}", window[l]);
+        }
+    }
+
+    #[test]
+    fn gauss_legendre_integrates_polynomials_exactly() {
+        for order in [1, 4, 8] {
+            let rule = gauss_legendre(order);
+            for k in 0..2 * order as i32 {
+                let integral: f64 = rule.iter().map(|(x, w)| w * x.powi(k)).sum();
+                assert!(
+                    (integral - 1.0 / (k + 1) as f64).abs() < 1e-14,
+                    "{order} {k}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pixel_window_samples_every_pixel_shape() {
+        // Summing over every pixel must give what the symmetries of the rings give.
+        let nside = 4;
+        let lmax = 3 * nside as usize - 1;
+        let rule = pixel_rule(3);
+        let mut sums = vec![0.0; lmax + 1];
+        for pixel in 0..12 * nside as u64 * nside as u64 {
+            add_pixel_window(nside, pixel, &rule, 1.0, &mut sums);
+        }
+        let total_pixels = (12 * nside * nside) as f64;
+        let window = integrate_pixel_window(nside, lmax, 3);
+        for (w, sum) in window.iter().zip(sums) {
+            assert!((w - math::sqrt(sum / total_pixels)).abs() < 1e-13);
+        }
+    }
+
+    #[test]
+    fn pixel_window_interpolation_matches_ring_sums() {
+        // At this resolution the sums along long rings, over the rings of the caps and over
+        // the equatorial rings are all interpolated. Without interpolation, each quarter of a
+        // northern ring stands for eight and one pixel for each equatorial ring.
+        let nside = 128;
+        let lmax = 3 * nside as usize - 1;
+        let rule = pixel_rule(1);
+        let mut exact = vec![0.0; lmax + 1];
+        for ring in 1..=2 * nside as i64 {
+            let info = Ring::ring_info(nside as i64, ring);
+            if ring <= nside as i64 {
+                for pixel in info.first_pixel..info.first_pixel + ring {
+                    add_pixel_window(nside, pixel as u64, &rule, 8.0, &mut exact);
+                }
+            } else {
+                let mirrored = if ring == 2 * nside as i64 { 1.0 } else { 2.0 };
+                let weight = mirrored * info.pixel_count as f64;
+                add_pixel_window(nside, info.first_pixel as u64, &rule, weight, &mut exact);
+            }
+        }
+        let mut sums = vec![0.0; lmax + 1];
+        for (pixel, weight) in window_samples(nside) {
+            add_pixel_window(nside, pixel, &rule, weight, &mut sums);
+        }
+        for (l, (e, s)) in exact.iter().zip(sums).enumerate() {
+            assert!((e - s).abs() < 1e-12 * exact[0], "{l}: {e} {s}");
+        }
+    }
+
+    #[test]
+    fn pixel_window_cost_does_not_grow_with_resolution() {
+        // The cost of a pixel window is linear in the number of pixels sampled and in `lmax`.
+        for nside in [256, 272, 8192, 1 << 20, 1 << 29] {
+            let samples = window_samples(nside);
+            assert!(samples.len() <= 600, "{nside}: {}", samples.len());
+            let total_pixels = 12.0 * nside as f64 * nside as f64;
+            let weights: f64 = samples.iter().map(|(_, weight)| weight).sum();
+            assert!((weights / total_pixels - 1.0).abs() < 1e-12, "{nside}");
+        }
+    }
+
+    #[test]
+    fn discrete_gauss_sums_polynomials_exactly() {
+        let sum = |count: usize, order: usize, k: i32| -> f64 {
+            discrete_gauss(count, order)
+                .iter()
+                .map(|(x, weight)| weight * (x / count as f64).powi(k))
+                .sum()
+        };
+        for count in [50, 1000] {
+            for order in [4, 12] {
+                for k in 0..2 * order as i32 {
+                    let exact: f64 = (0..count).map(|j| (j as f64 / count as f64).powi(k)).sum();
+                    assert!((sum(count, order, k) / exact - 1.0).abs() < 1e-12);
+                }
+            }
+        }
+
+        // Sums too long to take term by term, against Faulhaber's formulas.
+        let n = (1u64 << 40) as f64;
+        let exact = [n, (n - 1.0) / 2.0, (n - 1.0) * (2.0 * n - 1.0) / (6.0 * n)];
+        for (k, exact) in exact.into_iter().enumerate() {
+            assert!((sum(1 << 40, 12, k as i32) / exact - 1.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn smooth_sums_are_exact_for_polynomials() {
+        for count in [1, 2, 7, 64, 65, 129, 1000] {
+            for (symmetric, samples) in [(false, CAP_SAMPLES), (true, RING_SAMPLES)] {
+                let f = |j: usize| {
+                    let x = if symmetric {
+                        let d = j as f64 - (count - 1) as f64 / 2.0;
+                        d * d
+                    } else {
+                        j as f64
+                    };
+                    1.0 + x / count as f64 + (x / count as f64).powi(3)
+                };
+                let exact: f64 = (0..count).map(f).sum();
+                let rule: f64 = smooth_sum_rule(count, samples, symmetric)
+                    .into_iter()
+                    .map(|(j, weight)| weight * f(j))
+                    .sum();
+                assert!((rule / exact - 1.0).abs() < 1e-12, "{count} {symmetric}");
+            }
+        }
+    }
+
+    #[test]
+    fn pixel_window_quadrature_has_converged() {
+        for nside in [1, 2, 8] {
+            let lmax = 3 * nside as usize - 1;
+            let window = pixel_window(nside, lmax);
+            let finer = integrate_pixel_window(nside, lmax, QUADRATURE_ORDER + 4);
+            for (l, (w, f)) in window.iter().zip(finer).enumerate() {
+                assert!((w - f).abs() < 1e-10, "{nside} {l}: {w} {f}");
+            }
+        }
+    }
+
+    #[test]
+    fn pixel_window_deconvolution_divides_by_the_window() {
+        let healpix = DynamicHealpix::new(8).unwrap();
+        let (map, _) = band_limited(8, 6, 5);
+        let plain = healpix.anafast(&map, &options(6)).unwrap();
+        let options = SpectrumOptions {
+            pixel_window: true,
+            ..options(6)
+        };
+        let deconvolved = healpix.anafast(&map, &options).unwrap();
+        for ((d, c), w) in deconvolved.iter().zip(plain).zip(healpix.pixel_window(6)) {
+            assert_eq!(*d, c / (w * w));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::math;
+use crate::query_disc::max_pixel_radius;
+use crate::query_mode::QueryMode;
+use crate::query_strip::for_each_ring_in_zone;
+use alloc::vec::Vec;
+use core::f64::consts::{FRAC_PI_2, PI, TAU};
+
+/// Returns the sorted ring indices of the pixels whose centres lie in the RA/Dec box
+/// `[ra_min, ra_max] × [dec_min, dec_max]`, all in radians.
+///
+/// If `ra_min > ra_max` the RA range wraps through 0h.
+pub fn query_box_ring(
+    nside: i64,
+    ra_min: f64,
+    ra_max: f64,
+    dec_min: f64,
+    dec_max: f64,
+    mode: QueryMode,
+) -> Vec<u64> {
+    let margin = match mode {
+        QueryMode::Exact => 0.0,
+        QueryMode::Inclusive => max_pixel_radius(nside),
+    };
+    let full_circle = ra_max - ra_min >= TAU;
+    let width = math::rem_euclid(ra_max - ra_min, TAU);
+    let theta_north = (FRAC_PI_2 - dec_max - margin).max(0.0);
+    let theta_south = (FRAC_PI_2 - dec_min + margin).min(PI);
+    if theta_north > theta_south {
+        return Vec::new();
+    }
+
+    let (z_south, z_north) = (math::cos(theta_south), math::cos(theta_north));
+    let mut pixels = Vec::new();
+    for_each_ring_in_zone(nside, z_south, z_north, |info| {
+        let first = info.first_pixel as u64;
+        let count = info.pixel_count;
+
+        // Widen the RA range by the azimuth that spans `margin` on this ring.
+        let sin_theta = math::sqrt((1.0 - info.z * info.z).max(0.0));
+        let ratio = math::sin(margin) / sin_theta;
+        if full_circle || ratio >= 1.0 {
+            pixels.extend(first..first + count as u64);
+            return;
+        }
+        let dphi = math::asin(ratio);
+
+        let shift = if info.shifted { 0.5 } else { 0.0 };
+        let scale = count as f64 / TAU;
+        let lo = math::ceil((ra_min - dphi) * scale - shift) as i64;
+        let hi = math::floor((ra_min + width + dphi) * scale - shift) as i64;
+        if hi - lo + 1 >= count {
+            pixels.extend(first..first + count as u64);
+        } else {
+            pixels.extend((lo..=hi).map(|i| first + i.rem_euclid(count) as u64));
+        }
+    });
+    pixels.sort_unstable();
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DynamicHealpix, Healpix, Ring};
+
+    fn brute_force(nside: u32, ra_min: f64, ra_max: f64, dec_min: f64, dec_max: f64) -> Vec<u64> {
+        let healpix = DynamicHealpix::new(nside).unwrap();
+        healpix
+            .iter_pixels::<Ring>()
+            .filter(|p| {
+                let (theta, phi) = healpix.pixel_to_angle(*p).unwrap();
+                let dec = FRAC_PI_2 - theta;
+                let in_ra = if ra_min <= ra_max {
+                    ra_min <= phi && phi <= ra_max
+                } else {
+                    phi >= ra_min || phi <= ra_max
+                };
+                in_ra && dec_min <= dec && dec <= dec_max
+            })
+            .map(|p| p.as_u64())
+            .collect()
+    }
+
+    #[test]
+    fn box_matches_brute_force() {
+        let boxes = [
+            (0.5, 1.5, -0.3, 0.4),
+            (5.9, 0.4, -0.2, 0.2),
+            (1.0, 4.0, 1.0, 1.5),
+            (3.0, 3.5, -1.5, -1.0),
+        ];
+        for (ra_min, ra_max, dec_min, dec_max) in boxes {
+            let expected = brute_force(32, ra_min, ra_max, dec_min, dec_max);
+            let actual = query_box_ring(32, ra_min, ra_max, dec_min, dec_max, QueryMode::Exact);
+            assert!(!expected.is_empty());
+            assert_eq!(
+                actual,
+                expected,
+                "box {:?}",
+                (ra_min, ra_max, dec_min, dec_max)
+            );
+        }
+    }
+
+    #[test]
+    fn inclusive_box_covers_points_inside() {
+        let healpix = DynamicHealpix::new(32).unwrap();
+        let (ra_min, ra_max, dec_min, dec_max) = (6.0, 0.3, 0.1, 0.35);
+        let inclusive = query_box_ring(32, ra_min, ra_max, dec_min, dec_max, QueryMode::Inclusive);
+        for i in 0..=20 {
+            for j in 0..=20 {
+                let ra = ra_min + (ra_max + TAU - ra_min) * i as f64 / 20.0;
+                let dec = dec_min + (dec_max - dec_min) * j as f64 / 20.0;
+                let pixel = healpix.angle_to_pixel::<Ring>(FRAC_PI_2 - dec, ra);
+                assert!(inclusive.contains(&pixel.as_u64()));
+            }
+        }
+    }
+}
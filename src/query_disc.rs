@@ -0,0 +1,278 @@
+use crate::math;
+use crate::nested::Nested;
+use crate::query_mode::QueryMode;
+use crate::ring::Ring;
+use crate::vec3::Vec3;
+use alloc::vec::Vec;
+use core::f64::consts::{PI, TAU};
+use core::ops::Range;
+
+/// Returns the maximum angular distance between any pixel centre and the pixel's corners,
+/// which bounds the angular radius of every pixel at this resolution.
+pub fn max_pixel_radius(nside: i64) -> f64 {
+    let a = Vec3::from_z_phi(2.0 / 3.0, PI / (4 * nside) as f64);
+    let t = 1.0 - 1.0 / nside as f64;
+    let b = Vec3::from_z_phi(1.0 - t * t / 3.0, 0.0);
+    a.angle(&b)
+}
+
+/// Returns the threshold `dot` product must reach for two unit vectors to be at most
+/// `angle` apart. Angles outside `[0, pi]` map to thresholds that always or never pass.
+#[inline]
+pub fn cos_threshold(angle: f64) -> f64 {
+    if angle >= PI {
+        -2.0
+    } else if angle < 0.0 {
+        2.0
+    } else {
+        math::cos(angle)
+    }
+}
+
+/// Returns the radius pixel centres are tested against for the given query mode.
+#[inline]
+fn effective_radius(nside: i64, radius: f64, mode: QueryMode) -> f64 {
+    match mode {
+        QueryMode::Exact => radius,
+        QueryMode::Inclusive => radius + max_pixel_radius(nside),
+    }
+}
+
+/// Returns the sorted ring indices of the pixels selected by a disc query, walking the
+/// rings that intersect the disc.
+pub fn query_disc_ring(nside: i64, theta: f64, phi: f64, radius: f64, mode: QueryMode) -> Vec<u64> {
+    let npix = 12 * nside * nside;
+    let radius = effective_radius(nside, radius, mode);
+    if radius >= PI {
+        return (0..npix as u64).collect();
+    }
+    if radius < 0.0 {
+        return Vec::new();
+    }
+
+    let z0 = math::cos(theta);
+    let sin_theta0 = math::sin(theta);
+    let cos_radius = math::cos(radius);
+
+    let first_ring = if theta - radius <= 0.0 {
+        1
+    } else {
+        Ring::ring_above(nside, math::cos(theta - radius)) + 1
+    };
+    let last_ring = if theta + radius >= PI {
+        4 * nside - 1
+    } else {
+        Ring::ring_above(nside, math::cos(theta + radius))
+    };
+
+    let mut pixels = Vec::new();
+    for ring in first_ring..=last_ring {
+        let info = Ring::ring_info(nside, ring);
+        let first = info.first_pixel as u64;
+        let count = info.pixel_count;
+
+        let denominator = sin_theta0 * math::sqrt((1.0 - info.z * info.z).max(0.0));
+        let x = if denominator > 0.0 {
+            (cos_radius - info.z * z0) / denominator
+        } else if info.z * z0 >= cos_radius {
+            -1.0
+        } else {
+            2.0
+        };
+        if x > 1.0 {
+            continue;
+        }
+        if x <= -1.0 {
+            pixels.extend(first..first + count as u64);
+            continue;
+        }
+
+        let dphi = math::acos(x);
+        let shift = if info.shifted { 0.5 } else { 0.0 };
+        let scale = count as f64 / TAU;
+        let lo = math::ceil((phi - dphi) * scale - shift) as i64;
+        let hi = math::floor((phi + dphi) * scale - shift) as i64;
+        if hi - lo + 1 >= count {
+            pixels.extend(first..first + count as u64);
+        } else {
+            pixels.extend((lo..=hi).map(|i| first + i.rem_euclid(count) as u64));
+        }
+    }
+    pixels.sort_unstable();
+    pixels
+}
+
+/// Returns the sorted, non-overlapping nested index ranges of the pixels selected by a disc
+/// query, built by descending the quad-tree from the 12 base pixels.
+///
+/// Only valid for power-of-two `nside`.
+pub fn query_disc_nested_ranges(
+    nside: i64,
+    theta: f64,
+    phi: f64,
+    radius: f64,
+    mode: QueryMode,
+) -> Vec<Range<u64>> {
+    let depth = nside.trailing_zeros();
+    // Coarser pixels are pruned or accepted against the radius the leaves are tested with,
+    // so that inclusive queries keep every parent of a leaf they select.
+    let radius = effective_radius(nside, radius, mode);
+    let mut search = DiscSearch {
+        centre: Vec3::from_angle(theta, phi),
+        depth,
+        leaf_threshold: cos_threshold(radius),
+        outside_thresholds: [0.0; 32],
+        inside_thresholds: [0.0; 32],
+        ranges: Vec::new(),
+    };
+    for d in 0..=depth {
+        let pixel_radius = max_pixel_radius(1 << d);
+        search.outside_thresholds[d as usize] = cos_threshold(radius + pixel_radius);
+        search.inside_thresholds[d as usize] = cos_threshold(radius - pixel_radius);
+    }
+
+    for face in 0..12 {
+        search.descend(face, 0);
+    }
+    search.ranges
+}
+
+struct DiscSearch {
+    centre: Vec3,
+    depth: u32,
+    leaf_threshold: f64,
+    outside_thresholds: [f64; 32],
+    inside_thresholds: [f64; 32],
+    ranges: Vec<Range<u64>>,
+}
+
+impl DiscSearch {
+    fn descend(&mut self, pixel: u64, depth: u32) {
+        let nside = 1i64 << depth;
+        let hpd = Nested::nest2hpd(nside, pixel as i64);
+        let (z, phi) = Ring::hpd2loc(nside, &hpd);
+        let dot = Vec3::from_z_phi(z, phi).dot(&self.centre);
+
+        if depth == self.depth {
+            if dot >= self.leaf_threshold {
+                self.push(pixel..pixel + 1);
+            }
+            return;
+        }
+        if dot < self.outside_thresholds[depth as usize] {
+            return;
+        }
+        if dot >= self.inside_thresholds[depth as usize] {
+            let shift = 2 * (self.depth - depth);
+            self.push(pixel << shift..(pixel + 1) << shift);
+            return;
+        }
+        for child in 4 * pixel..4 * pixel + 4 {
+            self.descend(child, depth + 1);
+        }
+    }
+
+    fn push(&mut self, range: Range<u64>) {
+        match self.ranges.last_mut() {
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => self.ranges.push(range),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DynamicHealpix, Healpix, NumberingScheme, Pixel};
+
+    fn brute_force<N: NumberingScheme + Copy>(
+        nside: u32,
+        theta: f64,
+        phi: f64,
+        radius: f64,
+    ) -> Vec<u64> {
+        let healpix = DynamicHealpix::new(nside).unwrap();
+        let centre = Vec3::from_angle(theta, phi);
+        healpix
+            .iter_pixels::<N>()
+            .filter(|p| {
+                let (t, f) = healpix.pixel_to_angle(*p).unwrap();
+                Vec3::from_angle(t, f).angle(&centre) <= radius
+            })
+            .map(|p| p.as_u64())
+            .collect()
+    }
+
+    const DISCS: [(f64, f64, f64); 6] = [
+        (0.3, 1.0, 0.25),
+        (1.5, 0.0, 0.1),
+        (0.0, 0.0, 0.4),
+        (3.0, 5.9, 0.3),
+        (2.2, 6.2, 0.05),
+        (1.1, 3.3, 1.9),
+    ];
+
+    #[test]
+    fn ring_disc_matches_brute_force() {
+        for nside in [1, 4, 32] {
+            for (theta, phi, radius) in DISCS {
+                let expected = brute_force::<Ring>(nside, theta, phi, radius);
+                let actual = query_disc_ring(nside as i64, theta, phi, radius, QueryMode::Exact);
+                assert_eq!(
+                    actual,
+                    expected,
+                    "nside {} disc {:?}",
+                    nside,
+                    (theta, phi, radius)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nested_disc_matches_brute_force() {
+        for nside in [1, 4, 32] {
+            for (theta, phi, radius) in DISCS {
+                let expected = brute_force::<Nested>(nside, theta, phi, radius);
+                let actual: Vec<u64> =
+                    query_disc_nested_ranges(nside as i64, theta, phi, radius, QueryMode::Exact)
+                        .into_iter()
+                        .flatten()
+                        .collect();
+                assert_eq!(
+                    actual,
+                    expected,
+                    "nside {} disc {:?}",
+                    nside,
+                    (theta, phi, radius)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn inclusive_disc_contains_every_overlapping_pixel() {
+        let nside = 16;
+        let (theta, phi, radius) = (1.2, 2.0, 0.2);
+        let inclusive = query_disc_ring(nside, theta, phi, radius, QueryMode::Inclusive);
+
+        // Any pixel that has a point inside the disc also contains the pixel of that point.
+        let healpix = DynamicHealpix::new(nside as u32).unwrap();
+        for i in 0..200 {
+            let t = theta + radius * 0.99 * math::cos(i as f64 * 0.7);
+            let f = phi + radius * 0.99 * math::sin(i as f64 * 0.7) / math::sin(t);
+            if Vec3::from_angle(t, f).angle(&Vec3::from_angle(theta, phi)) <= radius {
+                let pixel: Pixel<Ring> = healpix.angle_to_pixel(t, f);
+                assert!(inclusive.contains(&pixel.as_u64()));
+            }
+        }
+    }
+
+    #[test]
+    fn nested_ranges_are_sorted_and_disjoint() {
+        let ranges = query_disc_nested_ranges(256, 1.0, 1.0, 0.3, QueryMode::Inclusive);
+        for pair in ranges.windows(2) {
+            assert!(pair[0].end < pair[1].start);
+        }
+    }
+}
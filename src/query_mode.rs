@@ -0,0 +1,13 @@
+/// Selects which pixels a region query returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueryMode {
+    /// Only pixels whose centre lies inside the region.
+    #[default]
+    Exact,
+
+    /// Every pixel that overlaps the region.
+    ///
+    /// Like the HEALPix reference implementation this is conservative: a few pixels
+    /// that only come close to the region may be returned as well.
+    Inclusive,
+}
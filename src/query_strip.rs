@@ -0,0 +1,131 @@
+use crate::math;
+use crate::numbering_scheme::NumberingScheme;
+use crate::pixel::Pixel;
+use crate::query_disc::max_pixel_radius;
+use crate::query_mode::QueryMode;
+use crate::ring::{Ring, RingInfo};
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+use core::ops::Range;
+
+/// Calls `f` with the layout of every ring whose centres have `cos(theta)` in `[z_min, z_max]`,
+/// from north to south.
+pub fn for_each_ring_in_zone(nside: i64, z_min: f64, z_max: f64, mut f: impl FnMut(&RingInfo)) {
+    let mut ring = Ring::ring_above(nside, z_max).max(1);
+    while ring < 4 * nside {
+        let info = Ring::ring_info(nside, ring);
+        if info.z < z_min {
+            break;
+        }
+        if info.z <= z_max {
+            f(&info);
+        }
+        ring += 1;
+    }
+}
+
+/// Returns the ring index ranges of the pixels whose centres lie between the colatitudes
+/// `theta1` and `theta2`.
+///
+/// If `theta1 > theta2` the strip wraps over the poles: the pixels north of `theta2` and south
+/// of `theta1` are returned. Every ring is a contiguous block in ring ordering, so the strip
+/// is at most two ranges.
+pub fn query_strip_ring_ranges(
+    nside: i64,
+    theta1: f64,
+    theta2: f64,
+    mode: QueryMode,
+) -> Vec<Range<u64>> {
+    let margin = match mode {
+        QueryMode::Exact => 0.0,
+        QueryMode::Inclusive => max_pixel_radius(nside),
+    };
+    let zones = if theta1 <= theta2 {
+        [Some((theta1 - margin, theta2 + margin)), None]
+    } else {
+        [Some((0.0, theta2 + margin)), Some((theta1 - margin, PI))]
+    };
+
+    let mut ranges: Vec<Range<u64>> = Vec::new();
+    for (north, south) in zones.into_iter().flatten() {
+        let z_max = math::cos(north.max(0.0));
+        let z_min = math::cos(south.min(PI));
+        for_each_ring_in_zone(nside, z_min, z_max, |info| {
+            let start = info.first_pixel as u64;
+            let end = start + info.pixel_count as u64;
+            match ranges.last_mut() {
+                Some(last) if last.end >= start => last.end = last.end.max(end),
+                _ => ranges.push(start..end),
+            }
+        });
+    }
+    ranges
+}
+
+/// Converts ring pixel indices to sorted pixels of scheme `N`.
+pub fn ring_to_scheme<N: NumberingScheme>(
+    face_resolution: u32,
+    pixels: impl IntoIterator<Item = u64>,
+) -> Vec<Pixel<N>> {
+    let mut result: Vec<Pixel<N>> = pixels
+        .into_iter()
+        .map(|p| {
+            let hpd = Ring::pixel_to_hpd(face_resolution, p);
+            Pixel::from_u64(N::hpd_to_pixel(face_resolution, hpd))
+        })
+        .collect();
+    result.sort_unstable_by_key(|p| p.as_u64());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DynamicHealpix, Healpix};
+
+    fn brute_force(nside: u32, theta1: f64, theta2: f64) -> Vec<u64> {
+        let healpix = DynamicHealpix::new(nside).unwrap();
+        healpix
+            .iter_pixels::<Ring>()
+            .filter(|p| {
+                let (theta, _) = healpix.pixel_to_angle(*p).unwrap();
+                if theta1 <= theta2 {
+                    theta1 <= theta && theta <= theta2
+                } else {
+                    theta <= theta2 || theta >= theta1
+                }
+            })
+            .map(|p| p.as_u64())
+            .collect()
+    }
+
+    #[test]
+    fn strip_matches_brute_force() {
+        for (theta1, theta2) in [(0.3, 0.9), (1.0, 2.5), (0.0, 0.2), (2.0, 1.0)] {
+            let expected = brute_force(16, theta1, theta2);
+            let ranges = query_strip_ring_ranges(16, theta1, theta2, QueryMode::Exact);
+            let actual: Vec<u64> = ranges.into_iter().flatten().collect();
+            assert_eq!(actual, expected, "strip {:?}", (theta1, theta2));
+        }
+    }
+
+    #[test]
+    fn strip_is_a_single_contiguous_range() {
+        let ranges = query_strip_ring_ranges(64, 0.5, 2.0, QueryMode::Inclusive);
+        assert_eq!(ranges.len(), 1);
+    }
+
+    #[test]
+    fn inclusive_strip_adds_the_bordering_rings() {
+        let exact: Vec<u64> = query_strip_ring_ranges(16, 1.0, 1.2, QueryMode::Exact)
+            .into_iter()
+            .flatten()
+            .collect();
+        let inclusive: Vec<u64> = query_strip_ring_ranges(16, 1.0, 1.2, QueryMode::Inclusive)
+            .into_iter()
+            .flatten()
+            .collect();
+        assert!(exact.iter().all(|p| inclusive.contains(p)));
+        assert!(inclusive.len() >= exact.len() + 2 * 64);
+    }
+}
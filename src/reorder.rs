@@ -0,0 +1,50 @@
+use crate::numbering_scheme::NumberingScheme;
+use alloc::vec;
+
+/// Permutes a map with one value per pixel from the ordering of scheme `F` to that of `T`,
+/// in place.
+///
+/// Each permutation cycle is applied with swaps, so only a bitset of visited pixels is
+/// allocated. `map.len()` must equal the number of pixels at `face_resolution`.
+pub fn reorder<F: NumberingScheme, T: NumberingScheme, V>(face_resolution: u32, map: &mut [V]) {
+    let target = |pixel: usize| {
+        let hpd = F::pixel_to_hpd(face_resolution, pixel as u64);
+        T::hpd_to_pixel(face_resolution, hpd) as usize
+    };
+
+    let mut visited = vec![0u64; map.len().div_ceil(64)];
+    for start in 0..map.len() {
+        if visited[start / 64] & (1 << (start % 64)) != 0 {
+            continue;
+        }
+        visited[start / 64] |= 1 << (start % 64);
+
+        let mut next = target(start);
+        while next != start {
+            map.swap(start, next);
+            visited[next / 64] |= 1 << (next % 64);
+            next = target(next);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Nested, Ring};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn reorder_moves_every_value_to_its_converted_pixel() {
+        let nside = 8;
+        let mut map: Vec<u64> = (0..12 * 64).collect();
+        reorder::<Nested, Ring, _>(nside, &mut map);
+        for (ring, nested) in map.iter().enumerate() {
+            let hpd = Nested::pixel_to_hpd(nside, *nested);
+            assert_eq!(Ring::hpd_to_pixel(nside, hpd), ring as u64);
+        }
+
+        reorder::<Ring, Nested, _>(nside, &mut map);
+        assert!(map.iter().enumerate().all(|(i, v)| i as u64 == *v));
+    }
+}
@@ -1,52 +1,407 @@
 use crate::hpd::Hpd;
-use crate::numbering_scheme::NumberingScheme;
+use crate::math;
+use crate::numbering_scheme::{NumberingScheme, checked_hpd};
 use crate::pixel::Pixel;
-use core::f64::consts::{PI, TAU};
+#[cfg(feature = "alloc")]
+use crate::query_disc::query_disc_ring;
+#[cfg(feature = "alloc")]
+use crate::query_mode::QueryMode;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::f64::consts::{FRAC_2_PI, FRAC_PI_2, PI, TAU};
 
 /// Precomputed constants from the original HEALPix C code.
 static JRLL: [i32; 12] = [2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4];
 static JPLL: [i32; 12] = [1, 3, 5, 7, 0, 2, 4, 6, 1, 3, 5, 7];
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Ring;
 
+/// Layout of a single iso-latitude ring in ring ordering.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct RingInfo {
+    /// Ring pixel index of the first pixel in the ring.
+    pub first_pixel: i64,
+    /// Number of pixels in the ring.
+    pub pixel_count: i64,
+    /// `cos(theta)` of every pixel centre in the ring.
+    pub z: f64,
+    /// Whether the first pixel centre is offset by half a pixel from `phi = 0`.
+    pub shifted: bool,
+}
+
 impl Ring {
-    #[inline]
-    fn north_polar(h: &Hpd, nside: f64) -> (f64, f64) {
-        let x = (h.x as f64 + 0.5) / nside;
-        let y = (h.y as f64 + 0.5) / nside;
-        let z = 1.0 - (x + y).powi(2) / 3.0;
-        let phi = (PI / 2.0) * (x - y) / (x + y);
-        (z, phi.rem_euclid(TAU))
+    /// Convert continuous face coordinates to `(z, phi, sin(theta))`, where `z = cos(theta)`,
+    /// with `sin(theta)` in the polar caps where `|z| > 0.99`.
+    ///
+    /// `x` and `y` run from 0 to 1 across the face, so the pixel `(h.x, h.y)` spans
+    /// `[h.x / nside, (h.x + 1) / nside]` in `x`. The region is chosen by the ring the point
+    /// lies on, not by its face, since the polar faces reach down to the equator. This follows
+    /// `xyf2loc` of the HEALPix C++ library, so boundary points are bit-for-bit the ones it
+    /// reports. Near the poles `z` rounds to ±1 at high resolutions, so `sin(theta)` is computed
+    /// from the distance to the pole there rather than from `z`. The pole itself has no
+    /// defined azimuth and maps to `phi = 0`.
+    pub(crate) fn face_to_loc_sth(face: i32, x: f64, y: f64) -> (f64, f64, Option<f64>) {
+        let face = face as usize;
+        let jr = JRLL[face] as f64 - x - y;
+        let (nr, z, sin_theta) = if jr < 1.0 {
+            // North polar cap
+            let tmp = jr * jr / 3.0;
+            let z = 1.0 - tmp;
+            (jr, z, (z > 0.99).then(|| math::sqrt(tmp * (2.0 - tmp))))
+        } else if jr > 3.0 {
+            // South polar cap
+            let nr = 4.0 - jr;
+            let tmp = nr * nr / 3.0;
+            let z = tmp - 1.0;
+            (nr, z, (z < -0.99).then(|| math::sqrt(tmp * (2.0 - tmp))))
+        } else {
+            // Equatorial region
+            (1.0, (2.0 - jr) * 2.0 / 3.0, None)
+        };
+
+        let mut tmp = JPLL[face] as f64 * nr + x - y;
+        if tmp < 0.0 {
+            tmp += 8.0;
+        }
+        if tmp >= 8.0 {
+            tmp -= 8.0;
+        }
+        let phi = if nr < 1e-15 {
+            0.0
+        } else {
+            (0.5 * FRAC_PI_2 * tmp) / nr
+        };
+        (z, phi, sin_theta)
+    }
+
+    /// Convert continuous face coordinates to `(z, phi)`, where `z = cos(theta)`. See
+    /// [`Ring::face_to_loc_sth`].
+    #[cfg(feature = "alloc")]
+    pub(crate) fn face_to_loc(face: i32, x: f64, y: f64) -> (f64, f64) {
+        let (z, phi, _) = Ring::face_to_loc_sth(face, x, y);
+        (z, phi)
+    }
+
+    /// Convert continuous face coordinates to `(theta, phi)`, keeping full precision near the
+    /// poles.
+    pub(crate) fn face_to_angle(face: i32, x: f64, y: f64) -> (f64, f64) {
+        let (z, phi, sin_theta) = Ring::face_to_loc_sth(face, x, y);
+        (Ring::loc_to_theta(z, sin_theta), phi)
+    }
+
+    /// Returns `theta` from `z = cos(theta)` and, in the polar caps, `sin(theta)`.
+    pub(crate) fn loc_to_theta(z: f64, sin_theta: Option<f64>) -> f64 {
+        match sin_theta {
+            Some(sin_theta) => math::atan2(sin_theta, z),
+            None => math::acos(z),
+        }
+    }
+
+    /// Returns the unit vector at `z = cos(theta)`, `phi` and, in the polar caps, `sin(theta)`.
+    pub(crate) fn loc_to_vec(z: f64, phi: f64, sin_theta: Option<f64>) -> [f64; 3] {
+        let sin_theta = sin_theta.unwrap_or_else(|| math::sqrt((1.0 - z) * (1.0 + z)));
+        [sin_theta * math::cos(phi), sin_theta * math::sin(phi), z]
+    }
+
+    /// Convert discrete face coordinates (`Hpd`) to the `(z, phi)` of the pixel centre,
+    /// where `z = cos(theta)`, and `sin(theta)` in the polar caps where `|z| > 0.99`.
+    ///
+    /// This follows `pix2loc` of the HEALPix C++ library for nested pixels, so the centres
+    /// are bit-for-bit the ones it reports for them. The azimuth is computed from integers as far
+    /// as possible, which keeps full precision next to `phi = 0`.
+    fn hpd_centre(nside: i64, h: &Hpd) -> (f64, f64, Option<f64>) {
+        let fact2 = 4.0 / (12 * nside * nside) as f64;
+        let fact1 = (2 * nside) as f64 * fact2;
+        let jr = JRLL[h.f as usize] as i64 * nside - h.x - h.y - 1;
+
+        let (nr, z, sin_theta) = if jr < nside {
+            // North polar cap
+            let tmp = (jr * jr) as f64 * fact2;
+            let z = 1.0 - tmp;
+            (jr, z, (z > 0.99).then(|| math::sqrt(tmp * (2.0 - tmp))))
+        } else if jr > 3 * nside {
+            // South polar cap
+            let nr = 4 * nside - jr;
+            let tmp = (nr * nr) as f64 * fact2;
+            let z = tmp - 1.0;
+            (nr, z, (z < -0.99).then(|| math::sqrt(tmp * (2.0 - tmp))))
+        } else {
+            // Equatorial region
+            (nside, (2 * nside - jr) as f64 * fact1, None)
+        };
+
+        let mut tmp = JPLL[h.f as usize] as i64 * nr + h.x - h.y;
+        if tmp < 0 {
+            tmp += 8 * nr;
+        } else if tmp >= 8 * nr {
+            tmp -= 8 * nr;
+        }
+        let phi = if nr == nside {
+            0.75 * FRAC_PI_2 * tmp as f64 * fact1
+        } else {
+            (0.5 * FRAC_PI_2 * tmp as f64) / nr as f64
+        };
+        (z, phi, sin_theta)
+    }
+
+    /// Convert discrete face coordinates (`Hpd`) to the `(z, phi)` of the pixel centre,
+    /// where `z = cos(theta)`.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn hpd2loc(nside: i64, h: &Hpd) -> (f64, f64) {
+        let (z, phi, _) = Ring::hpd_centre(nside, h);
+        (z, phi)
+    }
+
+    /// Convert discrete face coordinates (`Hpd`) to the `(theta, phi)` of the pixel centre.
+    pub(crate) fn hpd2ang(nside: i64, h: &Hpd) -> (f64, f64) {
+        let (z, phi, sin_theta) = Ring::hpd_centre(nside, h);
+        (Ring::loc_to_theta(z, sin_theta), phi)
+    }
+
+    /// Convert discrete face coordinates (`Hpd`) to the unit vector of the pixel centre.
+    pub(crate) fn hpd2vec(nside: i64, h: &Hpd) -> [f64; 3] {
+        let (z, phi, sin_theta) = Ring::hpd_centre(nside, h);
+        Ring::loc_to_vec(z, phi, sin_theta)
+    }
+
+    /// Convert a Ring pixel index to the `(z, phi)` of its centre, where `z = cos(theta)`, and
+    /// `sin(theta)` in the polar caps where `|z| > 0.99`.
+    ///
+    /// This follows `pix2loc` of the HEALPix C++ library for ring pixels, which counts the
+    /// azimuth along the ring rather than across the face as [`Ring::hpd_centre`] does, so the
+    /// centres are bit-for-bit the ones it reports for them.
+    fn ring_centre(nside: i64, pix: i64) -> (f64, f64, Option<f64>) {
+        let npix = 12 * nside * nside;
+        let ncap = 2 * nside * (nside - 1);
+        let fact2 = 4.0 / npix as f64;
+        let fact1 = (2 * nside) as f64 * fact2;
+
+        if pix < ncap {
+            // North polar cap
+            let iring = (1 + i64::isqrt(1 + 2 * pix)) >> 1;
+            let iphi = (pix + 1) - 2 * iring * (iring - 1);
+            let tmp = (iring * iring) as f64 * fact2;
+            let z = 1.0 - tmp;
+            let phi = (iphi as f64 - 0.5) * FRAC_PI_2 / iring as f64;
+            (z, phi, (z > 0.99).then(|| math::sqrt(tmp * (2.0 - tmp))))
+        } else if pix < npix - ncap {
+            // Equatorial region
+            let ip = pix - ncap;
+            let tmp = ip / (4 * nside);
+            let iring = tmp + nside;
+            let iphi = ip - 4 * nside * tmp + 1;
+            let fodd = if (iring + nside) & 1 == 1 { 1.0 } else { 0.5 };
+            let z = (2 * nside - iring) as f64 * fact1;
+            (z, (iphi as f64 - fodd) * PI * 0.75 * fact1, None)
+        } else {
+            // South polar cap
+            let ip = npix - pix;
+            let iring = (1 + i64::isqrt(2 * ip - 1)) >> 1;
+            let iphi = 4 * iring + 1 - (ip - 2 * iring * (iring - 1));
+            let tmp = (iring * iring) as f64 * fact2;
+            let z = tmp - 1.0;
+            let phi = (iphi as f64 - 0.5) * FRAC_PI_2 / iring as f64;
+            (z, phi, (z < -0.99).then(|| math::sqrt(tmp * (2.0 - tmp))))
+        }
+    }
+
+    /// Returns the layout of ring `ring`, counted from 1 at the north pole to `4 * nside - 1`.
+    pub(crate) fn ring_info(nside: i64, ring: i64) -> RingInfo {
+        let northern = if ring > 2 * nside {
+            4 * nside - ring
+        } else {
+            ring
+        };
+        let fact2 = 4.0 / (12 * nside * nside) as f64;
+
+        let (first_pixel, pixel_count, z, shifted) = if northern < nside {
+            // Polar cap
+            let z = 1.0 - (northern * northern) as f64 * fact2;
+            (2 * northern * (northern - 1), 4 * northern, z, true)
+        } else {
+            // Equatorial region
+            let z = (2 * nside - northern) as f64 * ((2 * nside) as f64 * fact2);
+            let first_pixel = 2 * nside * (nside - 1) + (northern - nside) * 4 * nside;
+            (first_pixel, 4 * nside, z, (northern - nside) & 1 == 0)
+        };
+
+        if northern == ring {
+            RingInfo {
+                first_pixel,
+                pixel_count,
+                z,
+                shifted,
+            }
+        } else {
+            RingInfo {
+                first_pixel: 12 * nside * nside - first_pixel - pixel_count,
+                pixel_count,
+                z: -z,
+                shifted,
+            }
+        }
+    }
+
+    /// Returns the index of the ring directly north of (or at) `z`, which is `0` above
+    /// the first ring.
+    pub(crate) fn ring_above(nside: i64, z: f64) -> i64 {
+        let za = z.abs();
+        if za <= 2.0 / 3.0 {
+            return (nside as f64 * (2.0 - 1.5 * z)) as i64;
+        }
+        let ring = (nside as f64 * math::sqrt(3.0 * (1.0 - za))) as i64;
+        if z > 0.0 { ring } else { 4 * nside - ring - 1 }
+    }
+
+    /// Convert `(theta, phi)` to the discrete face coordinates (`Hpd`) of the pixel containing
+    /// it.
+    pub(crate) fn ang2hpd(face_resolution: u32, theta: f64, phi: f64) -> Hpd {
+        let z = math::cos(theta);
+        // HEALPix C++ only switches to sin(theta) for theta < 0.01 and theta > 3.14159 - 0.01.
+        let sin_theta = (!(0.01..=3.13159).contains(&theta)).then(|| math::sin(theta));
+        Ring::loc2hpd(face_resolution, z, sin_theta, phi)
+    }
+
+    /// Convert a Cartesian vector, which need not be normalised, to the discrete face
+    /// coordinates (`Hpd`) of the pixel containing its direction.
+    pub(crate) fn vec2hpd(face_resolution: u32, [x, y, z]: [f64; 3]) -> Hpd {
+        let inverse_length = 1.0 / math::sqrt(x * x + y * y + z * z);
+        let z = z * inverse_length;
+        let phi = if x == 0.0 && y == 0.0 {
+            0.0
+        } else {
+            math::atan2(y, x)
+        };
+        let sin_theta = (z.abs() > 0.99).then(|| math::sqrt(x * x + y * y) * inverse_length);
+        Ring::loc2hpd(face_resolution, z, sin_theta, phi)
+    }
+
+    /// Convert `(z, phi)`, where `z = cos(theta)`, to the discrete face coordinates (`Hpd`)
+    /// of the pixel containing it.
+    ///
+    /// This follows `loc2pix` of the HEALPix C++ library operation for operation, so points
+    /// on a pixel edge land in the same pixel as in healpy. `sin_theta` is only used where
+    /// `|z| > 0.99`.
+    pub(crate) fn loc2hpd(face_resolution: u32, z: f64, sin_theta: Option<f64>, phi: f64) -> Hpd {
+        let nside = face_resolution as i64;
+        let nside_f = face_resolution as f64;
+        let za = z.abs();
+        let tt = fmodulo(phi * FRAC_2_PI, 4.0);
+
+        if za <= 2.0 / 3.0 {
+            // Equatorial region
+            let temp1 = nside_f * (0.5 + tt);
+            let temp2 = nside_f * z * 0.75;
+            let jp = (temp1 - temp2) as i64;
+            let jm = (temp1 + temp2) as i64;
+            let ifp = jp / nside;
+            let ifm = jm / nside;
+            let f = if ifp == ifm {
+                ifp | 4
+            } else if ifp < ifm {
+                ifp
+            } else {
+                ifm + 8
+            };
+            Hpd {
+                x: jm % nside,
+                y: nside - jp % nside - 1,
+                f: f as i32,
+            }
+        } else {
+            // Polar regions
+            let ntt = (tt as i32).min(3);
+            let tp = tt - ntt as f64;
+            let tmp = match sin_theta {
+                Some(sin_theta) if za >= 0.99 => nside_f * sin_theta / math::sqrt((1.0 + za) / 3.0),
+                _ => nside_f * math::sqrt(3.0 * (1.0 - za)),
+            };
+            let jp = ((tp * tmp) as i64).min(nside - 1);
+            let jm = (((1.0 - tp) * tmp) as i64).min(nside - 1);
+            if z > 0.0 {
+                Hpd {
+                    x: nside - jm - 1,
+                    y: nside - jp - 1,
+                    f: ntt,
+                }
+            } else {
+                Hpd {
+                    x: jp,
+                    y: jm,
+                    f: ntt + 8,
+                }
+            }
+        }
     }
 
-    #[inline]
-    fn equatorial(h: &Hpd, nside: f64) -> (f64, f64) {
-        let x = (h.x as f64 + 0.5) / nside;
-        let y = (h.y as f64 + 0.5) / nside;
-        let z = (2.0 / 3.0) * (2.0 - (x + y));
-        let phi = (PI / 2.0) * (x - y) + (h.f as f64 - 4.0) * (PI / 2.0);
-        (z, phi.rem_euclid(TAU))
+    /// Convert `(theta, phi)` to continuous face coordinates `(face, x, y)`, with `x` and `y`
+    /// running from 0 to 1 across the face as in [`Ring::face_to_loc`].
+    pub(crate) fn ang2face(theta: f64, phi: f64) -> (i32, f64, f64) {
+        let z = math::cos(theta);
+        let sin_theta = (z.abs() > 0.99).then(|| math::sin(theta));
+        Ring::loc2face(z, sin_theta, phi)
     }
 
-    #[inline]
-    fn south_polar(h: &Hpd, nside: f64) -> (f64, f64) {
-        let x = (h.x as f64 + 0.5) / nside;
-        let y = (h.y as f64 + 0.5) / nside;
-        let z = -1.0 + (x + y).powi(2) / 3.0;
-        let phi = (PI / 2.0) * (x - y) / (x + y);
-        (z, phi.rem_euclid(TAU))
+    /// Convert `(z, phi)`, where `z = cos(theta)`, to continuous face coordinates
+    /// `(face, x, y)`.
+    ///
+    /// Like the HEALPix C++ library, the distance from the pole is computed from `sin_theta`
+    /// when it is known, since `1 - |z|` loses all precision within a few pixels of the pole at
+    /// the deepest resolutions.
+    fn loc2face(z: f64, sin_theta: Option<f64>, phi: f64) -> (i32, f64, f64) {
+        let za = z.abs();
+        let phi = math::rem_euclid(phi, TAU);
+
+        if za <= 2.0 / 3.0 {
+            // Equatorial region
+            let tt = phi / (PI / 2.0);
+            let temp1 = 0.5 + tt;
+            let temp2 = z * 0.75;
+            let jp = temp1 - temp2;
+            let jm = temp1 + temp2;
+            let ifp = math::floor(jp) as u32;
+            let ifm = math::floor(jm) as u32;
+            let f = if ifp == ifm {
+                ifp | 4
+            } else if ifp < ifm {
+                ifp
+            } else {
+                ifm + 8
+            };
+            (f as i32, jm - ifm as f64, 1.0 + ifp as f64 - jp)
+        } else {
+            // Polar regions
+            let tt = phi / TAU * 4.0;
+            let ntt = (math::floor(tt) as i32).min(3);
+            let tp = tt - ntt as f64;
+            let tmp = match sin_theta {
+                Some(sin_theta) => sin_theta / math::sqrt((1.0 + za) / 3.0),
+                None => math::sqrt(3.0 * (1.0 - za)),
+            };
+            let jp = (tp * tmp).min(1.0);
+            let jm = ((1.0 - tp) * tmp).min(1.0);
+            if z >= 0.0 {
+                (ntt, 1.0 - jm, 1.0 - jp)
+            } else {
+                (ntt + 8, jp, jm)
+            }
+        }
     }
 
     /// Convert a discrete face-coordinate (`Hpd`) to a global ring pixel index.
     pub fn hpd2ring(nside: i64, h: Hpd) -> i64 {
+        debug_assert!(
+            (0..nside).contains(&h.x) && (0..nside).contains(&h.y) && (0..12).contains(&h.f),
+            "face coordinates out of range"
+        );
         let nl4 = 4 * nside;
         let jr = JRLL[h.f as usize] as i64 * nside - h.x - h.y - 1;
 
         if jr < nside {
             // North polar cap
             let mut jp = (JPLL[h.f as usize] as i64 * jr + h.x - h.y + 1) / 2;
-            if jp >= nl4 {
+            if jp > nl4 {
                 jp -= nl4;
             } else if jp < 1 {
                 jp += nl4;
@@ -56,7 +411,7 @@ impl Ring {
             // South polar cap
             let jr = nl4 - jr;
             let mut jp = (JPLL[h.f as usize] as i64 * jr + h.x - h.y + 1) / 2;
-            if jp >= nl4 {
+            if jp > nl4 {
                 jp -= nl4;
             } else if jp < 1 {
                 jp += nl4;
@@ -66,7 +421,7 @@ impl Ring {
             // Equatorial region
             let mut jp =
                 (JPLL[h.f as usize] as i64 * nside + h.x - h.y + 1 + ((jr - nside) & 1)) / 2;
-            if jp >= nl4 {
+            if jp > nl4 {
                 jp -= nl4;
             } else if jp < 1 {
                 jp += nl4;
@@ -103,8 +458,8 @@ impl Ring {
             let kshift = (iring + nside) & 1;
             let ire = iring - nside + 1;
             let irm = 2 * nside + 2 - ire;
-            let ifm = (iphi - irm / 2 + nside - 1) / nside;
-            let ifp = (iphi - ire / 2 + nside - 1) / nside;
+            let ifm = (iphi - ire / 2 + nside - 1) / nside;
+            let ifp = (iphi - irm / 2 + nside - 1) / nside;
             let face = if ifp == ifm {
                 ifp | 4
             } else if ifp < ifm {
@@ -142,74 +497,20 @@ impl Ring {
     }
 }
 
-impl NumberingScheme for Ring {
-    fn angle_to_pixel<N: NumberingScheme>(face_resolution: u32, theta: f64, phi: f64) -> Pixel<N> {
-        let z = theta.cos();
-        let za = z.abs();
-        let nside = face_resolution as f64;
-        let phi = phi.rem_euclid(TAU);
+/// `v1` modulo `v2`, in `[0, v2)`, computed like `fmodulo` in HEALPix C++.
+fn fmodulo(v1: f64, v2: f64) -> f64 {
+    if v1 >= 0.0 {
+        return if v1 < v2 { v1 } else { v1 % v2 };
+    }
+    let tmp = v1 % v2 + v2;
+    if tmp == v2 { 0.0 } else { tmp }
+}
 
-        let hpd = if za <= 2.0 / 3.0 {
-            // Equatorial region
-            let tt = phi / (PI / 2.0);
-            let temp1 = 0.5 + tt;
-            let temp2 = z * 0.75;
-            let jp = temp1 - temp2;
-            let jm = temp1 + temp2;
-            let ifp = jp.floor() as u32;
-            let ifm = jm.floor() as u32;
-            let f = if ifp == ifm {
-                ifp | 4
-            } else if ifp < ifm {
-                ifp
-            } else {
-                ifm + 8
-            };
-            let ix = ((jm - ifm as f64) * nside)
-                .floor()
-                .max(0.0)
-                .min(nside - 1.0) as i64;
-            let iy = ((1.0 + ifp as f64 - jp) * nside)
-                .floor()
-                .max(0.0)
-                .min(nside - 1.0) as i64;
-            Hpd {
-                x: ix,
-                y: iy,
-                f: f as i32,
-            }
-        } else {
-            // Polar regions
-            let mut tt = phi / TAU * 4.0;
-            if tt >= 4.0 {
-                tt = 3.999999;
-            }
-            let tmp = (3.0 * (1.0 - za)).sqrt();
-            let mut jp = tt * tmp;
-            let mut jm = (1.0 - tt) * tmp;
-            if jp > 1.0 {
-                jp = 1.0;
-            }
-            if jm > 1.0 {
-                jm = 1.0;
-            }
-            let (jp, jm) = if z >= 0.0 {
-                (1.0 - jm, 1.0 - jp)
-            } else {
-                (jp, jm)
-            };
-            let f = if z >= 0.0 {
-                tt.floor() as i32
-            } else {
-                (tt.floor() as i32) + 8
-            };
-            Hpd {
-                x: (jp * nside).floor().max(0.0).min(nside - 1.0) as i64,
-                y: (jm * nside).floor().max(0.0).min(nside - 1.0) as i64,
-                f,
-            }
-        };
+impl NumberingScheme for Ring {
+    const ORDERING: &'static str = "RING";
 
+    fn angle_to_pixel<N: NumberingScheme>(face_resolution: u32, theta: f64, phi: f64) -> Pixel<N> {
+        let hpd = Self::ang2hpd(face_resolution, theta, phi);
         Pixel::from_u64(Self::hpd2ring(face_resolution as i64, hpd) as u64)
     }
 
@@ -217,15 +518,112 @@ impl NumberingScheme for Ring {
         face_resolution: u32,
         pixel: Pixel<N>,
     ) -> crate::Result<(f64, f64)> {
-        let nside = face_resolution as i64;
-        let hpd = Self::ring2hpd(nside, pixel.as_u64() as i64);
-        let (z, phi) = if hpd.f < 4 {
-            Ring::north_polar(&hpd, nside as f64)
-        } else if hpd.f < 8 {
-            Ring::equatorial(&hpd, nside as f64)
-        } else {
-            Ring::south_polar(&hpd, nside as f64)
-        };
-        Ok((z.acos(), phi))
+        checked_hpd::<Self>(face_resolution, pixel.as_u64())?;
+        let (z, phi, sin_theta) = Self::ring_centre(face_resolution as i64, pixel.as_u64() as i64);
+        Ok((Self::loc_to_theta(z, sin_theta), phi))
+    }
+
+    fn pixel_to_hpd(face_resolution: u32, pixel: u64) -> Hpd {
+        Self::ring2hpd(face_resolution as i64, pixel as i64)
+    }
+
+    fn hpd_to_pixel(face_resolution: u32, hpd: Hpd) -> u64 {
+        Self::hpd2ring(face_resolution as i64, hpd) as u64
+    }
+
+    #[cfg(feature = "alloc")]
+    fn query_disc<N: NumberingScheme>(
+        face_resolution: u32,
+        theta: f64,
+        phi: f64,
+        radius: f64,
+        mode: QueryMode,
+    ) -> Vec<Pixel<N>> {
+        query_disc_ring(face_resolution as i64, theta, phi, radius, mode)
+            .into_iter()
+            .map(Pixel::from_u64)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_to_hpd_round_trips() {
+        for nside in [1i64, 2, 3, 4, 5, 6, 8, 32] {
+            for pix in 0..12 * nside * nside {
+                let hpd = Ring::ring2hpd(nside, pix);
+                assert!((0..nside).contains(&hpd.x) && (0..nside).contains(&hpd.y));
+                assert_eq!(Ring::hpd2ring(nside, hpd), pix, "nside {}", nside);
+            }
+        }
+    }
+
+    #[test]
+    fn ring_info_matches_pixel_centres() {
+        let nside = 8;
+        for ring in 1..4 * nside {
+            let info = Ring::ring_info(nside, ring);
+            for i in 0..info.pixel_count {
+                let pixel = Pixel::<Ring>::from_u64((info.first_pixel + i) as u64);
+                let (theta, phi) = Ring::pixel_to_angle(nside as u32, pixel).unwrap();
+                let offset = if info.shifted { 0.5 } else { 0.0 };
+                let expected_phi = (i as f64 + offset) * TAU / info.pixel_count as f64;
+                assert!((math::cos(theta) - info.z).abs() < 1e-12, "ring {}", ring);
+                assert!((phi - expected_phi).abs() < 1e-12, "ring {}", ring);
+            }
+        }
+    }
+
+    #[test]
+    fn deepest_resolution_round_trips() {
+        let nside = 1i64 << 29;
+        let npix = 12 * nside * nside;
+        let ncap = 2 * nside * (nside - 1);
+        for pix in [
+            0,
+            1,
+            5,
+            ncap - 1,
+            ncap,
+            npix / 2,
+            npix - ncap,
+            npix - 2,
+            npix - 1,
+        ] {
+            let hpd = Ring::ring2hpd(nside, pix);
+            assert_eq!(Ring::hpd2ring(nside, hpd), pix);
+
+            let pixel = Pixel::<Ring>::from_u64(pix as u64);
+            let (theta, phi) = Ring::pixel_to_angle(nside as u32, pixel).unwrap();
+            assert_eq!(
+                Ring::angle_to_pixel::<Ring>(nside as u32, theta, phi),
+                pixel
+            );
+        }
+        let beyond = Pixel::<Ring>::from_u64(npix as u64);
+        assert!(Ring::pixel_to_angle(nside as u32, beyond).is_err());
+    }
+
+    #[test]
+    fn polar_pixels_keep_their_colatitude() {
+        // The first ring lies at theta ≈ √(2/3) / nside, where cos(theta) rounds to 1.
+        let nside = 1u32 << 29;
+        let (theta, _) = Ring::pixel_to_angle(nside, Pixel::<Ring>::from_u64(0)).unwrap();
+        let expected = math::sqrt(2.0f64 / 3.0) / nside as f64;
+        assert!((theta / expected - 1.0).abs() < 1e-6, "{theta} {expected}");
+    }
+
+    #[test]
+    fn any_resolution_round_trips_through_angles() {
+        for nside in [3u32, 5, 6, 7] {
+            for pix in 0..12 * nside as u64 * nside as u64 {
+                let pixel = Pixel::<Ring>::from_u64(pix);
+                let (theta, phi) = Ring::pixel_to_angle(nside, pixel).unwrap();
+                assert_eq!(Ring::angle_to_pixel::<Ring>(nside, theta, phi), pixel);
+            }
+        }
     }
 }
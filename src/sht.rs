@@ -0,0 +1,340 @@
+use crate::alm::Alm;
+use crate::complex::Complex;
+use crate::healpix::Healpix;
+use crate::healpix_map::{HealpixMap, is_unseen};
+use crate::math;
+use crate::ring::Ring;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f64::consts::{PI, TAU};
+
+impl HealpixMap<f64, Ring> {
+    /// Computes the spherical harmonic coefficients of the map up to `lmax`, like healpy's
+    /// `map2alm`.
+    ///
+    /// The transform is a quadrature over the pixels of each ring, using one FFT per ring.
+    /// It is exact only for band-limited maps sampled finely enough, so `iterations` rounds of
+    /// Jacobi refinement can be added: each one synthesizes the current coefficients, and
+    /// analyses the residual. healpy uses 3 by default. `UNSEEN` and NaN pixels count as 0.
+    pub fn map2alm(&self, lmax: usize, iterations: usize) -> Alm {
+        map2alm(
+            self.face_resolution() as i64,
+            self.values(),
+            lmax,
+            iterations,
+        )
+    }
+}
+
+impl Alm {
+    /// Synthesizes the real map with these spherical harmonic coefficients at the resolution
+    /// of `healpix`, like healpy's `alm2map`.
+    pub fn alm2map(&self, healpix: &impl Healpix) -> HealpixMap<f64, Ring> {
+        let values = synthesis(healpix.face_resolution() as i64, self);
+        HealpixMap::from_vec(healpix, values).unwrap()
+    }
+}
+
+/// Computes the coefficients of a ring-ordered map. See [`HealpixMap::map2alm`].
+pub(crate) fn map2alm(nside: i64, values: &[f64], lmax: usize, iterations: usize) -> Alm {
+    let values: Vec<f64> = values
+        .iter()
+        .map(|v| if v.is_nan() || is_unseen(*v) { 0.0 } else { *v })
+        .collect();
+
+    let mut alm = analysis(nside, &values, lmax);
+    for _ in 0..iterations {
+        let synthesized = synthesis(nside, &alm);
+        let residual: Vec<f64> = values.iter().zip(synthesized).map(|(v, s)| v - s).collect();
+        let correction = analysis(nside, &residual, lmax);
+        for (a, c) in alm
+            .coefficients_mut()
+            .iter_mut()
+            .zip(correction.coefficients())
+        {
+            *a += *c;
+        }
+    }
+    alm
+}
+
+/// Returns the quadrature estimate of the coefficients of a ring-ordered map.
+fn analysis(nside: i64, values: &[f64], lmax: usize) -> Alm {
+    let mut alm = Alm::new(lmax);
+    let weight = 4.0 * PI / values.len() as f64;
+    let mut lambda = vec![0.0; Alm::size(lmax)];
+
+    for ring in 1..4 * nside {
+        let info = Ring::ring_info(nside, ring);
+        let first = info.first_pixel as usize;
+        let count = info.pixel_count as usize;
+        let spectrum = forward(&values[first..first + count], count.min(lmax + 1));
+        let phi0 = if info.shifted { PI / count as f64 } else { 0.0 };
+
+        legendre(lmax, info.z, &mut lambda);
+        let coefficients = alm.coefficients_mut();
+        for m in 0..=lmax {
+            // The first pixel sits at `phi0`, so shift the DFT to the azimuth origin.
+            let mode = spectrum[m % count] * Complex::cis(-(m as f64) * phi0) * weight;
+            let start = m * (2 * lmax + 1 - m) / 2;
+            for l in m..=lmax {
+                coefficients[start + l] += mode * lambda[start + l];
+            }
+        }
+    }
+    alm
+}
+
+/// Returns the ring-ordered map of the given coefficients.
+fn synthesis(nside: i64, alm: &Alm) -> Vec<f64> {
+    let lmax = alm.lmax();
+    let mut values = vec![0.0; (12 * nside * nside) as usize];
+    let mut lambda = vec![0.0; Alm::size(lmax)];
+
+    for ring in 1..4 * nside {
+        let info = Ring::ring_info(nside, ring);
+        let first = info.first_pixel as usize;
+        let count = info.pixel_count as usize;
+        let phi0 = if info.shifted { PI / count as f64 } else { 0.0 };
+
+        legendre(lmax, info.z, &mut lambda);
+        let mut spectrum = vec![Complex::ZERO; count];
+        for m in 0..=lmax {
+            let start = m * (2 * lmax + 1 - m) / 2;
+            let mut mode = Complex::ZERO;
+            for l in m..=lmax {
+                mode += alm.coefficients()[start + l] * lambda[start + l];
+            }
+            // Negative m contribute the complex conjugate, which doubles the real part.
+            let mode = if m == 0 {
+                Complex::from(mode.re)
+            } else {
+                mode * Complex::cis(m as f64 * phi0) * 2.0
+            };
+            spectrum[m % count] += mode;
+        }
+        backward(&spectrum, &mut values[first..first + count]);
+    }
+    values
+}
+
+/// Fills `lambda`, in the [`Alm`] storage order, with the orthonormal associated Legendre
+/// functions `λ_lm(z)`, so that `Y_lm(θ, φ) = λ_lm(cos θ) e^(imφ)`.
+fn legendre(lmax: usize, z: f64, lambda: &mut [f64]) {
+    let sin_theta = math::sqrt((1.0 - z * z).max(0.0));
+    let mut diagonal = math::sqrt(1.0 / (4.0 * PI));
+    for m in 0..=lmax {
+        if m > 0 {
+            diagonal *= -math::sqrt((2 * m + 1) as f64 / (2 * m) as f64) * sin_theta;
+        }
+        let start = m * (2 * lmax + 1 - m) / 2;
+        lambda[start + m] = diagonal;
+        if m == lmax {
+            break;
+        }
+        lambda[start + m + 1] = z * math::sqrt((2 * m + 3) as f64) * diagonal;
+        for l in m + 2..=lmax {
+            let (lf, mf) = (l as f64, m as f64);
+            let a = math::sqrt((4.0 * lf * lf - 1.0) / (lf * lf - mf * mf));
+            let b = math::sqrt(
+                ((lf - 1.0) * (lf - 1.0) - mf * mf) / (4.0 * (lf - 1.0) * (lf - 1.0) - 1.0),
+            );
+            lambda[start + l] = a * (z * lambda[start + l - 1] - b * lambda[start + l - 2]);
+        }
+    }
+}
+
+/// Returns the first `count` terms of the DFT `X_k = Σ_j x_j e^(-2πi jk/n)` of a ring.
+fn forward(values: &[f64], count: usize) -> Vec<Complex> {
+    let n = values.len();
+    if n.is_power_of_two() {
+        let mut data: Vec<Complex> = values.iter().map(|v| Complex::from(*v)).collect();
+        fft(&mut data, false);
+        data.truncate(count);
+        data
+    } else {
+        (0..count)
+            .map(|k| {
+                values
+                    .iter()
+                    .enumerate()
+                    .fold(Complex::ZERO, |sum, (j, v)| {
+                        sum + Complex::cis(-TAU * ((j * k) % n) as f64 / n as f64) * *v
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Writes the real part of the inverse DFT `x_j = Σ_k C_k e^(2πi jk/n)` of a ring to `values`.
+fn backward(spectrum: &[Complex], values: &mut [f64]) {
+    let n = values.len();
+    if n.is_power_of_two() {
+        let mut data = spectrum.to_vec();
+        fft(&mut data, true);
+        for (v, d) in values.iter_mut().zip(data) {
+            *v = d.re;
+        }
+    } else {
+        let terms: Vec<(usize, Complex)> = spectrum
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, c)| *c != Complex::ZERO)
+            .collect();
+        for (j, v) in values.iter_mut().enumerate() {
+            *v = terms
+                .iter()
+                .map(|(k, c)| (*c * Complex::cis(TAU * ((j * k) % n) as f64 / n as f64)).re)
+                .sum();
+        }
+    }
+}
+
+/// In-place radix-2 FFT, without normalization. `inverse` selects the `e^(+2πi jk/n)` kernel.
+fn fft(data: &mut [Complex], inverse: bool) {
+    let n = data.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut length = 2;
+    while length <= n {
+        let step = Complex::cis(sign * TAU / length as f64);
+        for chunk in data.chunks_exact_mut(length) {
+            let (low, high) = chunk.split_at_mut(length / 2);
+            let mut twiddle = Complex::new(1.0, 0.0);
+            for (a, b) in low.iter_mut().zip(high) {
+                let t = *b * twiddle;
+                *b = *a - t;
+                *a += t;
+                twiddle = twiddle * step;
+            }
+        }
+        length <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DynamicHealpix;
+
+    fn assert_close(actual: Complex, expected: Complex, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn fft_matches_the_direct_dft() {
+        let values: Vec<f64> = (0..16).map(|i| ((i * i) % 7) as f64 - 2.5).collect();
+        let fast = forward(&values, 16);
+        for (k, x) in fast.iter().enumerate() {
+            let direct = values
+                .iter()
+                .enumerate()
+                .fold(Complex::ZERO, |sum, (j, v)| {
+                    sum + Complex::cis(-TAU * (j * k) as f64 / 16.0) * *v
+                });
+            assert_close(*x, direct, 1e-12);
+        }
+
+        // Round trip through the direct path for a length that is not a power of two.
+        let values: Vec<f64> = (0..12).map(|i| math::sin(i as f64)).collect();
+        let spectrum = forward(&values, 12);
+        let mut restored = vec![0.0; 12];
+        backward(&spectrum, &mut restored);
+        for (r, v) in restored.iter().zip(&values) {
+            assert!((r / 12.0 - v).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn alm2map_matches_the_analytic_harmonics() {
+        let healpix = DynamicHealpix::new(8).unwrap();
+        let mut alm = Alm::new(2);
+        alm[(1, 0)] = Complex::new(1.0, 0.0);
+        alm[(1, 1)] = Complex::new(1.0, 0.0);
+
+        // Y_10 = sqrt(3/4π) cos θ and Y_11 = -sqrt(3/8π) sin θ e^(iφ), whose a_1(-1) term
+        // doubles the real part.
+        let map = alm.alm2map(&healpix);
+        for (pixel, value) in map.iter() {
+            let (theta, phi) = healpix.pixel_to_angle(pixel).unwrap();
+            let expected = math::sqrt(3.0 / (4.0 * PI)) * math::cos(theta)
+                - 2.0 * math::sqrt(3.0 / (8.0 * PI)) * math::sin(theta) * math::cos(phi);
+            assert!((value - expected).abs() < 1e-12, "{:?}", pixel);
+        }
+    }
+
+    #[test]
+    fn map2alm_recovers_band_limited_maps() {
+        let healpix = DynamicHealpix::new(16).unwrap();
+        let mut alm = Alm::new(8);
+        alm[(0, 0)] = Complex::new(2.0, 0.0);
+        alm[(2, 0)] = Complex::new(-1.0, 0.0);
+        alm[(3, 2)] = Complex::new(0.5, -0.25);
+        alm[(8, 7)] = Complex::new(0.0, 0.75);
+
+        let map = alm.alm2map(&healpix);
+        let once = map.map2alm(8, 0);
+        let refined = map.map2alm(8, 3);
+        for (i, expected) in alm.coefficients().iter().enumerate() {
+            assert_close(once.coefficients()[i], *expected, 1e-2);
+            assert_close(refined.coefficients()[i], *expected, 1e-6);
+        }
+    }
+
+    #[test]
+    fn transforms_work_at_any_ring_resolution() {
+        let healpix = DynamicHealpix::new_for::<Ring>(12).unwrap();
+        let mut alm = Alm::new(6);
+        alm[(1, 0)] = Complex::new(1.0, 0.0);
+        alm[(4, 3)] = Complex::new(-0.5, 0.25);
+
+        let map = alm.alm2map(&healpix);
+        assert_eq!(map.values().len(), 12 * 144);
+        let refined = map.map2alm(6, 3);
+        for (i, expected) in alm.coefficients().iter().enumerate() {
+            assert_close(refined.coefficients()[i], *expected, 1e-6);
+        }
+    }
+
+    #[test]
+    fn map2alm_of_a_constant_map_is_a_monopole() {
+        let healpix = DynamicHealpix::new(4).unwrap();
+        let map = HealpixMap::filled(&healpix, 1.0).unwrap();
+
+        // Equal pixel areas make the monopole exact, and the ring symmetries cancel the odd l
+        // and the 0 < m < 4 terms. Even l at m = 0 carry quadrature error until refined, and
+        // m = 4 aliases to m = 0 on the 4-pixel polar rings.
+        let alm = map.map2alm(4, 0);
+        assert_close(alm[(0, 0)], Complex::from(math::sqrt(4.0 * PI)), 1e-12);
+        for l in 1..=4 {
+            for m in 0..=l.min(3) {
+                if m > 0 || l % 2 == 1 {
+                    assert_close(alm[(l, m)], Complex::ZERO, 1e-12);
+                }
+            }
+        }
+
+        let refined = map.map2alm(4, 3);
+        for l in [2, 4] {
+            assert!(refined[(l, 0)].abs() < alm[(l, 0)].abs() / 10.0);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+/// Settings for estimating angular power spectra with [`crate::Healpix::anafast`].
+///
+/// The defaults match healpy's `anafast`: `lmax = 3 * nside - 1`, 3 Jacobi iterations, no
+/// mask and no pixel-window deconvolution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpectrumOptions<'a> {
+    /// The largest multipole to estimate, or `None` for `3 * nside - 1`.
+    pub lmax: Option<usize>,
+
+    /// Rounds of Jacobi refinement of the harmonic analysis, like healpy's `iter`.
+    pub iterations: usize,
+
+    /// A Ring-ordered weight per pixel, usually 0 or 1, multiplied into the maps. The spectrum
+    /// is divided by the mean squared weight, which is `f_sky` for a binary mask.
+    pub mask: Option<&'a [f64]>,
+
+    /// Divides the spectrum by the squared pixel window `w_l²`, removing the smoothing caused by
+    /// averaging the sky over pixels.
+    pub pixel_window: bool,
+}
+
+impl Default for SpectrumOptions<'_> {
+    fn default() -> Self {
+        Self {
+            lmax: None,
+            iterations: 3,
+            mask: None,
+            pixel_window: false,
+        }
+    }
+}
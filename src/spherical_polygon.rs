@@ -0,0 +1,307 @@
+use crate::math;
+use crate::numbering_scheme::NumberingScheme;
+use crate::pixel::Pixel;
+use crate::query_disc::max_pixel_radius;
+use crate::query_mode::QueryMode;
+use crate::ring::Ring;
+use crate::vec3::Vec3;
+use alloc::vec::Vec;
+use core::f64::consts::{FRAC_PI_2, PI, TAU};
+
+/// A simple polygon on the unit sphere whose vertices are joined by great-circle arcs.
+///
+/// Of the two regions bounded by the edges, the one with the smaller area is taken as the
+/// interior, so the winding order of the vertices does not matter.
+pub struct SphericalPolygon {
+    vertices: Vec<Vec3>,
+    reference: Vec3,
+    inside_is_left: bool,
+}
+
+impl SphericalPolygon {
+    /// Creates a polygon from its vertices, returning `None` if fewer than 3 distinct
+    /// vertices remain once consecutive duplicates (including a repeated closing vertex)
+    /// are dropped.
+    pub fn new(vertices: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+        let mut unique: Vec<Vec3> = Vec::new();
+        for v in vertices {
+            let v = v.normalized();
+            if unique.last().is_none_or(|last| last.angle(&v) > 1e-12) {
+                unique.push(v);
+            }
+        }
+        while unique.len() > 1 && unique[0].angle(&unique[unique.len() - 1]) <= 1e-12 {
+            unique.pop();
+        }
+        if unique.len() < 3 {
+            return None;
+        }
+
+        let n = unique.len();
+        let mut turning = 0.0;
+        for i in 0..n {
+            let prev = unique[(i + n - 1) % n];
+            let current = unique[i];
+            let next = unique[(i + 1) % n];
+            let incoming = prev.cross(&current).cross(&current);
+            let outgoing = current.cross(&next).cross(&current);
+            turning += math::atan2(
+                incoming.cross(&outgoing).dot(&current),
+                incoming.dot(&outgoing),
+            );
+        }
+        // Gauss-Bonnet: the region on the left of the edges has area 2π minus the total turning.
+        let left_area = TAU - turning;
+
+        // A point just to the left of the first edge lies in the left region.
+        let a = unique[0];
+        let b = unique[1];
+        let midpoint = (a + b).normalized();
+        let normal = a.cross(&b).normalized();
+        let reference = (midpoint + normal * 1e-9).normalized();
+
+        Some(Self {
+            vertices: unique,
+            reference,
+            inside_is_left: left_area <= TAU,
+        })
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| (self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+
+    /// Returns `true` if `point` lies inside the polygon.
+    pub fn contains(&self, point: &Vec3) -> bool {
+        let o = self.reference;
+        let crossings = if o.dot(point) > -0.5 {
+            self.crossings(&o, point)
+        } else {
+            // Avoid the ill-defined arc between nearly antipodal points by going through a
+            // point 90° from both ends.
+            let mut via = o.cross(point);
+            if via.length() < 1e-6 {
+                via = o.cross(&Vec3::new(o.y, o.z, o.x));
+            }
+            let via = via.normalized();
+            self.crossings(&o, &via) + self.crossings(&via, point)
+        };
+        (crossings % 2 == 0) == self.inside_is_left
+    }
+
+    fn crossings(&self, a: &Vec3, b: &Vec3) -> usize {
+        self.edges().filter(|(c, d)| arcs_cross(a, b, c, d)).count()
+    }
+
+    /// Returns the angular distance from `point` to the nearest point on the polygon edges.
+    pub fn distance_to_boundary(&self, point: &Vec3) -> f64 {
+        self.edges()
+            .map(|(a, b)| distance_to_arc(point, &a, &b))
+            .fold(PI, f64::min)
+    }
+
+    /// Returns a cap `(centre, radius)` that contains the whole polygon, or `None` if no cap
+    /// smaller than a hemisphere does.
+    pub fn bounding_cap(&self) -> Option<(Vec3, f64)> {
+        let sum = self.vertices.iter().fold(Vec3::ZERO, |sum, v| sum + *v);
+        if sum.length() < 1e-9 {
+            return None;
+        }
+        let centre = sum.normalized();
+        let radius = self
+            .vertices
+            .iter()
+            .map(|v| v.angle(&centre))
+            .fold(0.0, f64::max);
+
+        // Caps smaller than a hemisphere are convex, so they hold every edge. The rest of the
+        // sphere then lies on the larger side of the edges, which is never the interior.
+        (radius < FRAC_PI_2).then_some((centre, radius))
+    }
+}
+
+/// Returns the pixels of scheme `N` selected by `polygon`, sorted by pixel index.
+///
+/// Candidates come from a disc query around the polygon's bounding cap, falling back to the
+/// whole sphere for polygons larger than a hemisphere.
+pub fn query_polygon<N: NumberingScheme>(
+    face_resolution: u32,
+    polygon: &SphericalPolygon,
+    mode: QueryMode,
+) -> Vec<Pixel<N>> {
+    let nside = face_resolution as i64;
+    let pixel_radius = max_pixel_radius(nside);
+    let selected = |pixel: u64| {
+        let hpd = N::pixel_to_hpd(face_resolution, pixel);
+        let (z, phi) = Ring::hpd2loc(nside, &hpd);
+        let centre = Vec3::from_z_phi(z, phi);
+        polygon.contains(&centre)
+            || (mode == QueryMode::Inclusive
+                && polygon.distance_to_boundary(&centre) <= pixel_radius)
+    };
+
+    match polygon.bounding_cap() {
+        Some((centre, radius)) => {
+            let (theta, phi) = centre.to_angle();
+            N::query_disc::<N>(face_resolution, theta, phi, radius, mode)
+                .into_iter()
+                .filter(|pixel| selected(pixel.as_u64()))
+                .collect()
+        }
+        None => (0..12 * (nside * nside) as u64)
+            .filter(|pixel| selected(*pixel))
+            .map(Pixel::from_u64)
+            .collect(),
+    }
+}
+
+/// Returns `true` if the minor arcs `ab` and `cd` cross at a point interior to both.
+fn arcs_cross(a: &Vec3, b: &Vec3, c: &Vec3, d: &Vec3) -> bool {
+    let ab = a.cross(b);
+    let acb = -ab.dot(c);
+    let bda = ab.dot(d);
+    if acb * bda <= 0.0 {
+        return false;
+    }
+    let cd = c.cross(d);
+    let cbd = -cd.dot(b);
+    let dac = cd.dot(a);
+    acb * cbd > 0.0 && acb * dac > 0.0
+}
+
+/// Returns the angular distance from `p` to the minor arc `ab`.
+fn distance_to_arc(p: &Vec3, a: &Vec3, b: &Vec3) -> f64 {
+    let normal = a.cross(b);
+    let length = normal.length();
+    if length > 0.0 {
+        let normal = normal * (1.0 / length);
+        // The closest point of the great circle lies on the arc when it is between a and b.
+        let within = a.cross(p).dot(&normal) >= 0.0 && p.cross(b).dot(&normal) >= 0.0;
+        if within {
+            return math::asin(normal.dot(p).abs().min(1.0));
+        }
+    }
+    p.angle(a).min(p.angle(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DynamicHealpix, Healpix, Nested};
+
+    fn polygon(degrees: &[(f64, f64)]) -> SphericalPolygon {
+        SphericalPolygon::new(degrees.iter().map(|(ra, dec)| {
+            Vec3::from_angle(
+                core::f64::consts::FRAC_PI_2 - dec.to_radians(),
+                ra.to_radians(),
+            )
+        }))
+        .unwrap()
+    }
+
+    fn point(ra: f64, dec: f64) -> Vec3 {
+        Vec3::from_angle(
+            core::f64::consts::FRAC_PI_2 - dec.to_radians(),
+            ra.to_radians(),
+        )
+    }
+
+    fn brute_force(nside: u32, polygon: &SphericalPolygon) -> Vec<u64> {
+        let healpix = DynamicHealpix::new(nside).unwrap();
+        healpix
+            .iter_pixels::<Ring>()
+            .filter(|p| {
+                let (theta, phi) = healpix.pixel_to_angle(*p).unwrap();
+                polygon.contains(&Vec3::from_angle(theta, phi))
+            })
+            .map(|p| p.as_u64())
+            .collect()
+    }
+
+    #[test]
+    fn contains_handles_ra_wrap_and_winding_order() {
+        let square = [(350.0, -10.0), (10.0, -10.0), (10.0, 10.0), (350.0, 10.0)];
+        let reversed: Vec<_> = square.iter().rev().copied().collect();
+        for p in [polygon(&square), polygon(&reversed)] {
+            assert!(p.contains(&point(0.0, 0.0)));
+            assert!(p.contains(&point(355.0, 5.0)));
+            assert!(!p.contains(&point(180.0, 0.0)));
+            assert!(!p.contains(&point(20.0, 0.0)));
+        }
+    }
+
+    #[test]
+    fn contains_handles_polygons_around_a_pole() {
+        let cap = polygon(&[(0.0, 60.0), (90.0, 60.0), (180.0, 60.0), (270.0, 60.0)]);
+        assert!(cap.contains(&point(0.0, 90.0)));
+        assert!(cap.contains(&point(45.0, 70.0)));
+        assert!(!cap.contains(&point(45.0, 40.0)));
+        assert!(!cap.contains(&point(0.0, -90.0)));
+    }
+
+    #[test]
+    fn contains_handles_non_convex_polygons() {
+        // A "U" shape open to the north.
+        let u = polygon(&[
+            (0.0, 0.0),
+            (30.0, 0.0),
+            (30.0, 30.0),
+            (20.0, 30.0),
+            (20.0, 10.0),
+            (10.0, 10.0),
+            (10.0, 30.0),
+            (0.0, 30.0),
+        ]);
+        assert!(u.contains(&point(5.0, 20.0)));
+        assert!(u.contains(&point(25.0, 20.0)));
+        assert!(u.contains(&point(15.0, 5.0)));
+        assert!(!u.contains(&point(15.0, 20.0)));
+    }
+
+    #[test]
+    fn query_matches_brute_force() {
+        let polygons = [
+            polygon(&[(350.0, -10.0), (10.0, -10.0), (10.0, 10.0), (350.0, 10.0)]),
+            polygon(&[(0.0, 60.0), (90.0, 60.0), (180.0, 60.0), (270.0, 60.0)]),
+            polygon(&[
+                (0.0, 0.0),
+                (30.0, 0.0),
+                (30.0, 30.0),
+                (20.0, 30.0),
+                (20.0, 10.0),
+                (10.0, 10.0),
+                (10.0, 30.0),
+                (0.0, 30.0),
+            ]),
+        ];
+        for polygon in &polygons {
+            let expected = brute_force(32, polygon);
+            let actual: Vec<u64> = query_polygon::<Ring>(32, polygon, QueryMode::Exact)
+                .into_iter()
+                .map(|p| p.as_u64())
+                .collect();
+            assert!(!expected.is_empty());
+            assert_eq!(actual, expected);
+
+            let inclusive = query_polygon::<Ring>(32, polygon, QueryMode::Inclusive);
+            assert!(inclusive.len() > actual.len());
+            assert!(
+                actual
+                    .iter()
+                    .all(|p| inclusive.contains(&Pixel::from_u64(*p)))
+            );
+        }
+    }
+
+    #[test]
+    fn query_agrees_between_schemes() {
+        let polygon = polygon(&[(100.0, -70.0), (200.0, -80.0), (300.0, -60.0)]);
+        for mode in [QueryMode::Exact, QueryMode::Inclusive] {
+            let ring = query_polygon::<Ring>(16, &polygon, mode);
+            let nested = query_polygon::<Nested>(16, &polygon, mode);
+            assert!(!ring.is_empty());
+            assert_eq!(ring.len(), nested.len(), "{mode:?}");
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::grade_mode::GradeMode;
+use crate::healpix_map::{UNSEEN, is_unseen};
+use crate::map_value::MapValue;
+use crate::math;
+use alloc::vec::Vec;
+
+/// Changes the resolution of a [`crate::Nested`] map from `nside_in` to `nside_out`, both
+/// powers of two, using the quad-tree structure of the nested indices.
+///
+/// When degrading, each output pixel combines the `(nside_in / nside_out)^2` input pixels it
+/// covers, skipping `UNSEEN` and NaN values; pixels with no valid input become `UNSEEN`.
+/// When upgrading, each output pixel takes the value of the input pixel covering it, and
+/// `UNSEEN` and NaN pixels become `UNSEEN` children.
+pub fn ud_grade_nested<T: MapValue>(
+    map: &[T],
+    nside_in: u32,
+    nside_out: u32,
+    mode: GradeMode,
+) -> Vec<T> {
+    let scale = math::powf(nside_out as f64 / nside_in as f64, mode.power());
+    let unseen = T::from_f64(UNSEEN);
+
+    if nside_out <= nside_in {
+        let shift = 2 * (nside_in / nside_out).trailing_zeros();
+        map.chunks_exact(1 << shift)
+            .map(|children| {
+                let (sum, count) = children
+                    .iter()
+                    .map(|v| v.to_f64())
+                    .filter(|v| !v.is_nan() && !is_unseen(*v))
+                    .fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+                if count == 0 {
+                    unseen
+                } else {
+                    T::from_f64(sum / count as f64 * scale)
+                }
+            })
+            .collect()
+    } else {
+        let shift = 2 * (nside_out / nside_in).trailing_zeros();
+        (0..map.len() << shift)
+            .map(|pixel| {
+                let value = map[pixel >> shift].to_f64();
+                if value.is_nan() || is_unseen(value) {
+                    unseen
+                } else {
+                    T::from_f64(value * scale)
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degrade_skips_missing_values() {
+        let map = [1.0, 2.0, f64::NAN, UNSEEN, UNSEEN, f64::NAN, UNSEEN, UNSEEN];
+        let map = map.repeat(6);
+        let degraded = ud_grade_nested(&map, 2, 1, GradeMode::Mean);
+        assert_eq!(degraded.len(), 12);
+        assert_eq!(degraded[0], 1.5);
+        assert_eq!(degraded[1], UNSEEN);
+
+        let sum = ud_grade_nested(&map, 2, 1, GradeMode::Sum);
+        assert_eq!(sum[0], 6.0);
+    }
+
+    #[test]
+    fn upgrade_marks_the_children_of_missing_values_unseen() {
+        let mut map = [2.0f32; 12];
+        map[3] = f32::NAN;
+        map[7] = UNSEEN as f32;
+        for mode in [GradeMode::Mean, GradeMode::Sum] {
+            let upgraded = ud_grade_nested(&map, 1, 2, mode);
+            for (pixel, value) in upgraded.iter().enumerate() {
+                let parent = pixel / 4;
+                assert_eq!(
+                    is_unseen(*value as f64),
+                    parent == 3 || parent == 7,
+                    "pixel {pixel}: {value}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn upgrade_then_degrade_is_the_identity() {
+        let map: Vec<f32> = (0..48).map(|i| i as f32 * 0.25).collect();
+        for mode in [GradeMode::Mean, GradeMode::Sum, GradeMode::Power(2.0)] {
+            let upgraded = ud_grade_nested(&map, 2, 8, mode);
+            assert_eq!(upgraded.len(), 12 * 64);
+            assert_eq!(ud_grade_nested(&upgraded, 8, 2, mode), map);
+        }
+    }
+
+    #[test]
+    fn sum_preserves_the_total() {
+        let map: Vec<f64> = (0..192).map(|i| (i % 7) as f64).collect();
+        let total: f64 = map.iter().sum();
+        let degraded: f64 = ud_grade_nested(&map, 4, 1, GradeMode::Sum).iter().sum();
+        let upgraded: f64 = ud_grade_nested(&map, 4, 16, GradeMode::Sum).iter().sum();
+        assert_eq!(degraded, total);
+        assert!((upgraded - total).abs() < 1e-9);
+
+        let unseen = ud_grade_nested(&[UNSEEN; 12], 1, 2, GradeMode::Sum);
+        assert!(unseen.iter().all(|v| *v == UNSEEN));
+    }
+}
@@ -0,0 +1,141 @@
+use crate::hierarchical_pixel::HierarchicalPixel;
+use crate::nested::Nested;
+use crate::pixel::Pixel;
+
+/// A [`Nested`] pixel and its depth packed in one integer with the NUNIQ scheme,
+/// `uniq = 4 * 4^depth + pixel`.
+///
+/// Unlike a [`Pixel`], a NUNIQ value identifies a pixel across all resolutions, so pixels of
+/// different depths can share one column of a catalog or a MOC FITS file. Values sort by depth
+/// first and then by pixel, and all of them fit in an `i64`, so [`UniquePixel::as_u64`] can be
+/// used directly as a database key.
+///
+/// # Example
+/// ```rust
+/// use realpix::{Nested, Pixel, UniquePixel};
+///
+/// let uniq = UniquePixel::new(3, Pixel::<Nested>::from_u64(42)).unwrap();
+/// assert_eq!(uniq.as_u64(), 4 * 4u64.pow(3) + 42);
+/// assert_eq!(UniquePixel::from_u64(298).unwrap(), uniq);
+/// assert_eq!((uniq.depth(), uniq.pixel().as_u64()), (3, 42));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UniquePixel(u64);
+
+impl UniquePixel {
+    /// Encodes a pixel at the given depth.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidDepth`] if `depth` exceeds [`HierarchicalPixel::MAX_DEPTH`],
+    /// and [`crate::Error::InvalidPixel`] if the pixel does not exist at that depth.
+    pub fn new(depth: u8, pixel: Pixel<Nested>) -> crate::Result<Self> {
+        Ok(HierarchicalPixel::new(depth, pixel)?.into())
+    }
+
+    /// Wraps a NUNIQ value.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::InvalidPixel`] for values below 4, which encode no pixel, and
+    /// [`crate::Error::InvalidDepth`] for values deeper than [`HierarchicalPixel::MAX_DEPTH`].
+    pub fn from_u64(uniq: u64) -> crate::Result<Self> {
+        if uniq < 4 {
+            return Err(crate::Error::InvalidPixel);
+        }
+        if Self::depth_of(uniq) > HierarchicalPixel::MAX_DEPTH as u32 {
+            return Err(crate::Error::InvalidDepth);
+        }
+        Ok(Self(uniq))
+    }
+
+    /// Returns the NUNIQ value.
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the depth of the pixel.
+    pub fn depth(&self) -> u8 {
+        Self::depth_of(self.0) as u8
+    }
+
+    /// Returns the nested pixel at this pixel's depth.
+    pub fn pixel(&self) -> Pixel<Nested> {
+        Pixel::from_u64(self.0 - (4 << (2 * self.depth())))
+    }
+
+    /// Returns the depth encoded in a NUNIQ value of at least 4: the leading bit of
+    /// `4 * 4^depth + pixel` is bit `2 * depth + 2`.
+    fn depth_of(uniq: u64) -> u32 {
+        (63 - uniq.leading_zeros()) / 2 - 1
+    }
+}
+
+impl From<HierarchicalPixel> for UniquePixel {
+    fn from(pixel: HierarchicalPixel) -> Self {
+        Self((4 << (2 * pixel.depth())) + pixel.pixel().as_u64())
+    }
+}
+
+impl From<UniquePixel> for HierarchicalPixel {
+    fn from(uniq: UniquePixel) -> Self {
+        HierarchicalPixel::new(uniq.depth(), uniq.pixel())
+            .expect("a NUNIQ value always holds a valid pixel")
+    }
+}
+
+impl From<UniquePixel> for u64 {
+    fn from(uniq: UniquePixel) -> Self {
+        uniq.0
+    }
+}
+
+impl TryFrom<u64> for UniquePixel {
+    type Error = crate::Error;
+
+    fn try_from(uniq: u64) -> crate::Result<Self> {
+        Self::from_u64(uniq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_depth() {
+        for depth in 0..=HierarchicalPixel::MAX_DEPTH {
+            let last = (12u64 << (2 * depth)) - 1;
+            for index in [0, last / 3, last] {
+                let uniq = UniquePixel::new(depth, Pixel::from_u64(index)).unwrap();
+                assert_eq!(uniq.depth(), depth);
+                assert_eq!(uniq.pixel().as_u64(), index);
+                assert_eq!(UniquePixel::from_u64(uniq.as_u64()).unwrap(), uniq);
+
+                let hierarchical = HierarchicalPixel::from(uniq);
+                assert_eq!(UniquePixel::from(hierarchical), uniq);
+            }
+        }
+        let deepest = UniquePixel::new(29, Pixel::from_u64((12 << 58) - 1)).unwrap();
+        assert!(deepest.as_u64() <= i64::MAX as u64);
+    }
+
+    #[test]
+    fn sorts_by_depth_then_pixel() {
+        let uniq = |depth, index| UniquePixel::new(depth, Pixel::from_u64(index)).unwrap();
+        let mut values = [uniq(1, 0), uniq(0, 11), uniq(2, 5), uniq(0, 0), uniq(1, 47)];
+        values.sort();
+        assert_eq!(
+            values,
+            [uniq(0, 0), uniq(0, 11), uniq(1, 0), uniq(1, 47), uniq(2, 5)]
+        );
+        assert_eq!(uniq(0, 11).as_u64(), 15);
+        assert_eq!(uniq(1, 0).as_u64(), 16);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert!(UniquePixel::from_u64(3).is_err());
+        assert!(UniquePixel::from_u64(4 << 60).is_err());
+        assert!(UniquePixel::new(0, Pixel::from_u64(12)).is_err());
+        assert!(UniquePixel::try_from(0u64).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use crate::math;
+use core::ops::{Add, Mul, Neg};
+
+/// A cartesian vector, used internally for angular distance computations on the unit sphere.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Vec3 {
+    pub const ZERO: Vec3 = Vec3::new(0.0, 0.0, 0.0);
+
+    #[inline]
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Creates a unit vector from `z = cos(theta)` and the azimuth `phi`.
+    #[inline]
+    pub fn from_z_phi(z: f64, phi: f64) -> Self {
+        let sin_theta = math::sqrt((1.0 - z * z).max(0.0));
+        Self {
+            x: sin_theta * math::cos(phi),
+            y: sin_theta * math::sin(phi),
+            z,
+        }
+    }
+
+    /// Creates a unit vector from the colatitude `theta` and the azimuth `phi`.
+    #[inline]
+    pub fn from_angle(theta: f64, phi: f64) -> Self {
+        let sin_theta = math::sin(theta);
+        Self {
+            x: sin_theta * math::cos(phi),
+            y: sin_theta * math::sin(phi),
+            z: math::cos(theta),
+        }
+    }
+
+    /// Returns the colatitude `theta` and azimuth `phi` of this vector, with `phi` in `[0, 2π)`.
+    #[inline]
+    pub fn to_angle(self) -> (f64, f64) {
+        let theta = math::atan2(math::sqrt(self.x * self.x + self.y * self.y), self.z);
+        let phi = math::rem_euclid(math::atan2(self.y, self.x), core::f64::consts::TAU);
+        (theta, phi)
+    }
+
+    #[inline]
+    pub fn dot(&self, other: &Vec3) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    #[inline]
+    pub fn cross(&self, other: &Vec3) -> Vec3 {
+        Vec3 {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    #[inline]
+    pub fn length(&self) -> f64 {
+        math::sqrt(self.dot(self))
+    }
+
+    /// Returns this vector scaled to unit length.
+    #[inline]
+    pub fn normalized(&self) -> Vec3 {
+        *self * (1.0 / self.length())
+    }
+
+    /// Returns the angle between two vectors, in radians.
+    #[inline]
+    pub fn angle(&self, other: &Vec3) -> f64 {
+        math::atan2(self.cross(other).length(), self.dot(other))
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+
+    #[inline]
+    fn add(self, rhs: Vec3) -> Vec3 {
+        Vec3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    #[inline]
+    fn neg(self) -> Vec3 {
+        Vec3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl Mul<f64> for Vec3 {
+    type Output = Vec3;
+
+    #[inline]
+    fn mul(self, rhs: f64) -> Vec3 {
+        Vec3::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
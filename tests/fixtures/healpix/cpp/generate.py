@@ -0,0 +1,581 @@
+"""Generates the HEALPix C++ fixtures in this directory.
+
+Run with `python3 generate.py` from this directory. When healpy is installed, every value is
+computed by healpy, and so by the HEALPix C++ library it wraps, and the first line of each
+fixture records the healpy version. Otherwise the values come from `Base` below, a
+transcription of `T_Healpix_Base` (healpix_base.cc) written for these fixtures:
+`ang2pix`/`loc2pix`, `pix2ang`/`pix2loc`, `neighbors`, `get_ring_info`, `boundaries` and
+`get_interpol`, keeping the C++ order of operations on IEEE doubles. That transcription has
+not been checked against healpy or the C++ library, so those fixtures only show that the crate
+follows the algorithms as transcribed. The first line of each fixture says which source computed it;
+regenerate them wherever healpy is available.
+
+The sample points and pixels are chosen the same way in both cases. They include the points
+on pixel edges, at the poles, at z = ±2/3 and at phi = 0 and 2 pi, with the answer the
+source gives for them.
+"""
+
+import math
+import random
+import sys
+
+try:
+    import healpy
+except ImportError:
+    healpy = None
+
+HALFPI = math.pi / 2
+INV_HALFPI = 2 / math.pi
+TWOTHIRD = 2.0 / 3.0
+JRLL = [2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4]
+JPLL = [1, 3, 5, 7, 0, 2, 4, 6, 1, 3, 5, 7]
+ORDERS = list(range(0, 21))
+
+NB_XOFFSET = [-1, -1, 0, 1, 1, 1, 0, -1]
+NB_YOFFSET = [0, 1, 1, 1, 0, -1, -1, -1]
+NB_FACEARRAY = [
+    [8, 9, 10, 11, -1, -1, -1, -1, 10, 11, 8, 9],
+    [5, 6, 7, 4, 8, 9, 10, 11, 9, 10, 11, 8],
+    [-1, -1, -1, -1, 5, 6, 7, 4, -1, -1, -1, -1],
+    [4, 5, 6, 7, 11, 8, 9, 10, 11, 8, 9, 10],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+    [1, 2, 3, 0, 0, 1, 2, 3, 5, 6, 7, 4],
+    [-1, -1, -1, -1, 7, 4, 5, 6, -1, -1, -1, -1],
+    [3, 0, 1, 2, 3, 0, 1, 2, 4, 5, 6, 7],
+    [2, 3, 0, 1, -1, -1, -1, -1, 0, 1, 2, 3],
+]
+NB_SWAPARRAY = [
+    [0, 0, 3], [0, 0, 6], [0, 0, 0], [0, 0, 5], [0, 0, 0],
+    [5, 0, 0], [0, 0, 0], [6, 0, 0], [3, 0, 0],
+]
+
+
+def fmodulo(v1, v2):
+    if v1 >= 0:
+        return v1 if v1 < v2 else math.fmod(v1, v2)
+    tmp = math.fmod(v1, v2) + v2
+    return 0.0 if tmp == v2 else tmp
+
+
+def cdiv(a, b):
+    """C++ integer division, which truncates towards zero."""
+    q = abs(a) // abs(b)
+    return q if (a >= 0) == (b > 0) else -q
+
+
+class Base:
+    def __init__(self, order):
+        self.order = order
+        self.nside = 1 << order
+        self.npface = self.nside * self.nside
+        self.ncap = (self.npface - self.nside) << 1
+        self.npix = 12 * self.npface
+        self.fact2 = 4.0 / self.npix
+        self.fact1 = (self.nside << 1) * self.fact2
+
+    # --- index conversions -------------------------------------------------------------
+
+    def xyf2nest(self, ix, iy, face):
+        pixel = 0
+        for bit in range(32):
+            pixel |= ((ix >> bit) & 1) << (2 * bit)
+            pixel |= ((iy >> bit) & 1) << (2 * bit + 1)
+        return (face << (2 * self.order)) + pixel
+
+    def nest2xyf(self, pix):
+        face = pix >> (2 * self.order)
+        pix &= self.npface - 1
+        ix = iy = 0
+        for bit in range(32):
+            ix |= ((pix >> (2 * bit)) & 1) << bit
+            iy |= ((pix >> (2 * bit + 1)) & 1) << bit
+        return ix, iy, face
+
+    def ring_info_small(self, ring):
+        nside = self.nside
+        if ring < nside:
+            return 2 * ring * (ring - 1), 4 * ring, True
+        if ring < 3 * nside:
+            ringpix = 4 * nside
+            return self.ncap + (ring - nside) * ringpix, ringpix, ((ring - nside) & 1) == 0
+        nr = 4 * nside - ring
+        return self.npix - 2 * nr * (nr + 1), 4 * nr, True
+
+    def xyf2ring(self, ix, iy, face):
+        nl4 = 4 * self.nside
+        jr = JRLL[face] * self.nside - ix - iy - 1
+        n_before, nr, shifted = self.ring_info_small(jr)
+        nr >>= 2
+        kshift = 1 - shifted
+        jp = cdiv(JPLL[face] * nr + ix - iy + 1 + kshift, 2)
+        if jp < 1:
+            jp += nl4
+        return n_before + jp - 1
+
+    def ring2xyf(self, pix):
+        nside = self.nside
+        nl2 = 2 * nside
+        if pix < self.ncap:
+            iring = (1 + math.isqrt(1 + 2 * pix)) >> 1
+            iphi = (pix + 1) - 2 * iring * (iring - 1)
+            kshift = 0
+            nr = iring
+            face = (iphi - 1) // nr
+        elif pix < self.npix - self.ncap:
+            ip = pix - self.ncap
+            tmp = ip >> (self.order + 2)
+            iring = tmp + nside
+            iphi = ip - tmp * 4 * nside + 1
+            kshift = (iring + nside) & 1
+            nr = nside
+            ire = tmp + 1
+            irm = nl2 + 1 - tmp
+            ifm = (iphi - (ire >> 1) + nside - 1) >> self.order
+            ifp = (iphi - (irm >> 1) + nside - 1) >> self.order
+            face = (ifp | 4) if ifp == ifm else (ifp if ifp < ifm else ifm + 8)
+        else:
+            ip = self.npix - pix
+            iring = (1 + math.isqrt(2 * ip - 1)) >> 1
+            iphi = 4 * iring + 1 - (ip - 2 * iring * (iring - 1))
+            kshift = 0
+            nr = iring
+            iring = 2 * nl2 - iring
+            face = (iphi - 1) // nr + 8
+        irt = iring - ((2 + (face >> 2)) * nside) + 1
+        ipt = 2 * iphi - JPLL[face] * nr - kshift - 1
+        if ipt >= nl2:
+            ipt -= 8 * nside
+        return (ipt - irt) >> 1, (-ipt - irt) >> 1, face
+
+    def nest2ring(self, pix):
+        return self.xyf2ring(*self.nest2xyf(pix))
+
+    # --- angles ------------------------------------------------------------------------
+
+    def loc2pix(self, z, phi, sth, have_sth, ring):
+        nside = self.nside
+        za = abs(z)
+        tt = fmodulo(phi * INV_HALFPI, 4.0)
+        if ring:
+            if za <= TWOTHIRD:
+                nl4 = 4 * nside
+                temp1 = nside * (0.5 + tt)
+                temp2 = nside * z * 0.75
+                jp = int(temp1 - temp2)
+                jm = int(temp1 + temp2)
+                ir = nside + 1 + jp - jm
+                kshift = 1 - (ir & 1)
+                t1 = jp + jm - nside + kshift + 1 + nl4 + nl4
+                ip = (t1 >> 1) & (nl4 - 1) if self.order > 0 else (t1 >> 1) % nl4
+                return self.ncap + (ir - 1) * nl4 + ip
+            tp = tt - int(tt)
+            if za < 0.99 or not have_sth:
+                tmp = nside * math.sqrt(3 * (1 - za))
+            else:
+                tmp = nside * sth / math.sqrt((1.0 + za) / 3.0)
+            jp = int(tp * tmp)
+            jm = int((1.0 - tp) * tmp)
+            ir = jp + jm + 1
+            ip = int(tt * ir)
+            return 2 * ir * (ir - 1) + ip if z > 0 else self.npix - 2 * ir * (ir + 1) + ip
+        if za <= TWOTHIRD:
+            temp1 = nside * (0.5 + tt)
+            temp2 = nside * (z * 0.75)
+            jp = int(temp1 - temp2)
+            jm = int(temp1 + temp2)
+            ifp = jp >> self.order
+            ifm = jm >> self.order
+            face = (ifp | 4) if ifp == ifm else (ifp if ifp < ifm else ifm + 8)
+            ix = jm & (nside - 1)
+            iy = nside - (jp & (nside - 1)) - 1
+            return self.xyf2nest(ix, iy, face)
+        ntt = min(3, int(tt))
+        tp = tt - ntt
+        if za < 0.99 or not have_sth:
+            tmp = nside * math.sqrt(3 * (1 - za))
+        else:
+            tmp = nside * sth / math.sqrt((1.0 + za) / 3.0)
+        jp = min(int(tp * tmp), nside - 1)
+        jm = min(int((1.0 - tp) * tmp), nside - 1)
+        if z > 0:
+            return self.xyf2nest(nside - jm - 1, nside - jp - 1, ntt)
+        return self.xyf2nest(jp, jm, ntt + 8)
+
+    def ang2pix(self, theta, phi, ring):
+        z = math.cos(theta)
+        sth = math.sin(theta)
+        have_sth = theta < 0.01 or theta > 3.14159 - 0.01
+        return self.loc2pix(z, phi, sth if have_sth else 0.0, have_sth, ring)
+
+    def pix2loc(self, pix, ring):
+        nside = self.nside
+        have_sth = False
+        sth = 0.0
+        if ring:
+            if pix < self.ncap:
+                iring = (1 + math.isqrt(1 + 2 * pix)) >> 1
+                iphi = (pix + 1) - 2 * iring * (iring - 1)
+                tmp = (iring * iring) * self.fact2
+                z = 1.0 - tmp
+                if z > 0.99:
+                    sth, have_sth = math.sqrt(tmp * (2.0 - tmp)), True
+                phi = (iphi - 0.5) * HALFPI / iring
+            elif pix < self.npix - self.ncap:
+                nl4 = 4 * nside
+                ip = pix - self.ncap
+                tmp = ip >> (self.order + 2)
+                iring = tmp + nside
+                iphi = ip - nl4 * tmp + 1
+                fodd = 1 if ((iring + nside) & 1) else 0.5
+                z = (2 * nside - iring) * self.fact1
+                phi = (iphi - fodd) * math.pi * 0.75 * self.fact1
+            else:
+                ip = self.npix - pix
+                iring = (1 + math.isqrt(2 * ip - 1)) >> 1
+                iphi = 4 * iring + 1 - (ip - 2 * iring * (iring - 1))
+                tmp = (iring * iring) * self.fact2
+                z = tmp - 1.0
+                if z < -0.99:
+                    sth, have_sth = math.sqrt(tmp * (2.0 - tmp)), True
+                phi = (iphi - 0.5) * HALFPI / iring
+            return z, phi, sth, have_sth
+        ix, iy, face = self.nest2xyf(pix)
+        jr = (JRLL[face] << self.order) - ix - iy - 1
+        if jr < nside:
+            nr = jr
+            tmp = (nr * nr) * self.fact2
+            z = 1 - tmp
+            if z > 0.99:
+                sth, have_sth = math.sqrt(tmp * (2.0 - tmp)), True
+        elif jr > 3 * nside:
+            nr = nside * 4 - jr
+            tmp = (nr * nr) * self.fact2
+            z = tmp - 1
+            if z < -0.99:
+                sth, have_sth = math.sqrt(tmp * (2.0 - tmp)), True
+        else:
+            nr = nside
+            z = (2 * nside - jr) * self.fact1
+        tmp = JPLL[face] * nr + ix - iy
+        if tmp < 0:
+            tmp += 8 * nr
+        elif tmp >= 8 * nr:
+            tmp -= 8 * nr
+        phi = 0.75 * HALFPI * tmp * self.fact1 if nr == nside else (0.5 * HALFPI * tmp) / nr
+        return z, phi, sth, have_sth
+
+    def pix2ang(self, pix, ring):
+        z, phi, sth, have_sth = self.pix2loc(pix, ring)
+        return (math.atan2(sth, z) if have_sth else math.acos(z)), phi
+
+    # --- neighbours, rings and boundaries ----------------------------------------------
+
+    def neighbors(self, pix, ring):
+        nside = self.nside
+        ix, iy, face = self.ring2xyf(pix) if ring else self.nest2xyf(pix)
+        result = []
+        for i in range(8):
+            x = ix + NB_XOFFSET[i]
+            y = iy + NB_YOFFSET[i]
+            nbnum = 4
+            if x < 0:
+                x += nside
+                nbnum -= 1
+            elif x >= nside:
+                x -= nside
+                nbnum += 1
+            if y < 0:
+                y += nside
+                nbnum -= 3
+            elif y >= nside:
+                y -= nside
+                nbnum += 3
+            f = NB_FACEARRAY[nbnum][face]
+            if f < 0:
+                result.append(-1)
+                continue
+            bits = NB_SWAPARRAY[nbnum][face >> 2]
+            if bits & 1:
+                x = nside - x - 1
+            if bits & 2:
+                y = nside - y - 1
+            if bits & 4:
+                x, y = y, x
+            result.append(self.xyf2ring(x, y, f) if ring else self.xyf2nest(x, y, f))
+        return result
+
+    def ring_info(self, ring):
+        nside = self.nside
+        northring = 4 * nside - ring if ring > 2 * nside else ring
+        if northring < nside:
+            tmp = northring * northring * self.fact2
+            costheta = 1 - tmp
+            ringpix = 4 * northring
+            shifted = True
+            startpix = 2 * northring * (northring - 1)
+        else:
+            costheta = (2 * nside - northring) * self.fact1
+            ringpix = 4 * nside
+            shifted = ((northring - nside) & 1) == 0
+            startpix = self.ncap + (northring - nside) * ringpix
+        if northring != ring:
+            costheta = -costheta
+            startpix = self.npix - startpix - ringpix
+        return startpix, ringpix, costheta, shifted
+
+    def ring_info2(self, ring):
+        nside = self.nside
+        northring = 4 * nside - ring if ring > 2 * nside else ring
+        if northring < nside:
+            tmp = northring * northring * self.fact2
+            theta = math.atan2(math.sqrt(tmp * (2 - tmp)), 1 - tmp)
+            ringpix = 4 * northring
+            shifted = True
+            startpix = 2 * northring * (northring - 1)
+        else:
+            theta = math.acos((2 * nside - northring) * self.fact1)
+            ringpix = 4 * nside
+            shifted = ((northring - nside) & 1) == 0
+            startpix = self.ncap + (northring - nside) * ringpix
+        if northring != ring:
+            theta = math.pi - theta
+            startpix = self.npix - startpix - ringpix
+        return startpix, ringpix, theta, shifted
+
+    def ring_above(self, z):
+        az = abs(z)
+        if az <= TWOTHIRD:
+            return int(self.nside * (2 - 1.5 * z))
+        iring = int(self.nside * math.sqrt(3 * (1 - az)))
+        return iring if z > 0 else 4 * self.nside - iring - 1
+
+    def get_interpol(self, theta, phi):
+        """The RING pixels and weights of bilinear interpolation, for 0 <= phi < 2 pi."""
+        pix, wgt = [0] * 4, [0.0] * 4
+
+        def bracket(ring, slot):
+            sp, nr, ring_theta, shift = self.ring_info2(ring)
+            dphi = 2 * math.pi / nr
+            tmp = phi / dphi - 0.5 * shift
+            i1 = int(tmp) - 1 if tmp < 0 else int(tmp)
+            w1 = (phi - (i1 + 0.5 * shift) * dphi) / dphi
+            i2 = i1 + 1
+            if i1 < 0:
+                i1 += nr
+            if i2 >= nr:
+                i2 -= nr
+            pix[slot], pix[slot + 1] = sp + i1, sp + i2
+            wgt[slot], wgt[slot + 1] = 1 - w1, w1
+            return ring_theta
+
+        ir1 = self.ring_above(math.cos(theta))
+        ir2 = ir1 + 1
+        theta1 = bracket(ir1, 0) if ir1 > 0 else None
+        theta2 = bracket(ir2, 2) if ir2 < 4 * self.nside else None
+        if ir1 == 0:
+            wtheta = theta / theta2
+            wgt[2] *= wtheta
+            wgt[3] *= wtheta
+            fac = (1 - wtheta) * 0.25
+            wgt[0], wgt[1] = fac, fac
+            wgt[2] += fac
+            wgt[3] += fac
+            pix[0] = (pix[2] + 2) & 3
+            pix[1] = (pix[3] + 2) & 3
+        elif ir2 == 4 * self.nside:
+            wtheta = (theta - theta1) / (math.pi - theta1)
+            wgt[0] *= 1 - wtheta
+            wgt[1] *= 1 - wtheta
+            fac = wtheta * 0.25
+            wgt[0] += fac
+            wgt[1] += fac
+            wgt[2], wgt[3] = fac, fac
+            pix[2] = ((pix[0] + 2) & 3) + self.npix - 4
+            pix[3] = ((pix[1] + 2) & 3) + self.npix - 4
+        else:
+            wtheta = (theta - theta1) / (theta2 - theta1)
+            wgt[0] *= 1 - wtheta
+            wgt[1] *= 1 - wtheta
+            wgt[2] *= wtheta
+            wgt[3] *= wtheta
+        return pix, wgt
+
+    @staticmethod
+    def xyf2loc(x, y, face):
+        have_sth = False
+        sth = 0.0
+        jr = JRLL[face] - x - y
+        if jr < 1:
+            nr = jr
+            tmp = nr * nr / 3.0
+            z = 1 - tmp
+            if z > 0.99:
+                sth, have_sth = math.sqrt(tmp * (2.0 - tmp)), True
+        elif jr > 3:
+            nr = 4 - jr
+            tmp = nr * nr / 3.0
+            z = tmp - 1
+            if z < -0.99:
+                sth, have_sth = math.sqrt(tmp * (2.0 - tmp)), True
+        else:
+            nr = 1
+            z = (2 - jr) * 2.0 / 3.0
+        tmp = JPLL[face] * nr + x - y
+        if tmp < 0:
+            tmp += 8
+        if tmp >= 8:
+            tmp -= 8
+        phi = 0 if nr < 1e-15 else (0.5 * HALFPI * tmp) / nr
+        return z, phi, sth, have_sth
+
+    @staticmethod
+    def loc2vec(z, phi, sth, have_sth):
+        if not have_sth:
+            sth = math.sqrt((1.0 - z) * (1.0 + z))
+        return sth * math.cos(phi), sth * math.sin(phi), z
+
+    def boundaries(self, pix, step, ring):
+        ix, iy, face = self.ring2xyf(pix) if ring else self.nest2xyf(pix)
+        nside = self.nside
+        dc = 0.5 / nside
+        xc = (ix + 0.5) / nside
+        yc = (iy + 0.5) / nside
+        d = 1.0 / (step * nside)
+        out = [None] * (4 * step)
+        for i in range(step):
+            out[i] = self.loc2vec(*self.xyf2loc(xc + dc - i * d, yc + dc, face))
+            out[i + step] = self.loc2vec(*self.xyf2loc(xc - dc, yc + dc - i * d, face))
+            out[i + 2 * step] = self.loc2vec(*self.xyf2loc(xc - dc + i * d, yc - dc, face))
+            out[i + 3 * step] = self.loc2vec(*self.xyf2loc(xc + dc, yc - dc + i * d, face))
+        return out
+
+
+def special_points():
+    """Poles, the equator, the z = ±2/3 transitions and phi = 0 / 2 pi."""
+    thetas = [0.0, math.pi, math.pi / 2, math.acos(TWOTHIRD), math.acos(-TWOTHIRD)]
+    for base in (math.acos(TWOTHIRD), math.acos(-TWOTHIRD), math.pi / 2):
+        thetas += [base - 1e-7, base + 1e-7, base - 1e-12, base + 1e-12]
+    thetas += [1e-9, 0.005, 0.0099999, 0.0100001, math.pi - 1e-9, math.pi - 0.005]
+    phis = [0.0, 2 * math.pi, math.pi / 4, math.pi, 1.0, 2 * math.pi - 1e-9]
+    return [(t, p) for t in thetas for p in phis]
+
+
+class Healpy:
+    """The subset of `Base` used by `main`, computed by healpy."""
+
+    def __init__(self, nside):
+        self.nside = nside
+
+    def ang2pix(self, theta, phi, ring):
+        return int(healpy.ang2pix(self.nside, theta, phi, nest=not ring))
+
+    def pix2ang(self, pix, ring):
+        theta, phi = healpy.pix2ang(self.nside, pix, nest=not ring)
+        return float(theta), float(phi)
+
+    def neighbors(self, pix, ring):
+        return [int(n) for n in healpy.get_all_neighbours(self.nside, pix, nest=not ring)]
+
+    def nest2ring(self, pix):
+        return int(healpy.nest2ring(self.nside, pix))
+
+    def boundaries(self, pix, step, ring):
+        vectors = healpy.boundaries(self.nside, pix, step=step, nest=not ring)
+        return [tuple(float(c) for c in vectors[:, i]) for i in range(4 * step)]
+
+    def get_interpol(self, theta, phi):
+        pix, wgt = healpy.get_interp_weights(self.nside, theta, phi)
+        return [int(p) for p in pix], [float(w) for w in wgt]
+
+    def ring_info(self, ring):
+        start, count, costheta, _, shifted = healpy.ringinfo(self.nside, [ring])
+        return int(start[0]), int(count[0]), float(costheta[0]), bool(shifted[0])
+
+
+def pixel_sample(base, rng, ring):
+    """Pixels at the poles, the cap/equatorial transitions, the equator, face corners and random
+    positions."""
+    nside, npix = base.nside, base.npix
+    pixels = set(range(min(npix, 8))) | set(range(max(0, npix - 8), npix))
+    if ring:
+        for r in (nside - 1, nside, nside + 1, 2 * nside, 3 * nside - 1, 3 * nside, 3 * nside + 1):
+            if 1 <= r < 4 * nside:
+                start, count, _, _ = base.ring_info(r)
+                pixels |= {start, start + count - 1}
+    else:
+        for face in range(12):
+            for x, y in ((0, 0), (nside - 1, 0), (0, nside - 1), (nside - 1, nside - 1)):
+                pixels.add(base.xyf2nest(x, y, face))
+    pixels |= {rng.randrange(npix) for _ in range(8)}
+    return sorted(pixels)
+
+
+def main():
+    rng = random.Random(424242)
+    ang2pix, pix2ang, neighbours, ring_info, nest2ring, boundaries = [], [], [], [], [], []
+    interpolation = []
+    for order in ORDERS:
+        # `base` picks the samples, which only needs integer indexing; `source` computes
+        # every value that is written.
+        base = Base(order)
+        nside = base.nside
+        source = Healpy(nside) if healpy else base
+
+        points = special_points()
+        for i in range(24):
+            theta = math.acos(rng.uniform(-1, 1)) if i % 3 else rng.uniform(0, 0.02)
+            points.append((theta, rng.uniform(0, 2 * math.pi)))
+        for theta, phi in points:
+            nested = source.ang2pix(theta, phi, False)
+            ring = source.ang2pix(theta, phi, True)
+            ang2pix.append(f"{nside},{theta!r},{phi!r},{nested},{ring}")
+            # HEALPix C++ leaves phi = 2 pi unwrapped, which picks the next ring.
+            if phi < 2 * math.pi:
+                pix, wgt = source.get_interpol(theta, phi)
+                values = ",".join([str(p) for p in pix] + [repr(w) for w in wgt])
+                interpolation.append(f"{nside},{theta!r},{phi!r},{values}")
+
+        for ring, scheme in ((False, "NESTED"), (True, "RING")):
+            for pix in pixel_sample(base, rng, ring):
+                theta, phi = source.pix2ang(pix, ring)
+                pix2ang.append(f"{scheme},{nside},{pix},{theta!r},{phi!r}")
+                nbs = ",".join(str(n) for n in source.neighbors(pix, ring))
+                neighbours.append(f"{scheme},{nside},{pix},{nbs}")
+                if not ring:
+                    nest2ring.append(f"{nside},{pix},{source.nest2ring(pix)}")
+            for pix in pixel_sample(base, rng, ring)[:6]:
+                vectors = source.boundaries(pix, 2, ring)
+                vectors = ",".join(f"{c!r}" for v in vectors for c in v)
+                boundaries.append(f"{scheme},{nside},{pix},{vectors}")
+
+        rings = {1, 2, nside - 1, nside, nside + 1, 2 * nside, 3 * nside, 4 * nside - 1}
+        rings |= {rng.randrange(1, 4 * nside) for _ in range(4)}
+        for r in sorted(x for x in rings if 1 <= x < 4 * nside):
+            start, count, z, shifted = source.ring_info(r)
+            ring_info.append(f"{nside},{r},{start},{count},{z!r},{int(shifted)}")
+
+    files = {
+        "ang2pix.csv": ("nside,theta,phi,nested,ring", ang2pix),
+        "pix2ang.csv": ("scheme,nside,pixel,theta,phi", pix2ang),
+        "neighbours.csv": ("scheme,nside,pixel,sw,w,nw,n,ne,e,se,s (-1 if missing)", neighbours),
+        "nest2ring.csv": ("nside,nested,ring", nest2ring),
+        "ring_info.csv": ("nside,ring,first_pixel,pixel_count,z,shifted", ring_info),
+        "boundaries.csv": ("scheme,nside,pixel,x0,y0,z0,...,x7,y7,z7 (step 2)", boundaries),
+        "interpolation.csv": ("nside,theta,phi,p0,p1,p2,p3,w0,w1,w2,w3 (RING)", interpolation),
+    }
+    if healpy:
+        computed_by = f"healpy {healpy.__version__}"
+    else:
+        computed_by = (
+            "its unverified Python transcription of HEALPix C++, as healpy was not installed"
+        )
+        print("healpy is not installed; using the transcription of HEALPix C++", file=sys.stderr)
+    for name, (header, rows) in files.items():
+        with open(name, "w") as out:
+            out.write(f"# Generated by generate.py with {computed_by}.\n")
+            out.write(f"# {header}\n")
+            out.write("\n".join(rows) + "\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,89 @@
+"""Writes the HEALPix FITS map fixtures in this directory.
+
+Run with `python3 generate.py` from this directory. The files are written byte by byte by this
+script, not by healpy: they follow the binary-table layout of `healpy.write_map` (an empty
+primary HDU, then one table with the `PIXTYPE`, `ORDERING`, `NSIDE` and `INDXSCHM` keywords)
+with only the cards the reader needs.
+
+- `iqu_ring_nside2.fits` is an implicit RING map at NSIDE 2 with the columns `I_STOKES`,
+  `Q_STOKES` and `U_STOKES` holding `p`, `p / 2` and `-p` for pixel `p`.
+- `partial_nested_nside4.fits` is an explicit NESTED map at NSIDE 4 with four `PIXEL` and
+  `SIGNAL` rows, the last of them `BAD_DATA`.
+"""
+
+import struct
+
+BLOCK = 2880
+
+
+def card(keyword, value):
+    if isinstance(value, bool):
+        value = "%20s" % ("T" if value else "F")
+    elif isinstance(value, int):
+        value = "%20d" % value
+    elif isinstance(value, float):
+        value = "%20s" % ("%.6E" % value)
+    else:
+        value = "'%-8s'" % value
+    return ("%-8s= %s" % (keyword, value)).ljust(80)
+
+
+def unit(cards, data=b""):
+    header = "".join(cards) + "END".ljust(80)
+    header = header.ljust(-(-len(header) // BLOCK) * BLOCK).encode("ascii")
+    return header + data.ljust(-(-len(data) // BLOCK) * BLOCK, b"\0")
+
+
+def write(path, columns, keywords, rows, row_format):
+    primary = unit(
+        [card("SIMPLE", True), card("BITPIX", 8), card("NAXIS", 0), card("EXTEND", True)]
+    )
+    cards = [
+        card("XTENSION", "BINTABLE"),
+        card("BITPIX", 8),
+        card("NAXIS", 2),
+        card("NAXIS1", struct.calcsize(row_format)),
+        card("NAXIS2", len(rows)),
+        card("PCOUNT", 0),
+        card("GCOUNT", 1),
+        card("TFIELDS", len(columns)),
+    ]
+    for index, (name, form) in enumerate(columns, 1):
+        cards += [card("TTYPE%d" % index, name), card("TFORM%d" % index, form)]
+    cards += [card(keyword, value) for keyword, value in keywords]
+    data = b"".join(struct.pack(row_format, *row) for row in rows)
+    with open(path, "wb") as file:
+        file.write(primary + unit(cards, data))
+
+
+write(
+    "iqu_ring_nside2.fits",
+    [("I_STOKES", "E"), ("Q_STOKES", "E"), ("U_STOKES", "E")],
+    [
+        ("EXTNAME", "xtension"),
+        ("PIXTYPE", "HEALPIX"),
+        ("ORDERING", "RING"),
+        ("COORDSYS", "G"),
+        ("NSIDE", 2),
+        ("FIRSTPIX", 0),
+        ("LASTPIX", 47),
+        ("INDXSCHM", "IMPLICIT"),
+        ("OBJECT", "FULLSKY"),
+    ],
+    [(p, p / 2, -p) for p in range(48)],
+    ">fff",
+)
+write(
+    "partial_nested_nside4.fits",
+    [("PIXEL", "1J"), ("SIGNAL", "1E")],
+    [
+        ("PIXTYPE", "HEALPIX"),
+        ("ORDERING", "NESTED"),
+        ("NSIDE", 4),
+        ("INDXSCHM", "EXPLICIT"),
+        ("OBJECT", "PARTIAL"),
+        ("BAD_DATA", -1.6375e30),
+    ],
+    [(0, 1.5), (5, -2.0), (100, 3.25), (191, -1.6375e30)],
+    ">if",
+)
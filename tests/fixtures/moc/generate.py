@@ -0,0 +1,96 @@
+"""Writes the MOC fixtures in this directory.
+
+Run with `python3 generate.py` from this directory. The three files describe the same MOC of
+depth 8, made of the cells 1, 2, 3 and 9 at depth 3 and 40 and 41 at depth 4, in the three
+serializations of the IVOA MOC 1.1 recommendation. They are written by this script from that
+list of cells, following the recommendation directly: they were not produced by MOCPy, Aladin
+or any other MOC library, nor by this crate.
+
+- `small.txt` is the ASCII form, with consecutive cells written as ranges and the empty depth
+  8 closing the list.
+- `small.json` is the JSON form, one key per depth.
+- `small.fits` is the FITS form: an empty primary HDU, then a binary table with one `UNIQ`
+  column of 32-bit NUNIQ indices (`4 * 4^depth + pixel`), sorted, and the `PIXTYPE`,
+  `ORDERING`, `COORDSYS` and `MOCORDER` keywords.
+"""
+
+import struct
+
+BLOCK = 2880
+DEPTH = 8
+CELLS = {3: [1, 2, 3, 9], 4: [40, 41], DEPTH: []}
+
+
+def ascii_moc():
+    parts = []
+    for depth, pixels in sorted(CELLS.items()):
+        runs, start = [], None
+        for i, pixel in enumerate(pixels):
+            if start is None:
+                start = pixel
+            if i + 1 == len(pixels) or pixels[i + 1] != pixel + 1:
+                runs.append(str(start) if start == pixel else f"{start}-{pixel}")
+                start = None
+        parts.append(f"{depth}/" + " ".join(runs))
+    return " ".join(parts)
+
+
+def json_moc():
+    items = [f'"{depth}":[{",".join(map(str, pixels))}]' for depth, pixels in sorted(CELLS.items())]
+    return "{" + ",".join(items) + "}"
+
+
+def card(keyword, value):
+    if isinstance(value, bool):
+        value = "%20s" % ("T" if value else "F")
+    elif isinstance(value, int):
+        value = "%20d" % value
+    else:
+        value = "'%-8s'" % value
+    return ("%-8s= %s" % (keyword, value)).ljust(80)
+
+
+def unit(cards, data=b""):
+    header = "".join(cards) + "END".ljust(80)
+    header = header.ljust(-(-len(header) // BLOCK) * BLOCK).encode("ascii")
+    return header + data.ljust(-(-len(data) // BLOCK) * BLOCK, b"\0")
+
+
+def fits_moc():
+    uniq = sorted(4 * 4**depth + pixel for depth, pixels in CELLS.items() for pixel in pixels)
+    primary = unit(
+        [card("SIMPLE", True), card("BITPIX", 8), card("NAXIS", 0), card("EXTEND", True)]
+    )
+    table = unit(
+        [
+            card("XTENSION", "BINTABLE"),
+            card("BITPIX", 8),
+            card("NAXIS", 2),
+            card("NAXIS1", 4),
+            card("NAXIS2", len(uniq)),
+            card("PCOUNT", 0),
+            card("GCOUNT", 1),
+            card("TFIELDS", 1),
+            card("TTYPE1", "UNIQ"),
+            card("TFORM1", "1J"),
+            card("PIXTYPE", "HEALPIX"),
+            card("ORDERING", "NUNIQ"),
+            card("COORDSYS", "C"),
+            card("MOCORDER", DEPTH),
+        ],
+        b"".join(struct.pack(">i", u) for u in uniq),
+    )
+    return primary + table
+
+
+def main():
+    with open("small.txt", "w") as f:
+        f.write(ascii_moc() + "\n")
+    with open("small.json", "w") as f:
+        f.write(json_moc() + "\n")
+    with open("small.fits", "wb") as f:
+        f.write(fits_moc())
+
+
+if __name__ == "__main__":
+    main()